@@ -34,6 +34,24 @@ fn main() {
     println!("cargo:rustc-link-search={}", lib_path.display());
     println!("cargo:rustc-link-lib=static=MWCapture");
 
+    if env::var("CARGO_FEATURE_MP4").is_ok() {
+        fs::copy(
+            format!("{}/libmw_mp4.a", vendor_lib_path),
+            lib_path.join("libmw_mp4.a"),
+        )
+        .unwrap();
+        println!("cargo:rustc-link-lib=static=mw_mp4");
+    }
+
+    if env::var("CARGO_FEATURE_CC708").is_ok() {
+        fs::copy(
+            format!("{}/libmwcc708decoder.a", vendor_lib_path),
+            lib_path.join("libmwcc708decoder.a"),
+        )
+        .unwrap();
+        println!("cargo:rustc-link-lib=static=mwcc708decoder");
+    }
+
     println!("cargo:rustc-link-lib=stdc++");
 
     if env::var("CARGO_FEATURE_DEP_STUBS").is_ok() {
@@ -68,10 +86,41 @@ fn main() {
         .header("src/lib.hpp")
         .allowlist_function("MW.+")
         .allowlist_var("MW.+")
+        // Many of the SDK's enums and structs are only ever passed around as `DWORD`s or void
+        // pointers, so they need to be allowlisted explicitly.
+        .allowlist_type("_?MW.+")
         .generate()
         .expect("unable to generate bindings");
 
     bindings
         .write_to_file(out_path.join("bindings.rs"))
         .expect("unable to write bindings");
+
+    if env::var("CARGO_FEATURE_MP4").is_ok() {
+        bindgen::Builder::default()
+            .header(format!("{}/Include/mw_mp4/mw_mp4.h", SDK_PATH))
+            .allowlist_function("mw_mp4_.+")
+            .allowlist_type("_?mw_mp4_.+")
+            .generate()
+            .expect("unable to generate mp4 bindings")
+            .write_to_file(out_path.join("mp4_bindings.rs"))
+            .expect("unable to write mp4 bindings");
+    }
+
+    if env::var("CARGO_FEATURE_CC708").is_ok() {
+        bindgen::Builder::default()
+            .header(format!(
+                "{}/Include/LibMWClosedCaption/mw_cc708_decoder.h",
+                SDK_PATH
+            ))
+            // The header uses `bool` without including stdbool.h.
+            .clang_args(["-x", "c++"])
+            .allowlist_function("MW.+")
+            .allowlist_type("_?mw_cc708.+|_?cc708.+|_?CC_RESULT|MW_CC_RESULT")
+            .allowlist_var("I708_.+")
+            .generate()
+            .expect("unable to generate cc708 bindings")
+            .write_to_file(out_path.join("cc708_bindings.rs"))
+            .expect("unable to write cc708 bindings");
+    }
 }
@@ -0,0 +1,181 @@
+use super::{
+    AtcType, NotifyEvents, NotifyHandle, NotifyMonitor, ProEcoCaptureFamilyChannel, Result,
+    SdiAncPacket, Timecode,
+};
+use snafu::prelude::*;
+use std::time::Duration;
+
+/// The number of ANC packet types that can be captured at once.
+pub const MAX_ANC_SUBSCRIPTIONS: usize = 4;
+
+/// The DID/SDID pair identifying a type of SMPTE 291 ancillary data packet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AncId {
+    pub did: u8,
+    pub sdid: u8,
+}
+
+impl AncId {
+    /// SMPTE 2016-3 active format description and bar data.
+    pub const AFD: Self = Self::new(0x41, 0x05);
+    /// SMPTE 2010 SCTE-104 messages.
+    pub const SCTE_104: Self = Self::new(0x41, 0x07);
+    /// SMPTE 12-2 ancillary timecode.
+    pub const ATC: Self = Self::new(0x60, 0x60);
+    /// SMPTE 334 CEA-708 caption distribution packets.
+    pub const CEA_708: Self = Self::new(0x61, 0x01);
+    /// SMPTE 334 CEA-608 captions.
+    pub const CEA_608: Self = Self::new(0x61, 0x02);
+
+    pub const fn new(did: u8, sdid: u8) -> Self {
+        Self { did, sdid }
+    }
+}
+
+/// A type of ANC packet to capture from SDI inputs, and where to look for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AncSubscription {
+    pub id: AncId,
+    /// Whether to capture packets in the horizontal blanking interval.
+    pub hanc: bool,
+    /// Whether to capture packets in the vertical blanking interval.
+    pub vanc: bool,
+}
+
+impl AncSubscription {
+    /// Subscribes to packets in the vertical blanking interval, which is where most packet types,
+    /// including all of those in `AncId`, are carried.
+    pub fn vanc(id: AncId) -> Self {
+        Self {
+            id,
+            hanc: false,
+            vanc: true,
+        }
+    }
+}
+
+/// An ANC packet captured from an SDI input. The SDK's `MWCAP_SDI_ANC_PACKET` doesn't say which
+/// line the packet was carried on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AncPacket {
+    pub did: u8,
+    pub sdid: u8,
+    /// The user data words, truncated to 8 bits.
+    pub data: Vec<u8>,
+    /// The device time at which the packet was polled. The SDK doesn't timestamp packets, so all
+    /// of the packets drained by one poll share the same time.
+    pub timestamp: Duration,
+}
+
+impl AncPacket {
+    pub fn new(packet: &SdiAncPacket, timestamp: Duration) -> Self {
+        Self {
+            did: packet.did(),
+            sdid: packet.sdid(),
+            data: packet.data().to_vec(),
+            timestamp,
+        }
+    }
+
+    pub fn id(&self) -> AncId {
+        AncId::new(self.did, self.sdid)
+    }
+
+    /// Returns the active format description code of an AFD packet, along with whether the coded
+    /// frame is 16:9 rather than 4:3.
+    pub fn afd(&self) -> Option<(u8, bool)> {
+        if self.id() != AncId::AFD {
+            return None;
+        }
+        let b = *self.data.first()?;
+        Some(((b >> 3) & 0x0f, b & 0x04 != 0))
+    }
+
+    /// Returns the timecode carried by an ATC packet, along with whether it's LTC or VITC.
+    pub fn atc(&self) -> Option<(AtcType, Timecode)> {
+        if self.id() != AncId::ATC {
+            return None;
+        }
+        Timecode::from_atc(&self.data)
+    }
+}
+
+/// Captures the subscribed ANC packets from a channel's SDI input.
+pub struct AncMonitor {
+    handle: NotifyHandle,
+}
+
+impl AncMonitor {
+    /// Subscribes to up to `MAX_ANC_SUBSCRIPTIONS` packet types. Any previously captured packets
+    /// are discarded.
+    pub fn new<C: ProEcoCaptureFamilyChannel + ?Sized>(
+        channel: &C,
+        subscriptions: &[AncSubscription],
+    ) -> Result<Self> {
+        if subscriptions.len() > MAX_ANC_SUBSCRIPTIONS {
+            whatever!(
+                "at most {} anc packet types can be captured",
+                MAX_ANC_SUBSCRIPTIONS
+            );
+        }
+        for index in 0..MAX_ANC_SUBSCRIPTIONS {
+            channel.set_sdi_anc_type(index as _, subscriptions.get(index))?;
+        }
+        while channel.get_sdi_anc_packet()?.is_some() {}
+        let handle = channel.register_notify(NotifyEvents::NEW_SDI_ANC_PACKET)?;
+        Ok(Self { handle })
+    }
+}
+
+impl NotifyMonitor for AncMonitor {
+    type Output = Vec<AncPacket>;
+
+    /// Returns the packets received since the last call.
+    fn poll<C: ProEcoCaptureFamilyChannel + ?Sized>(
+        &mut self,
+        channel: &C,
+    ) -> Result<Vec<AncPacket>> {
+        let mut packets = Vec::new();
+        if channel.get_notify_status(self.handle)?.is_empty() {
+            return Ok(packets);
+        }
+        let timestamp = channel.get_device_time()?;
+        while let Some(packet) = channel.get_sdi_anc_packet()? {
+            packets.push(AncPacket::new(&packet, timestamp));
+        }
+        Ok(packets)
+    }
+
+    /// Unregisters the monitor and clears the subscriptions.
+    fn close<C: ProEcoCaptureFamilyChannel + ?Sized>(self, channel: &C) -> Result<()> {
+        channel.unregister_notify(self.handle)?;
+        for index in 0..MAX_ANC_SUBSCRIPTIONS {
+            channel.set_sdi_anc_type(index as _, None)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_afd() {
+        let packet = |id: AncId, data: Vec<u8>| AncPacket {
+            did: id.did,
+            sdid: id.sdid,
+            data,
+            timestamp: Duration::ZERO,
+        };
+        // AFD 8 (full frame) in a 16:9 coded frame.
+        assert_eq!(
+            packet(AncId::AFD, vec![0x44, 0, 0, 0, 0, 0, 0, 0]).afd(),
+            Some((8, true))
+        );
+        // AFD 10 (16:9 center) in a 4:3 coded frame.
+        assert_eq!(packet(AncId::AFD, vec![0x50]).afd(), Some((10, false)));
+        assert_eq!(packet(AncId::AFD, vec![]).afd(), None);
+        assert_eq!(packet(AncId::CEA_708, vec![0x44]).afd(), None);
+    }
+}
@@ -0,0 +1,223 @@
+use super::{Caption, Result};
+use snafu::prelude::*;
+use std::{io::Write, time::Duration};
+
+/// The sidecar format written by `CaptionWriter`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptionFormat {
+    WebVtt,
+    Srt,
+}
+
+/// Writes captions as a WebVTT or SRT sidecar.
+///
+/// Caption timestamps are device times, as are frame timestamps, so times in the file are
+/// relative to `origin`, which is typically the timestamp of the first frame of the recording.
+pub struct CaptionWriter<W: Write> {
+    out: W,
+    format: CaptionFormat,
+    origin: Duration,
+    frame_duration: Option<Duration>,
+    count: usize,
+}
+
+impl<W: Write> CaptionWriter<W> {
+    pub fn new(out: W, format: CaptionFormat, origin: Duration) -> Result<Self> {
+        let mut writer = Self {
+            out,
+            format,
+            origin,
+            frame_duration: None,
+            count: 0,
+        };
+        if format == CaptionFormat::WebVtt {
+            writer.write_str("WEBVTT\n\n")?;
+        }
+        Ok(writer)
+    }
+
+    /// Creates a WebVTT writer for an HLS segment, mapping `origin` to the given 90 kHz MPEG-TS
+    /// timestamp of the corresponding media segment.
+    pub fn new_hls_segment(out: W, origin: Duration, mpegts: u64) -> Result<Self> {
+        let mut writer = Self {
+            out,
+            format: CaptionFormat::WebVtt,
+            origin,
+            frame_duration: None,
+            count: 0,
+        };
+        writer.write_str(&format!(
+            "WEBVTT\nX-TIMESTAMP-MAP=MPEGTS:{},LOCAL:00:00:00.000\n\n",
+            mpegts
+        ))?;
+        Ok(writer)
+    }
+
+    /// Rounds caption times to the nearest frame boundary after `origin`, so that captions line
+    /// up with the frames they were received with.
+    pub fn align_to_frames(&mut self, frame_duration: Duration) {
+        self.frame_duration = Some(frame_duration).filter(|d| !d.is_zero());
+    }
+
+    /// Writes a caption. Captions that end before `origin` are skipped.
+    pub fn write(&mut self, caption: &Caption) -> Result<()> {
+        let (start, end) = (self.time(caption.start), self.time(caption.end));
+        if end <= start {
+            return Ok(());
+        }
+        self.count += 1;
+        let cue = match self.format {
+            CaptionFormat::WebVtt => format!(
+                "{} --> {}\n{}\n\n",
+                format_time(start, '.'),
+                format_time(end, '.'),
+                caption
+                    .text
+                    .replace('&', "&amp;")
+                    .replace('<', "&lt;")
+                    .replace('>', "&gt;")
+            ),
+            CaptionFormat::Srt => format!(
+                "{}\n{} --> {}\n{}\n\n",
+                self.count,
+                format_time(start, ','),
+                format_time(end, ','),
+                caption.text
+            ),
+        };
+        self.write_str(&cue)
+    }
+
+    /// Flushes and returns the underlying writer.
+    pub fn into_inner(mut self) -> Result<W> {
+        self.out
+            .flush()
+            .whatever_context("unable to flush captions")?;
+        Ok(self.out)
+    }
+
+    fn time(&self, timestamp: Duration) -> Duration {
+        let time = timestamp.saturating_sub(self.origin);
+        match self.frame_duration {
+            Some(frame_duration) => {
+                let frames =
+                    (time.as_nanos() + frame_duration.as_nanos() / 2) / frame_duration.as_nanos();
+                frame_duration * frames as u32
+            }
+            None => time,
+        }
+    }
+
+    fn write_str(&mut self, s: &str) -> Result<()> {
+        self.out
+            .write_all(s.as_bytes())
+            .whatever_context("unable to write captions")
+    }
+}
+
+fn format_time(time: Duration, decimal_separator: char) -> String {
+    let ms = time.as_millis();
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1000 % 60,
+        decimal_separator,
+        ms % 1000
+    )
+}
+
+/// Returns the parts of the captions that are displayed between `start` and `end`, e.g. for
+/// writing a segmented sidecar.
+pub fn clip_captions(captions: &[Caption], start: Duration, end: Duration) -> Vec<Caption> {
+    captions
+        .iter()
+        .filter(|caption| caption.start < end && caption.end > start)
+        .map(|caption| Caption {
+            start: caption.start.max(start),
+            end: caption.end.min(end),
+            text: caption.text.clone(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn caption(start_ms: u64, end_ms: u64, text: &str) -> Caption {
+        Caption {
+            start: Duration::from_millis(start_ms),
+            end: Duration::from_millis(end_ms),
+            text: text.to_string(),
+        }
+    }
+
+    fn write(mut writer: CaptionWriter<Vec<u8>>, captions: &[Caption]) -> String {
+        for caption in captions {
+            writer.write(caption).unwrap();
+        }
+        String::from_utf8(writer.into_inner().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_formats() {
+        let origin = Duration::from_secs(1000);
+        let captions = [
+            caption(999_000, 1_000_500, "BEFORE"),
+            caption(1_001_000, 1_003_500, "HELLO <WORLD>"),
+            caption(4_661_000, 4_662_001, "LINE ONE\nLINE TWO"),
+        ];
+
+        let writer = CaptionWriter::new(Vec::new(), CaptionFormat::WebVtt, origin).unwrap();
+        assert_eq!(
+            write(writer, &captions),
+            "WEBVTT\n\n\
+             00:00:00.000 --> 00:00:00.500\nBEFORE\n\n\
+             00:00:01.000 --> 00:00:03.500\nHELLO &lt;WORLD&gt;\n\n\
+             01:01:01.000 --> 01:01:02.001\nLINE ONE\nLINE TWO\n\n"
+        );
+
+        let writer = CaptionWriter::new(Vec::new(), CaptionFormat::Srt, origin).unwrap();
+        assert_eq!(
+            write(writer, &captions[1..]),
+            "1\n00:00:01,000 --> 00:00:03,500\nHELLO <WORLD>\n\n\
+             2\n01:01:01,000 --> 01:01:02,001\nLINE ONE\nLINE TWO\n\n"
+        );
+
+        let writer = CaptionWriter::new_hls_segment(Vec::new(), origin, 900_000).unwrap();
+        assert!(write(writer, &[])
+            .starts_with("WEBVTT\nX-TIMESTAMP-MAP=MPEGTS:900000,LOCAL:00:00:00.000\n\n"));
+    }
+
+    #[test]
+    fn test_align_to_frames() {
+        let mut writer =
+            CaptionWriter::new(Vec::new(), CaptionFormat::Srt, Duration::ZERO).unwrap();
+        writer.align_to_frames(Duration::from_millis(40));
+        assert_eq!(
+            write(
+                writer,
+                &[caption(1_019, 2_021, "A"), caption(3_001, 3_010, "B")]
+            ),
+            "1\n00:00:01,000 --> 00:00:02,040\nA\n\n"
+        );
+    }
+
+    #[test]
+    fn test_clip_captions() {
+        let captions = [
+            caption(0, 1_000, "A"),
+            caption(1_500, 6_500, "B"),
+            caption(6_500, 7_000, "C"),
+        ];
+        assert_eq!(
+            clip_captions(&captions, Duration::from_secs(2), Duration::from_secs(4)),
+            vec![caption(2_000, 4_000, "B")]
+        );
+        assert_eq!(
+            clip_captions(&captions, Duration::from_secs(6), Duration::from_secs(8)),
+            vec![caption(6_000, 6_500, "B"), caption(6_500, 7_000, "C")]
+        );
+    }
+}
@@ -0,0 +1,619 @@
+use super::{AncId, AncPacket, Rational, Result, Timecode};
+use snafu::prelude::*;
+use std::time::Duration;
+
+const CDP_IDENTIFIER: [u8; 2] = [0x96, 0x69];
+const CDP_HEADER_SIZE: usize = 7;
+
+const SECTION_TIME_CODE: u8 = 0x71;
+const SECTION_CC_DATA: u8 = 0x72;
+const SECTION_SVC_INFO: u8 = 0x73;
+const SECTION_FOOTER: u8 = 0x74;
+
+/// The type of a caption data triplet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CcType {
+    Cea608Field1,
+    Cea608Field2,
+    /// The continuation of a DTVCC (CEA-708) packet.
+    DtvccData,
+    /// The start of a DTVCC (CEA-708) packet.
+    DtvccStart,
+}
+
+/// A caption data triplet, carrying two bytes of CEA-608 or CEA-708 data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CcData {
+    pub valid: bool,
+    pub cc_type: CcType,
+    pub data: [u8; 2],
+}
+
+/// A SMPTE 334-2 caption distribution packet, as carried in ANC packets with `AncId::CEA_708`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cdp {
+    /// The frame rate of the video the captions belong to, if signaled.
+    pub frame_rate: Option<Rational>,
+    pub sequence_counter: u16,
+    pub timecode: Option<Timecode>,
+    pub cc_data: Vec<CcData>,
+}
+
+impl Cdp {
+    /// Parses a single CDP, verifying its checksum. Any bytes after the CDP are ignored.
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        if data.len() < CDP_HEADER_SIZE || data[..2] != CDP_IDENTIFIER {
+            whatever!("invalid cdp header");
+        }
+        let len = data[2] as usize;
+        if len < CDP_HEADER_SIZE || data.len() < len {
+            whatever!("cdp length {} exceeds the data size", len);
+        }
+        let data = &data[..len];
+        if data.iter().fold(0u8, |sum, b| sum.wrapping_add(*b)) != 0 {
+            whatever!("invalid cdp checksum");
+        }
+
+        let mut cdp = Self {
+            frame_rate: match data[3] >> 4 {
+                1 => Some(Rational::new(24000, 1001)),
+                2 => Some(Rational::new(24, 1)),
+                3 => Some(Rational::new(25, 1)),
+                4 => Some(Rational::new(30000, 1001)),
+                5 => Some(Rational::new(30, 1)),
+                6 => Some(Rational::new(50, 1)),
+                7 => Some(Rational::new(60000, 1001)),
+                8 => Some(Rational::new(60, 1)),
+                _ => None,
+            },
+            sequence_counter: u16::from_be_bytes([data[5], data[6]]),
+            timecode: None,
+            cc_data: Vec::new(),
+        };
+
+        let mut rest = &data[CDP_HEADER_SIZE..];
+        while let Some(&section) = rest.first() {
+            let size = match section {
+                SECTION_TIME_CODE => 5,
+                SECTION_CC_DATA => 2 + 3 * (*rest.get(1).unwrap_or(&0) & 0x1f) as usize,
+                SECTION_SVC_INFO => 2 + 7 * (*rest.get(1).unwrap_or(&0) & 0x0f) as usize,
+                SECTION_FOOTER => break,
+                // Future sections carry their length.
+                _ => 2 + *rest.get(1).unwrap_or(&0) as usize,
+            };
+            if rest.len() < size {
+                whatever!("cdp section {:#04x} is truncated", section);
+            }
+            let body = &rest[1..size];
+            match section {
+                SECTION_TIME_CODE => {
+                    // The drop frame flag is in the top bit rather than bit 6.
+                    let frames = (body[3] & 0x3f) | if body[3] & 0x80 != 0 { 0x40 } else { 0 };
+                    cdp.timecode =
+                        Timecode::from_bcd(frames, body[2], body[1] & 0x7f, body[0] & 0x3f);
+                }
+                SECTION_CC_DATA => {
+                    cdp.cc_data = body[1..]
+                        .chunks_exact(3)
+                        .map(|cc| CcData {
+                            valid: cc[0] & 0x04 != 0,
+                            cc_type: match cc[0] & 0x03 {
+                                0 => CcType::Cea608Field1,
+                                1 => CcType::Cea608Field2,
+                                2 => CcType::DtvccData,
+                                _ => CcType::DtvccStart,
+                            },
+                            data: [cc[1], cc[2]],
+                        })
+                        .collect();
+                }
+                _ => {}
+            }
+            rest = &rest[size..];
+        }
+        Ok(cdp)
+    }
+
+    /// Parses concatenated CDPs, e.g. as recorded from a stream.
+    pub fn parse_all(mut data: &[u8]) -> Result<Vec<Self>> {
+        let mut cdps = Vec::new();
+        while !data.is_empty() {
+            cdps.push(Self::parse(data)?);
+            data = &data[data[2] as usize..];
+        }
+        Ok(cdps)
+    }
+}
+
+/// A CEA-608 caption channel. CC1 and CC2 are carried in field 1, and CC3 and CC4 in field 2.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Cea608Channel {
+    #[default]
+    Cc1,
+    Cc2,
+    Cc3,
+    Cc4,
+}
+
+impl Cea608Channel {
+    fn cc_type(&self) -> CcType {
+        match self {
+            Self::Cc1 | Self::Cc2 => CcType::Cea608Field1,
+            Self::Cc3 | Self::Cc4 => CcType::Cea608Field2,
+        }
+    }
+
+    fn data_channel(&self) -> u8 {
+        match self {
+            Self::Cc1 | Self::Cc3 => 0,
+            Self::Cc2 | Self::Cc4 => 1,
+        }
+    }
+}
+
+/// Caption text that was displayed from `start` until `end`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Caption {
+    pub start: Duration,
+    pub end: Duration,
+    /// The displayed rows, separated by newlines.
+    pub text: String,
+}
+
+/// The caption that's currently displayed, along with when it appeared.
+#[derive(Debug, Default)]
+pub(crate) struct ShownCaption(Option<(String, Duration)>);
+
+impl ShownCaption {
+    /// Changes the displayed text, returning the caption it replaces, if any.
+    pub(crate) fn update(&mut self, text: String, timestamp: Duration) -> Option<Caption> {
+        if self.0.as_ref().map_or("", |(shown, _)| shown) == text {
+            return None;
+        }
+        let caption = self.flush(timestamp);
+        if !text.is_empty() {
+            self.0 = Some((text, timestamp));
+        }
+        caption
+    }
+
+    pub(crate) fn flush(&mut self, timestamp: Duration) -> Option<Caption> {
+        self.0.take().map(|(text, start)| Caption {
+            start,
+            end: timestamp,
+            text,
+        })
+    }
+}
+
+const ROWS: usize = 15;
+const COLUMNS: usize = 32;
+
+// The first row addressed by each preamble address code, indexed by the low bits of its first
+// byte.
+const PAC_ROWS: [usize; 8] = [10, 0, 2, 11, 13, 4, 6, 8];
+
+const SPECIAL_CHARS: [char; 16] = [
+    '®', '°', '½', '¿', '™', '¢', '£', '♪', 'à', '\u{a0}', 'è', 'â', 'ê', 'î', 'ô', 'û',
+];
+
+const EXTENDED_CHARS: [[char; 32]; 2] = [
+    [
+        'Á', 'É', 'Ó', 'Ú', 'Ü', 'ü', '‘', '¡', '*', '’', '—', '©', '℠', '•', '“', '”', 'À', 'Â',
+        'Ç', 'È', 'Ê', 'Ë', 'ë', 'Î', 'Ï', 'ï', 'Ô', 'Ù', 'ù', 'Û', '«', '»',
+    ],
+    [
+        'Ã', 'ã', 'Í', 'Ì', 'ì', 'Ò', 'ò', 'Õ', 'õ', '{', '}', '\\', '^', '_', '|', '~', 'Ä', 'ä',
+        'Ö', 'ö', 'ß', '¥', '¤', '│', 'Å', 'å', 'Ø', 'ø', '┌', '┐', '└', '┘',
+    ],
+];
+
+fn basic_char(b: u8) -> char {
+    match b {
+        0x2a => 'á',
+        0x5c => 'é',
+        0x5e => 'í',
+        0x5f => 'ó',
+        0x60 => 'ú',
+        0x7b => 'ç',
+        0x7c => '÷',
+        0x7d => 'Ñ',
+        0x7e => 'ñ',
+        0x7f => '█',
+        b => b as char,
+    }
+}
+
+#[derive(Clone)]
+struct Screen {
+    cells: [[Option<char>; COLUMNS]; ROWS],
+}
+
+impl Screen {
+    fn new() -> Self {
+        Self {
+            cells: [[None; COLUMNS]; ROWS],
+        }
+    }
+
+    fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    fn text(&self) -> String {
+        self.cells
+            .iter()
+            .map(|row| row.iter().map(|c| c.unwrap_or(' ')).collect::<String>())
+            .filter_map(|row| {
+                let row = row.trim();
+                (!row.is_empty()).then(|| row.to_string())
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    PopOn,
+    RollUp(usize),
+    PaintOn,
+}
+
+/// Decodes the CEA-608 captions carried in CDPs into timed captions, e.g. for WebVTT or SRT
+/// sidecars. Most CDPs carry CEA-608 captions alongside their CEA-708 services, which are decoded
+/// by `Cea708Decoder` when the `cc708` feature is enabled.
+///
+/// Pop-on and paint-on captions start and end when a control code changes what's displayed.
+/// Roll-up captions are sampled at each carriage return, so each caption shows the rows as they
+/// were once a line was complete.
+pub struct CaptionDecoder {
+    channel: Cea608Channel,
+    // whether the most recent control code selected our data channel
+    selected: bool,
+    // whether an extended data services packet is being received, which interrupts captions
+    in_xds: bool,
+    // whether the channel is in text mode, whose data isn't displayed
+    text_mode: bool,
+    last_control: Option<[u8; 2]>,
+    mode: Mode,
+    displayed: Screen,
+    non_displayed: Screen,
+    row: usize,
+    column: usize,
+    shown: ShownCaption,
+}
+
+impl CaptionDecoder {
+    pub fn new(channel: Cea608Channel) -> Self {
+        Self {
+            channel,
+            selected: false,
+            in_xds: false,
+            text_mode: false,
+            last_control: None,
+            mode: Mode::PopOn,
+            displayed: Screen::new(),
+            non_displayed: Screen::new(),
+            row: ROWS - 1,
+            column: 0,
+            shown: ShownCaption::default(),
+        }
+    }
+
+    /// Decodes a CDP captured at `timestamp`, returning any captions that were removed from the
+    /// screen.
+    pub fn push_cdp(&mut self, cdp: &Cdp, timestamp: Duration) -> Vec<Caption> {
+        let cc_type = self.channel.cc_type();
+        cdp.cc_data
+            .iter()
+            .filter(|cc| cc.valid && cc.cc_type == cc_type)
+            .filter_map(|cc| self.push_pair(cc.data, timestamp))
+            .collect()
+    }
+
+    /// Decodes the CDP carried by an ANC packet, using the packet's timestamp. Other packets are
+    /// ignored.
+    pub fn push_anc(&mut self, packet: &AncPacket) -> Result<Vec<Caption>> {
+        if packet.id() != AncId::CEA_708 {
+            return Ok(Vec::new());
+        }
+        Ok(self.push_cdp(&Cdp::parse(&packet.data)?, packet.timestamp))
+    }
+
+    /// Ends the caption that's currently displayed, if any, e.g. at the end of a recording.
+    pub fn flush(&mut self, timestamp: Duration) -> Option<Caption> {
+        self.shown.flush(timestamp)
+    }
+
+    fn push_pair(&mut self, pair: [u8; 2], timestamp: Duration) -> Option<Caption> {
+        // Strip the parity bits.
+        let (b1, b2) = (pair[0] & 0x7f, pair[1] & 0x7f);
+        if (0x01..=0x0f).contains(&b1) {
+            // Extended data services packets start with a class code and end with 0x0f, and the
+            // pairs in between aren't captions.
+            self.in_xds = b1 != 0x0f;
+            self.last_control = None;
+            return None;
+        }
+        if (0x10..=0x1f).contains(&b1) {
+            // Control codes also end extended data services packets.
+            self.in_xds = false;
+            // Control codes are usually sent twice in a row, in which case the second is ignored.
+            if self.last_control == Some([b1, b2]) {
+                self.last_control = None;
+                return None;
+            }
+            self.last_control = Some([b1, b2]);
+            self.selected = (b1 >> 3) & 1 == self.channel.data_channel();
+            if !self.selected {
+                return None;
+            }
+            return self.control(b1 & !0x08, b2, timestamp);
+        }
+        self.last_control = None;
+        // Pairs starting below 0x10 are padding.
+        if self.in_xds || !self.selected || b1 < 0x20 {
+            return None;
+        }
+        self.put_char(basic_char(b1));
+        if b2 >= 0x20 {
+            self.put_char(basic_char(b2));
+        }
+        None
+    }
+
+    fn control(&mut self, b1: u8, b2: u8, timestamp: Duration) -> Option<Caption> {
+        match (b1, b2) {
+            // Miscellaneous control codes, which use 0x15 in field 2.
+            (0x14 | 0x15, 0x20..=0x2f) => return self.command(b2, timestamp),
+            // Tab offsets.
+            (0x17, 0x21..=0x23) => {
+                self.column = (self.column + (b2 - 0x20) as usize).min(COLUMNS - 1)
+            }
+            // Mid-row codes change the style, which is displayed as a space.
+            (0x11, 0x20..=0x2f) => self.put_char(' '),
+            (0x11, 0x30..=0x3f) => self.put_char(SPECIAL_CHARS[(b2 - 0x30) as usize]),
+            // Extended characters replace the preceding character, which is a fallback for
+            // decoders that don't support them.
+            (0x12 | 0x13, 0x20..=0x3f) => {
+                self.backspace();
+                self.put_char(EXTENDED_CHARS[(b1 - 0x12) as usize][(b2 - 0x20) as usize]);
+            }
+            (_, 0x40..=0x7f) => self.preamble_address(b1, b2),
+            _ => {}
+        }
+        self.sample(timestamp)
+    }
+
+    fn command(&mut self, command: u8, timestamp: Duration) -> Option<Caption> {
+        match command {
+            // Resume caption loading.
+            0x20 => {
+                self.mode = Mode::PopOn;
+                self.text_mode = false;
+            }
+            0x21 => self.backspace(),
+            // Delete to end of row.
+            0x24 => {
+                let (row, column) = (self.row, self.column);
+                self.target().cells[row][column..].fill(None);
+            }
+            // Roll-up with 2, 3, or 4 rows.
+            0x25..=0x27 => {
+                if !matches!(self.mode, Mode::RollUp(_)) {
+                    self.displayed.clear();
+                    self.non_displayed.clear();
+                    self.row = ROWS - 1;
+                }
+                self.mode = Mode::RollUp((command - 0x23) as usize);
+                self.text_mode = false;
+                self.column = 0;
+            }
+            // Resume direct captioning.
+            0x29 => {
+                self.mode = Mode::PaintOn;
+                self.text_mode = false;
+            }
+            // Text restart and resume text display.
+            0x2a | 0x2b => self.text_mode = true,
+            // Erase displayed memory.
+            0x2c => {
+                self.displayed.clear();
+                return self.update_display(timestamp);
+            }
+            // Carriage return.
+            0x2d => {
+                if let Mode::RollUp(rows) = self.mode {
+                    let caption = self.update_display(timestamp);
+                    self.roll_up(rows);
+                    return caption;
+                }
+            }
+            // Erase non-displayed memory.
+            0x2e => self.non_displayed.clear(),
+            // End of caption.
+            0x2f => {
+                std::mem::swap(&mut self.displayed, &mut self.non_displayed);
+                self.mode = Mode::PopOn;
+                self.text_mode = false;
+            }
+            _ => {}
+        }
+        self.sample(timestamp)
+    }
+
+    fn preamble_address(&mut self, b1: u8, b2: u8) {
+        let row = PAC_ROWS[(b1 & 0x07) as usize] + ((b2 & 0x20 != 0) as usize);
+        if row >= ROWS {
+            return;
+        }
+        if let Mode::RollUp(rows) = self.mode {
+            // The roll-up window moves along with its base row.
+            if row != self.row {
+                let old = self.displayed.clone();
+                self.displayed.clear();
+                for i in 0..rows.min(row + 1).min(self.row + 1) {
+                    self.displayed.cells[row - i] = old.cells[self.row - i];
+                }
+            }
+        }
+        self.row = row;
+        self.column = if b2 & 0x10 != 0 {
+            ((b2 & 0x0e) >> 1) as usize * 4
+        } else {
+            0
+        };
+    }
+
+    fn roll_up(&mut self, rows: usize) {
+        let top = (self.row + 1).saturating_sub(rows);
+        let cells = &mut self.displayed.cells;
+        for row in 0..self.row {
+            cells[row] = if row >= top {
+                cells[row + 1]
+            } else {
+                [None; COLUMNS]
+            };
+        }
+        cells[self.row] = [None; COLUMNS];
+        self.column = 0;
+    }
+
+    fn target(&mut self) -> &mut Screen {
+        match self.mode {
+            Mode::PopOn => &mut self.non_displayed,
+            Mode::RollUp(_) | Mode::PaintOn => &mut self.displayed,
+        }
+    }
+
+    fn put_char(&mut self, c: char) {
+        if self.text_mode {
+            return;
+        }
+        let (row, column) = (self.row, self.column);
+        self.target().cells[row][column] = Some(c);
+        self.column = (column + 1).min(COLUMNS - 1);
+    }
+
+    fn backspace(&mut self) {
+        if self.column > 0 {
+            self.column -= 1;
+            let (row, column) = (self.row, self.column);
+            self.target().cells[row][column] = None;
+        }
+    }
+
+    /// Updates the displayed caption after a control code, except in roll-up mode, where it's
+    /// only updated at carriage returns and erasures.
+    fn sample(&mut self, timestamp: Duration) -> Option<Caption> {
+        match self.mode {
+            Mode::RollUp(_) => None,
+            Mode::PopOn | Mode::PaintOn => self.update_display(timestamp),
+        }
+    }
+
+    fn update_display(&mut self, timestamp: Duration) -> Option<Caption> {
+        self.shown.update(self.displayed.text(), timestamp)
+    }
+}
+
+impl Default for CaptionDecoder {
+    fn default() -> Self {
+        Self::new(Cea608Channel::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(name: &str, channel: Cea608Channel) -> Vec<(u32, u32, String)> {
+        let data = std::fs::read(format!(
+            "{}/testdata/cdp/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            name
+        ))
+        .unwrap();
+        let cdps = Cdp::parse_all(&data).unwrap();
+        let rate = cdps[0].frame_rate.unwrap();
+        assert_eq!(rate, Rational::new(30000, 1001));
+        let frame_duration = Duration::from_secs(rate.den as _) / rate.num;
+        let frame = |d: Duration| (d.as_nanos() / frame_duration.as_nanos()) as u32;
+
+        let mut decoder = CaptionDecoder::new(channel);
+        let mut captions = Vec::new();
+        for (i, cdp) in cdps.iter().enumerate() {
+            assert_eq!(cdp.sequence_counter, i as u16);
+            captions.extend(decoder.push_cdp(cdp, frame_duration * i as u32));
+        }
+        captions.extend(decoder.flush(frame_duration * cdps.len() as u32));
+        captions
+            .into_iter()
+            .map(|c| (frame(c.start), frame(c.end), c.text))
+            .collect()
+    }
+
+    #[test]
+    fn test_cdp() {
+        let data = std::fs::read(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/cdp/pop-on.bin"
+        ))
+        .unwrap();
+        let cdp = Cdp::parse(&data).unwrap();
+        assert_eq!(cdp.cc_data.len(), 20);
+        assert_eq!(cdp.timecode.unwrap().to_string(), "10:00:00:00");
+        assert_eq!(
+            cdp.cc_data[0],
+            CcData {
+                valid: true,
+                cc_type: CcType::Cea608Field1,
+                data: [0x94, 0x20],
+            }
+        );
+
+        let mut corrupt = data.clone();
+        corrupt[10] ^= 1;
+        assert!(Cdp::parse(&corrupt).is_err());
+        assert!(Cdp::parse(&data[..10]).is_err());
+    }
+
+    #[test]
+    fn test_pop_on() {
+        assert_eq!(
+            decode("pop-on.bin", Cea608Channel::Cc1),
+            vec![
+                (10, 100, "HELLO WORLD".to_string()),
+                (118, 150, "LINE ONE\nLINE TWO".to_string()),
+            ]
+        );
+        assert_eq!(
+            decode("pop-on.bin", Cea608Channel::Cc3),
+            vec![(27, 150, "NOISE".to_string())]
+        );
+        assert!(decode("pop-on.bin", Cea608Channel::Cc2).is_empty());
+    }
+
+    #[test]
+    fn test_roll_up() {
+        assert_eq!(
+            decode("roll-up.bin", Cea608Channel::Cc1),
+            vec![
+                (7, 23, "HELLO".to_string()),
+                (23, 42, "HELLO\nWORLD".to_string()),
+                (42, 60, "WORLD\nFOO".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_xds() {
+        // The caption's text is interrupted by an XDS program name packet.
+        assert_eq!(
+            decode("xds.bin", Cea608Channel::Cc3),
+            vec![(13, 40, "CAPTION".to_string())]
+        );
+        assert!(decode("xds.bin", Cea608Channel::Cc1).is_empty());
+    }
+}
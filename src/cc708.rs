@@ -0,0 +1,198 @@
+use super::{cc708_sys as sys, AncId, AncPacket, Caption, Result, ShownCaption};
+use snafu::prelude::*;
+use std::time::Duration;
+
+/// The number of CEA-708 caption services supported by the decoder, which are numbered from 1.
+/// The SDK's decoder doesn't support the extended services beyond these.
+pub const CEA708_SERVICES: u8 = 6;
+
+fn check(result: sys::MW_CC_RESULT, what: &str) -> Result<()> {
+    let reason = match result {
+        sys::_CC_RESULT_MW_CC_NO_ERROR => return Ok(()),
+        sys::_CC_RESULT_MW_CC_ERROR_ARGUMENT | sys::_CC_RESULT_MW_CC708_ERROR_ARGUMENT => {
+            "invalid argument"
+        }
+        sys::_CC_RESULT_MW_CC_OUT_OF_MEMORY | sys::_CC_RESULT_MW_CC708_OUT_OF_MEMORY => {
+            "out of memory"
+        }
+        sys::_CC_RESULT_MW_CC_ANC_INVALID | sys::_CC_RESULT_MW_CC_ERROR_ANC_708_INVALID => {
+            "invalid anc packet"
+        }
+        sys::_CC_RESULT_MW_CC_ERROR_ANC_DID | sys::_CC_RESULT_MW_CC_ERROR_ANC_SDID => "not a cdp",
+        sys::_CC_RESULT_MW_CC708_ERROR_ANC_708_DATA_SIZE => "invalid cdp length",
+        sys::_CC_RESULT_MW_CC708_ERROR_ANC_708_TIME_PACKET
+        | sys::_CC_RESULT_MW_CC708_ERROR_ANC_TIME_CODE_LENGTH => "invalid cdp time code",
+        sys::_CC_RESULT_MW_CC708_ERROR_ANC_708_DATA_PACKET => "invalid cdp caption data",
+        sys::_CC_RESULT_MW_CC708_ERROR_ANC_708_SINFO_PACKET => "invalid cdp service info",
+        _ => "unexpected error",
+    };
+    whatever!("unable to {}: {}", what, reason)
+}
+
+/// Decodes a CEA-708 caption service from the CDPs carried in ANC packets into timed captions,
+/// using the SDK's decoder.
+///
+/// Captions start and end whenever the text of the service's visible windows changes, which the
+/// decoder checks at carriage returns and when windows are displayed, hidden, or deleted. Windows
+/// are listed in priority order, with each window's rows separated by newlines.
+pub struct Cea708Decoder {
+    decoder: *mut sys::mw_cc708_decoder_t,
+    service: u8,
+    shown: ShownCaption,
+}
+
+// The decoder isn't tied to the thread that created it.
+unsafe impl Send for Cea708Decoder {}
+
+impl Cea708Decoder {
+    /// Creates a decoder for the given service, from 1 to `CEA708_SERVICES`. Service 1 is the
+    /// primary caption service.
+    pub fn new(service: u8) -> Result<Self> {
+        if !(1..=CEA708_SERVICES).contains(&service) {
+            whatever!("invalid cea-708 service {}", service);
+        }
+        let decoder = unsafe { sys::MWCreateCC708Decoder() };
+        if decoder.is_null() {
+            whatever!("unable to create cea-708 decoder");
+        }
+        unsafe { sys::MWSetCC708DecodeType(decoder, false, true) };
+        Ok(Self {
+            decoder,
+            service,
+            shown: ShownCaption::default(),
+        })
+    }
+
+    /// Decodes the CDP carried by an ANC packet, using the packet's timestamp, and returning any
+    /// captions that were removed from the screen. Other packets are ignored.
+    pub fn push_anc(&mut self, packet: &AncPacket) -> Result<Vec<Caption>> {
+        if packet.id() != AncId::CEA_708 {
+            return Ok(Vec::new());
+        }
+        // The decoder takes the whole packet, starting with the DID, SDID, and data count.
+        let mut data = vec![packet.did, packet.sdid, packet.data.len() as u8];
+        data.extend_from_slice(&packet.data);
+        unsafe {
+            check(
+                sys::MWDecodeCC708(self.decoder, data.as_ptr(), data.len() as _),
+                "decode cdp",
+            )?;
+        }
+        Ok(self
+            .shown
+            .update(self.screen_text(), packet.timestamp)
+            .into_iter()
+            .collect())
+    }
+
+    /// Ends the caption that's currently displayed, if any, e.g. at the end of a recording.
+    pub fn flush(&mut self, timestamp: Duration) -> Option<Caption> {
+        self.shown.flush(timestamp)
+    }
+
+    /// Returns the text of the service's visible windows, as of the decoder's last screen update.
+    fn screen_text(&self) -> String {
+        // The decoder only reports screen updates for service 1 through its callback, so the
+        // service's screen is read directly. Its decoder is created once the service is received.
+        let screen = unsafe {
+            let services = (*(*self.decoder).cc708_sad_decoders).cc708_service_decoder;
+            let service = *services.add(self.service as usize - 1);
+            if service.is_null() {
+                return String::new();
+            }
+            &(*service).m_win_screen
+        };
+        let count = (screen.nwindow_num.max(0) as usize).min(screen.windows.len());
+        let mut rows = Vec::new();
+        for window in &screen.windows[..count] {
+            let columns = (window.col_count.max(0) as usize).min(sys::I708_MAX_COLUMNS as _);
+            for &cells in window.cell_rows.iter().take(window.row_count.max(0) as _) {
+                if cells.is_null() {
+                    continue;
+                }
+                let cells = unsafe { std::slice::from_raw_parts(cells, columns) };
+                let row: String = cells
+                    .iter()
+                    .map(|cell| match cell.init {
+                        0 => ' ',
+                        _ => char::from_u32(cell.sym as _).unwrap_or(' '),
+                    })
+                    .collect();
+                let row = row.trim();
+                if !row.is_empty() {
+                    rows.push(row.to_string());
+                }
+            }
+        }
+        rows.join("\n")
+    }
+}
+
+impl Drop for Cea708Decoder {
+    fn drop(&mut self) {
+        unsafe { sys::MWDestoryCC708Decoder(self.decoder) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(service: u8) -> Vec<(u32, u32, String)> {
+        let mut data = std::fs::read(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/cdp/dtvcc.bin"
+        ))
+        .unwrap();
+        let frame_duration = Duration::from_secs(1001) / 30000;
+        let frame = |d: Duration| (d.as_nanos() / frame_duration.as_nanos()) as u32;
+
+        let mut decoder = Cea708Decoder::new(service).unwrap();
+        let mut captions = Vec::new();
+        let mut timestamp = Duration::ZERO;
+        while !data.is_empty() {
+            let rest = data.split_off(data[2] as _);
+            let packet = AncPacket {
+                did: AncId::CEA_708.did,
+                sdid: AncId::CEA_708.sdid,
+                data: std::mem::replace(&mut data, rest),
+                timestamp,
+            };
+            captions.extend(decoder.push_anc(&packet).unwrap());
+            timestamp += frame_duration;
+        }
+        captions.extend(decoder.flush(timestamp));
+        captions
+            .into_iter()
+            .map(|c| (frame(c.start), frame(c.end), c.text))
+            .collect()
+    }
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(
+            decode(1),
+            vec![
+                (10, 40, "HELLO\nWORLD".to_string()),
+                (50, 60, "LIVE".to_string()),
+            ]
+        );
+        assert_eq!(decode(2), vec![(20, 70, "OTHER".to_string())]);
+        assert!(decode(3).is_empty());
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(Cea708Decoder::new(0).is_err());
+        assert!(Cea708Decoder::new(CEA708_SERVICES + 1).is_err());
+
+        let mut decoder = Cea708Decoder::new(1).unwrap();
+        let packet = AncPacket {
+            did: AncId::CEA_708.did,
+            sdid: AncId::CEA_708.sdid,
+            data: Vec::new(),
+            timestamp: Duration::ZERO,
+        };
+        assert!(decoder.push_anc(&packet).is_err());
+    }
+}
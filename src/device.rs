@@ -0,0 +1,221 @@
+use super::{
+    get_channel_info_by_index, get_device_path_by_index, init, refresh_device_list, sys,
+    ChannelInfo, Family, Result, DEVICE_LIST_MUTEX,
+};
+use snafu::prelude::*;
+use std::{
+    ffi::{c_void, CStr, CString},
+    mem::{size_of, MaybeUninit},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PcieLinkType {
+    Gen1,
+    Gen2,
+    Gen3,
+    Gen4,
+    Other(u8),
+}
+
+impl From<u8> for PcieLinkType {
+    fn from(link_type: u8) -> Self {
+        match link_type as sys::MWCAP_PCIE_LINK_TYPE {
+            sys::_MWCAP_PCIE_LINK_TYPE_MWCAP_PCIE_LINK_GEN_1 => Self::Gen1,
+            sys::_MWCAP_PCIE_LINK_TYPE_MWCAP_PCIE_LINK_GEN_2 => Self::Gen2,
+            sys::_MWCAP_PCIE_LINK_TYPE_MWCAP_PCIE_LINK_GEN_3 => Self::Gen3,
+            sys::_MWCAP_PCIE_LINK_TYPE_MWCAP_PCIE_LINK_GEN_4 => Self::Gen4,
+            _ => Self::Other(link_type),
+        }
+    }
+}
+
+/// Connection info for Pro and Eco devices, which are both PCIe devices.
+pub struct PcieCaptureInfo {
+    inner: sys::MWCAP_PRO_CAPTURE_INFO,
+}
+
+impl PcieCaptureInfo {
+    pub fn bus_id(&self) -> u8 {
+        self.inner.byPCIBusID
+    }
+
+    pub fn device_id(&self) -> u8 {
+        self.inner.byPCIDevID
+    }
+
+    pub fn link_type(&self) -> PcieLinkType {
+        self.inner.byLinkType.into()
+    }
+
+    pub fn link_width(&self) -> u8 {
+        self.inner.byLinkWidth
+    }
+
+    pub fn board_index(&self) -> u8 {
+        self.inner.byBoardIndex
+    }
+
+    pub fn max_payload_size(&self) -> u16 {
+        self.inner.wMaxPayloadSize
+    }
+
+    pub fn max_read_request_size(&self) -> u16 {
+        self.inner.wMaxReadRequestSize
+    }
+
+    pub fn total_memory_size(&self) -> u32 {
+        self.inner.cbTotalMemorySize
+    }
+
+    pub fn free_memory_size(&self) -> u32 {
+        self.inner.cbFreeMemorySize
+    }
+}
+
+impl From<sys::MWCAP_PRO_CAPTURE_INFO> for PcieCaptureInfo {
+    fn from(info: sys::MWCAP_PRO_CAPTURE_INFO) -> Self {
+        PcieCaptureInfo { inner: info }
+    }
+}
+
+/// Connection info for USB devices.
+pub struct UsbCaptureInfo {
+    inner: sys::MWUSBCAP_CAPTURE_INFO,
+}
+
+impl UsbCaptureInfo {
+    /// Returns the USB version that the device is connected with, e.g. 3 for USB 3.0.
+    pub fn usb_speed(&self) -> u8 {
+        self.inner.byUSBSpeed
+    }
+
+    pub fn board_index(&self) -> u8 {
+        self.inner.byBoardIndex
+    }
+
+    pub fn total_memory_size(&self) -> u32 {
+        self.inner.cbTotalMemorySize
+    }
+
+    pub fn free_memory_size(&self) -> u32 {
+        self.inner.cbFreeMemorySize
+    }
+}
+
+impl From<sys::MWUSBCAP_CAPTURE_INFO> for UsbCaptureInfo {
+    fn from(info: sys::MWUSBCAP_CAPTURE_INFO) -> Self {
+        UsbCaptureInfo { inner: info }
+    }
+}
+
+/// Family-specific info, as returned by `MWGetFamilyInfo`.
+pub enum FamilyInfo {
+    Pro(PcieCaptureInfo),
+    Eco(PcieCaptureInfo),
+    Usb(UsbCaptureInfo),
+}
+
+impl FamilyInfo {
+    /// Gets the family info using the given function, which should invoke `MWGetFamilyInfo` or
+    /// `MWGetFamilyInfoByIndex` with the given pointer and size.
+    pub(crate) fn get(
+        family: Family,
+        f: impl FnOnce(*mut c_void, u32) -> sys::MW_RESULT,
+    ) -> Result<Self> {
+        unsafe fn get_info<T>(f: impl FnOnce(*mut c_void, u32) -> sys::MW_RESULT) -> Result<T> {
+            let mut info = MaybeUninit::<T>::uninit();
+            if f(info.as_mut_ptr() as _, size_of::<T>() as _) != sys::_MW_RESULT__MW_SUCCEEDED {
+                whatever!("unable to get family info");
+            }
+            Ok(info.assume_init())
+        }
+
+        unsafe {
+            Ok(match family {
+                Family::Pro => Self::Pro(get_info::<sys::MWCAP_PRO_CAPTURE_INFO>(f)?.into()),
+                Family::Eco => Self::Eco(get_info::<sys::MWCAP_ECO_CAPTURE_INFO>(f)?.into()),
+                Family::Usb => Self::Usb(get_info::<sys::MWUSBCAP_CAPTURE_INFO>(f)?.into()),
+                Family::Other(id) => whatever!("unknown device family {}", id),
+            })
+        }
+    }
+}
+
+/// A physical capture device, which may have multiple channels.
+pub struct Device {
+    family_info: FamilyInfo,
+    channels: Vec<(CString, ChannelInfo)>,
+}
+
+impl Device {
+    pub fn family_info(&self) -> &FamilyInfo {
+        &self.family_info
+    }
+
+    /// Returns the device path and info for each of the device's channels, ordered by channel
+    /// index. The device path can be passed to `Channel::open_by_path`.
+    pub fn channels(&self) -> &[(CString, ChannelInfo)] {
+        &self.channels
+    }
+
+    /// Returns the info for the device's first channel. All channels of a device share the same
+    /// family, product, and board info.
+    fn first_channel(&self) -> &ChannelInfo {
+        &self.channels[0].1
+    }
+
+    pub fn family(&self) -> Family {
+        self.first_channel().family()
+    }
+
+    pub fn board_index(&self) -> u8 {
+        self.first_channel().board_index()
+    }
+
+    pub fn board_serial_number(&self) -> &CStr {
+        self.first_channel().board_serial_number()
+    }
+}
+
+/// Returns all available capture devices, with their channels grouped by board.
+pub fn get_devices() -> Result<Vec<Device>> {
+    if !init() {
+        whatever!("unable to initialize magewell api");
+    }
+
+    let _lock = DEVICE_LIST_MUTEX
+        .lock()
+        .expect("the lock must never be poisoned");
+
+    refresh_device_list()?;
+
+    let channel_count = unsafe { sys::MWGetChannelCount() };
+
+    let mut devices: Vec<Device> = Vec::new();
+    for i in 0..channel_count {
+        let info = get_channel_info_by_index(i)?;
+        let path = get_device_path_by_index(i)?;
+        match devices.iter_mut().find(|d| {
+            d.family() == info.family() && d.board_serial_number() == info.board_serial_number()
+        }) {
+            Some(device) => device.channels.push((path, info)),
+            None => {
+                let family_info = FamilyInfo::get(info.family(), |ptr, size| unsafe {
+                    sys::MWGetFamilyInfoByIndex(i, ptr, size)
+                })?;
+                devices.push(Device {
+                    family_info,
+                    channels: vec![(path, info)],
+                });
+            }
+        }
+    }
+
+    for device in &mut devices {
+        device
+            .channels
+            .sort_by_key(|(_, info)| info.channel_index());
+    }
+
+    Ok(devices)
+}
@@ -0,0 +1,182 @@
+use super::{get_device_paths, init, sys, ChannelInfo, Result};
+use snafu::prelude::*;
+use std::{
+    collections::HashMap,
+    ffi::{c_char, c_void, CString},
+    sync::{
+        mpsc::{self, Receiver, RecvTimeoutError, Sender},
+        Mutex, OnceLock,
+    },
+    thread::JoinHandle,
+    time::Duration,
+};
+
+pub enum DeviceEvent {
+    Added { path: CString, info: ChannelInfo },
+    Removed { path: CString, info: ChannelInfo },
+}
+
+enum WatcherMessage {
+    Rescan,
+    Stop,
+}
+
+// The SDK only supports a single hotplug callback, so it's registered once and fans out to every
+// live watcher.
+static HOTPLUG_SENDERS: Mutex<Vec<Sender<WatcherMessage>>> = Mutex::new(Vec::new());
+static HOTPLUG_REGISTERED: OnceLock<bool> = OnceLock::new();
+
+unsafe extern "C" fn hotplug_callback(
+    _event: sys::MWUSBHOT_PLUG_EVETN,
+    _path: *const c_char,
+    _param: *mut c_void,
+) {
+    // We don't get a `ChannelInfo` here, so just ask every watcher to rescan immediately. It'll
+    // figure out what was added or removed.
+    HOTPLUG_SENDERS
+        .lock()
+        .expect("the lock must never be poisoned")
+        .retain(|tx| tx.send(WatcherMessage::Rescan).is_ok());
+}
+
+fn register_hotplug(tx: Sender<WatcherMessage>) {
+    HOTPLUG_SENDERS
+        .lock()
+        .expect("the lock must never be poisoned")
+        .push(tx);
+    HOTPLUG_REGISTERED.get_or_init(|| unsafe {
+        // This fails if USB support isn't available (e.g. with the `dep-stubs` feature). That's
+        // fine, since USB devices will still be picked up by polling, just less promptly.
+        sys::MWUSBRegisterHotPlug(Some(hotplug_callback), std::ptr::null_mut())
+            == sys::_MW_RESULT__MW_SUCCEEDED
+    });
+}
+
+type DeviceKey = (CString, CString, u8);
+
+fn scan() -> Result<HashMap<DeviceKey, (CString, ChannelInfo)>> {
+    Ok(get_device_paths()?
+        .into_iter()
+        .map(|(path, info)| {
+            (
+                (
+                    path.clone(),
+                    info.board_serial_number().to_owned(),
+                    info.channel_index(),
+                ),
+                (path, info),
+            )
+        })
+        .collect())
+}
+
+/// Watches for capture channels being added or removed, e.g. when USB devices are plugged in or
+/// when PCIe channels appear after a driver reload.
+///
+/// USB devices are picked up immediately via the SDK's hotplug notifications. Everything else is
+/// detected by periodically refreshing the device list, which is serialized with other users via
+/// `DEVICE_LIST_MUTEX`.
+pub struct DeviceWatcher {
+    events: Receiver<Result<DeviceEvent>>,
+    control: Sender<WatcherMessage>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl DeviceWatcher {
+    /// Starts watching for device changes, rescanning the device list every `poll_interval`. An
+    /// `Added` event is emitted for every channel that's already present.
+    pub fn new(poll_interval: Duration) -> Result<Self> {
+        if !init() {
+            whatever!("unable to initialize magewell api");
+        }
+
+        let (control_tx, control_rx) = mpsc::channel();
+        let (events_tx, events_rx) = mpsc::channel();
+
+        register_hotplug(control_tx.clone());
+
+        let thread = std::thread::Builder::new()
+            .name("magewell-device-watcher".to_string())
+            .spawn(move || {
+                let mut known = HashMap::new();
+                loop {
+                    match scan() {
+                        Ok(current) => {
+                            let removed = known
+                                .iter()
+                                .filter(|(key, _)| !current.contains_key(*key))
+                                .map(|(_, (path, info))| DeviceEvent::Removed {
+                                    path: path.clone(),
+                                    info: info.clone(),
+                                });
+                            let added = current
+                                .iter()
+                                .filter(|(key, _)| !known.contains_key(*key))
+                                .map(|(_, (path, info))| DeviceEvent::Added {
+                                    path: path.clone(),
+                                    info: info.clone(),
+                                });
+                            for event in removed.chain(added) {
+                                if events_tx.send(Ok(event)).is_err() {
+                                    return;
+                                }
+                            }
+                            known = current;
+                        }
+                        Err(e) => {
+                            if events_tx.send(Err(e)).is_err() {
+                                return;
+                            }
+                        }
+                    }
+
+                    match control_rx.recv_timeout(poll_interval) {
+                        Ok(WatcherMessage::Rescan) | Err(RecvTimeoutError::Timeout) => {}
+                        Ok(WatcherMessage::Stop) | Err(RecvTimeoutError::Disconnected) => return,
+                    }
+                }
+            })
+            .whatever_context("unable to spawn device watcher thread")?;
+
+        Ok(Self {
+            events: events_rx,
+            control: control_tx,
+            thread: Some(thread),
+        })
+    }
+
+    /// Blocks until the next event is available.
+    pub fn recv(&self) -> Result<DeviceEvent> {
+        match self.events.recv() {
+            Ok(event) => event,
+            Err(_) => whatever!("device watcher stopped"),
+        }
+    }
+
+    /// Blocks until the next event is available or the timeout elapses. Returns `None` on
+    /// timeout.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<Option<DeviceEvent>> {
+        match self.events.recv_timeout(timeout) {
+            Ok(event) => event.map(Some),
+            Err(RecvTimeoutError::Timeout) => Ok(None),
+            Err(RecvTimeoutError::Disconnected) => whatever!("device watcher stopped"),
+        }
+    }
+}
+
+impl Iterator for DeviceWatcher {
+    type Item = Result<DeviceEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.events.recv().ok()
+    }
+}
+
+impl Drop for DeviceWatcher {
+    fn drop(&mut self) {
+        let _ = self.control.send(WatcherMessage::Stop);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
@@ -1,10 +1,86 @@
 use super::{
-    sys, ChannelHandle, ChannelInfo, EcoVideoCaptureFrame, EcoVideoCaptureStatus, FourCC,
-    ProEcoCaptureFamilyChannel, Result, UniversalCaptureFamilyChannel,
+    close_monitor, poll_monitor, sys, AncMonitor, AncPacket, AncSubscription, ChannelHandle,
+    ChannelInfo, Colorimetry, EcoVideoCaptureFrame, EcoVideoCaptureStatus, FourCC,
+    HdrMetadataTracker, NotifyEvents, NotifyHandle, NotifyMonitor, ProEcoCaptureFamilyChannel,
+    QuantizationRange, Result, SaturationRange, SignalEvent, SignalMonitor,
+    UniversalCaptureFamilyChannel, VideoColorFormat, VideoProcSettings,
 };
 use nix::sys::eventfd::EventFd;
 use snafu::prelude::*;
-use std::{boxed::Box, ffi::c_void, mem::MaybeUninit, os::fd::AsRawFd, pin::Pin};
+use std::{boxed::Box, ffi::c_void, mem::MaybeUninit, os::fd::AsRawFd, pin::Pin, time::Duration};
+
+/// Parameters for `EcoChannel::start_video_capture`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EcoCaptureConfig {
+    pub width: u16,
+    pub height: u16,
+    pub format: FourCC,
+    /// The duration of each captured frame. If `None`, frames are captured at the source's frame
+    /// rate. Otherwise, frames are dropped to decimate the source to the given rate. The SDK uses
+    /// 100 ns units, so durations under 100 ns are rejected.
+    pub frame_duration: Option<Duration>,
+}
+
+impl EcoCaptureConfig {
+    pub fn new(width: u16, height: u16, format: FourCC) -> Self {
+        Self {
+            width,
+            height,
+            format,
+            frame_duration: None,
+        }
+    }
+}
+
+/// Color settings that can be changed while an Eco capture is running.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EcoCaptureSettings {
+    pub color_format: VideoColorFormat,
+    pub quantization_range: QuantizationRange,
+    pub saturation_range: SaturationRange,
+    pub proc_settings: VideoProcSettings,
+}
+
+struct FollowSource {
+    monitor: SignalMonitor,
+    stride_alignment: usize,
+}
+
+/// Caches the input's colorimetry while capturing, re-reading it when the signal changes rather
+/// than for every frame.
+struct InputColorimetry {
+    handle: NotifyHandle,
+    // `None` if it couldn't be read
+    colorimetry: Option<Colorimetry>,
+}
+
+impl InputColorimetry {
+    fn new(channel: &EcoChannel) -> Result<Self> {
+        let handle = channel.register_notify(
+            NotifyEvents::VIDEO_SIGNAL_CHANGE | NotifyEvents::INPUT_SPECIFIC_CHANGE,
+        )?;
+        Ok(Self {
+            handle,
+            colorimetry: channel.get_input_colorimetry().ok(),
+        })
+    }
+}
+
+impl NotifyMonitor for InputColorimetry {
+    type Output = ();
+
+    fn poll<C: ProEcoCaptureFamilyChannel + ?Sized>(&mut self, channel: &C) -> Result<()> {
+        let changed = !channel.get_notify_status(self.handle)?.is_empty();
+        if changed || self.colorimetry.is_none() {
+            self.colorimetry = channel.get_input_colorimetry().ok();
+        }
+        Ok(())
+    }
+
+    fn close<C: ProEcoCaptureFamilyChannel + ?Sized>(self, channel: &C) -> Result<()> {
+        channel.unregister_notify(self.handle)
+    }
+}
 
 pub struct EcoChannel {
     handle: ChannelHandle,
@@ -12,6 +88,19 @@ pub struct EcoChannel {
     event_fd: EventFd,
     // hold onto a reference of the currently set capture frame
     video_capture_frame: Option<Pin<Box<EcoVideoCaptureFrame>>>,
+    // the config of the running video capture
+    capture_config: Option<EcoCaptureConfig>,
+    // the settings applied to the running video capture via `update_capture_settings`
+    capture_settings: Option<EcoCaptureSettings>,
+    // the input colorimetry of the running video capture
+    input_colorimetry: Option<InputColorimetry>,
+    follow_source: Option<FollowSource>,
+    hdr_metadata: Option<HdrMetadataTracker>,
+    anc: Option<AncMonitor>,
+    // the ANC packets received since the last captured frame
+    anc_packets: Vec<AncPacket>,
+    // whether the next captured frame should be marked as a discontinuity
+    discontinuity: bool,
 }
 
 unsafe impl UniversalCaptureFamilyChannel for EcoChannel {
@@ -38,24 +127,192 @@ impl EcoChannel {
             info,
             event_fd,
             video_capture_frame: None,
+            capture_config: None,
+            capture_settings: None,
+            input_colorimetry: None,
+            follow_source: None,
+            hdr_metadata: None,
+            anc: None,
+            anc_packets: Vec::new(),
+            discontinuity: false,
         })
     }
 
-    pub fn start_video_capture(&mut self, width: u16, height: u16, format: FourCC) -> Result<()> {
+    /// Enables "follow source" mode. Whenever the input signal settles on a new format, the video
+    /// capture is restarted at the new resolution with the same pixel format, frames are
+    /// reallocated to fit, and the next captured frame is marked as a discontinuity so that
+    /// downstream consumers can re-initialize.
+    ///
+    /// Signal changes are checked by `get_video_capture_status`, so they're picked up the next time
+    /// `wait` returns. Reallocated frames use a stride aligned to `stride_alignment` bytes.
+    pub fn enable_follow_source(
+        &mut self,
+        debounce: Duration,
+        stride_alignment: usize,
+    ) -> Result<()> {
+        if self.follow_source.is_some() {
+            whatever!("follow source already enabled");
+        }
+        let monitor = SignalMonitor::new(&*self, debounce)?;
+        self.follow_source = Some(FollowSource {
+            monitor,
+            stride_alignment,
+        });
+        Ok(())
+    }
+
+    pub fn disable_follow_source(&mut self) -> Result<()> {
+        if let Some(follow_source) = self.follow_source.take() {
+            follow_source.monitor.close(&*self)?;
+        }
+        Ok(())
+    }
+
+    /// Enables HDR metadata tracking. The HDR and AVI InfoFrames are re-read whenever the input
+    /// specific status changes, and the resulting metadata is attached to each captured frame.
+    pub fn enable_hdr_metadata(&mut self) -> Result<()> {
+        if self.hdr_metadata.is_some() {
+            whatever!("hdr metadata already enabled");
+        }
+        self.hdr_metadata = Some(HdrMetadataTracker::new(&*self)?);
+        Ok(())
+    }
+
+    pub fn disable_hdr_metadata(&mut self) -> Result<()> {
+        close_monitor(self, |channel| &mut channel.hdr_metadata)
+    }
+
+    /// Enables capturing the given types of ANC packets from SDI inputs. Each captured frame is
+    /// given the packets received since the previous frame.
+    pub fn enable_anc(&mut self, subscriptions: &[AncSubscription]) -> Result<()> {
+        if self.anc.is_some() {
+            whatever!("anc already enabled");
+        }
+        self.anc = Some(AncMonitor::new(&*self, subscriptions)?);
+        Ok(())
+    }
+
+    pub fn disable_anc(&mut self) -> Result<()> {
+        close_monitor(self, |channel| &mut channel.anc)?;
+        self.anc_packets.clear();
+        Ok(())
+    }
+
+    /// Returns the config of the running video capture. In "follow source" mode, the width and
+    /// height change whenever the capture is restarted.
+    pub fn capture_config(&self) -> Option<&EcoCaptureConfig> {
+        self.capture_config.as_ref()
+    }
+
+    fn follow_source(&mut self) -> Result<()> {
+        let Some(mut follow_source) = self.follow_source.take() else {
+            return Ok(());
+        };
+        let result = self.follow_source_with(&mut follow_source);
+        self.follow_source = Some(follow_source);
+        result
+    }
+
+    fn follow_source_with(&mut self, follow_source: &mut FollowSource) -> Result<()> {
+        let events = follow_source.monitor.poll(&*self)?;
+        if !events.iter().any(|event| {
+            matches!(
+                event,
+                SignalEvent::VideoAcquired(_) | SignalEvent::VideoFormatChanged { .. }
+            )
+        }) {
+            return Ok(());
+        }
+
+        // Even if the resolution is the same, the frame rate or scanning may have changed.
+        self.discontinuity = true;
+
+        let (Some(config), Some(video_format)) =
+            (self.capture_config, follow_source.monitor.video_format())
+        else {
+            return Ok(());
+        };
+        if (video_format.width, video_format.height) == (config.width, config.height) {
+            return Ok(());
+        }
+
+        let had_frame = self.video_capture_frame.is_some();
+        let settings = self.capture_settings;
+        self.stop_video_capture()?;
+        // The SDK no longer references the frame once capture is stopped.
+        self.video_capture_frame = None;
+        let config = EcoCaptureConfig {
+            width: video_format.width,
+            height: video_format.height,
+            ..config
+        };
+        self.start_video_capture(&config)?;
+        if let Some(settings) = settings {
+            self.update_capture_settings(&settings)?;
+        }
+        if had_frame {
+            let stride = config
+                .format
+                .min_stride(config.width, follow_source.stride_alignment);
+            let size = config
+                .format
+                .image_size(config.width, config.height, stride);
+            self.set_video_capture_frame(EcoVideoCaptureFrame::new(size, stride))?;
+        }
+        Ok(())
+    }
+
+    pub fn start_video_capture(&mut self, config: &EcoCaptureConfig) -> Result<()> {
+        // The SDK uses 100ns units, with -1 meaning the source's frame rate.
+        let frame_duration = match config.frame_duration {
+            Some(duration) => match i64::try_from(duration.as_nanos() / 100) {
+                Ok(units) if units > 0 => units,
+                _ => whatever!("invalid frame duration {:?}", duration),
+            },
+            None => -1,
+        };
         let mut params = sys::_MWCAP_VIDEO_ECO_CAPTURE_OPEN {
-            cx: width as _,
-            cy: height as _,
-            dwFOURCC: format.as_u32(),
-            llFrameDuration: -1,
+            cx: config.width as _,
+            cy: config.height as _,
+            dwFOURCC: config.format.as_u32(),
+            llFrameDuration: frame_duration,
             hEvent: self.event_fd.as_raw_fd() as _,
         };
+        let input_colorimetry = InputColorimetry::new(self)?;
         unsafe {
             if sys::MWStartVideoEcoCapture(self.handle(), &mut params as *mut _)
                 != sys::_MW_RESULT__MW_SUCCEEDED
             {
+                let _ = input_colorimetry.close(self);
                 whatever!("unable to start video capture");
             }
         }
+        self.capture_config = Some(*config);
+        self.input_colorimetry = Some(input_colorimetry);
+        Ok(())
+    }
+
+    /// Changes the color settings of the running video capture without restarting it. Changing
+    /// the size, format, or frame duration requires restarting the capture.
+    pub fn update_capture_settings(&mut self, settings: &EcoCaptureSettings) -> Result<()> {
+        settings.proc_settings.validate()?;
+        let mut params = sys::MWCAP_VIDEO_ECO_CAPTURE_SETTINGS {
+            colorFormat: settings.color_format.into(),
+            quantRange: settings.quantization_range.into(),
+            satRange: settings.saturation_range.into(),
+            sContrast: settings.proc_settings.contrast,
+            sBrightness: settings.proc_settings.brightness,
+            sSaturation: settings.proc_settings.saturation,
+            sHue: settings.proc_settings.hue,
+        };
+        unsafe {
+            if sys::MWSetVideoEcoCaptureSettings(self.handle(), &mut params as *mut _)
+                != sys::_MW_RESULT__MW_SUCCEEDED
+            {
+                whatever!("unable to set video capture settings");
+            }
+        }
+        self.capture_settings = Some(*settings);
         Ok(())
     }
 
@@ -65,13 +322,33 @@ impl EcoChannel {
                 whatever!("unable to stop video capture");
             }
         }
-        Ok(())
+        self.capture_config = None;
+        self.capture_settings = None;
+        close_monitor(self, |channel| &mut channel.input_colorimetry)
     }
 
+    /// Sets the frame to capture the next video frame into. In "follow source" mode, the frame is
+    /// replaced with a new one if it's too small for the current capture.
     pub fn set_video_capture_frame(&mut self, frame: EcoVideoCaptureFrame) -> Result<()> {
         if self.video_capture_frame.is_some() {
             whatever!("video frame already set");
         }
+        let frame = match (&self.follow_source, &self.capture_config) {
+            (Some(follow_source), Some(config)) => {
+                let stride = config
+                    .format
+                    .min_stride(config.width, follow_source.stride_alignment);
+                let size = config
+                    .format
+                    .image_size(config.width, config.height, stride);
+                if frame.as_slice().len() < size || frame.stride() < stride {
+                    EcoVideoCaptureFrame::new(size, stride)
+                } else {
+                    frame
+                }
+            }
+            _ => frame,
+        };
         let mut frame = Box::pin(frame);
         unsafe {
             if sys::MWCaptureSetVideoEcoFrame(self.handle(), frame.as_mut_ptr())
@@ -87,6 +364,12 @@ impl EcoChannel {
     /// Returns the next video capture frame, if available. If no frame is available, returns
     /// `None`. Invoke `wait` to block until a frame may be available.
     pub fn get_video_capture_status(&mut self) -> Result<Option<EcoVideoCaptureStatus>> {
+        self.follow_source()?;
+        poll_monitor(self, |channel| &mut channel.hdr_metadata)?;
+        if let Some(packets) = poll_monitor(self, |channel| &mut channel.anc)? {
+            self.anc_packets.extend(packets);
+        }
+        poll_monitor(self, |channel| &mut channel.input_colorimetry)?;
         let status = unsafe {
             let mut status = MaybeUninit::uninit();
             if sys::MWGetVideoEcoCaptureStatus(self.handle(), status.as_mut_ptr())
@@ -99,6 +382,7 @@ impl EcoChannel {
         if status.pvFrame == 0 {
             return Ok(None);
         }
+        let colorimetry = self.capture_colorimetry();
         Ok(Some(EcoVideoCaptureStatus::new(
             *Pin::into_inner(
                 self.video_capture_frame
@@ -106,9 +390,40 @@ impl EcoChannel {
                     .whatever_context("no video frame set")?,
             ),
             status,
+            std::mem::take(&mut self.discontinuity),
+            colorimetry,
+            self.hdr_metadata.as_ref().map(|tracker| tracker.metadata()),
+            std::mem::take(&mut self.anc_packets),
         )))
     }
 
+    /// Returns the colorimetry of captured frames, which is the input's unless overridden via
+    /// `update_capture_settings`. Anything that can't be determined is unknown.
+    fn capture_colorimetry(&self) -> Colorimetry {
+        let input = self
+            .input_colorimetry
+            .as_ref()
+            .and_then(|input| input.colorimetry)
+            .unwrap_or_default();
+        let Some(settings) = self.capture_settings else {
+            return input;
+        };
+        Colorimetry {
+            color_format: match settings.color_format {
+                VideoColorFormat::Unknown => input.color_format,
+                format => format,
+            },
+            quantization_range: match settings.quantization_range {
+                QuantizationRange::Unknown => input.quantization_range,
+                range => range,
+            },
+            saturation_range: match settings.saturation_range {
+                SaturationRange::Unknown => input.saturation_range,
+                range => range,
+            },
+        }
+    }
+
     /// Blocks until the next video frame is available or until an event registered via
     /// `register_notify`.
     pub fn wait(&self) -> Result<()> {
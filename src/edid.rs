@@ -0,0 +1,1167 @@
+use super::Result;
+use bitflags::bitflags;
+use snafu::prelude::*;
+
+const BLOCK_SIZE: usize = 128;
+const DESCRIPTOR_SIZE: usize = 18;
+const HEADER: [u8; 8] = [0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00];
+const YEAR_BASE: u16 = 1990;
+const STANDARD_TIMING_UNUSED: [u8; 2] = [0x01, 0x01];
+
+const DESCRIPTOR_TAG_SERIAL_NUMBER: u8 = 0xff;
+const DESCRIPTOR_TAG_TEXT: u8 = 0xfe;
+const DESCRIPTOR_TAG_RANGE_LIMITS: u8 = 0xfd;
+const DESCRIPTOR_TAG_PRODUCT_NAME: u8 = 0xfc;
+const DESCRIPTOR_TAG_DUMMY: u8 = 0x10;
+
+const CEA_EXTENSION_TAG: u8 = 0x02;
+const CEA_TAG_AUDIO: u8 = 1;
+const CEA_TAG_VIDEO: u8 = 2;
+const CEA_TAG_EXTENDED: u8 = 7;
+const CEA_EXTENDED_TAG_HDR_STATIC_METADATA: u8 = 6;
+
+/// Returns the checksum byte that makes the given 128-byte EDID block sum to zero.
+pub fn edid_checksum(block: &[u8]) -> u8 {
+    block[..BLOCK_SIZE - 1]
+        .iter()
+        .fold(0u8, |sum, b| sum.wrapping_add(*b))
+        .wrapping_neg()
+}
+
+fn verify_block(block: &[u8]) -> Result<()> {
+    if edid_checksum(block) != block[BLOCK_SIZE - 1] {
+        whatever!("invalid edid block checksum");
+    }
+    Ok(())
+}
+
+/// A parsed EDID 1.4 base block along with its extension blocks.
+///
+/// `Edid::parse` and `Edid::to_bytes` round-trip well-formed EDIDs exactly, so an EDID read from a
+/// device can be modified and written back without disturbing anything this type doesn't model.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Edid {
+    /// The three-letter PNP ID of the manufacturer, e.g. "MWL".
+    pub manufacturer_id: String,
+    pub product_code: u16,
+    pub serial_number: u32,
+    /// The week of manufacture, 0 if unspecified, or 0xff if `year` is the model year.
+    pub week: u8,
+    pub year: u16,
+    pub version: u8,
+    pub revision: u8,
+    /// The raw video input definition. Bit 7 is set for digital inputs.
+    pub video_input_definition: u8,
+    /// The screen width and height in cm. If either is 0, the other encodes an aspect ratio.
+    pub screen_size: (u8, u8),
+    /// The raw display transfer characteristic, where gamma = (value + 100) / 100.
+    pub gamma: u8,
+    /// The raw feature support bitmap.
+    pub feature_support: u8,
+    pub chromaticity: Chromaticity,
+    /// The raw established timings I, II, and manufacturer's timings bitmaps.
+    pub established_timings: [u8; 3],
+    pub standard_timings: [Option<StandardTiming>; 8],
+    /// The bytes that mark an unused standard timing, normally `01 01`, though some EDIDs use
+    /// `00 00`.
+    pub unused_standard_timing: [u8; 2],
+    /// The 18-byte descriptors of the base block. At most four are allowed, and missing ones are
+    /// filled with dummy descriptors, which are likewise omitted when parsing. The first should be
+    /// the preferred timing.
+    pub descriptors: Vec<EdidDescriptor>,
+    pub extensions: Vec<EdidExtension>,
+}
+
+impl Edid {
+    /// Parses an EDID, verifying the header and each block's checksum.
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        if data.len() < BLOCK_SIZE || data[..8] != HEADER {
+            whatever!("invalid edid header");
+        }
+        let base = &data[..BLOCK_SIZE];
+        verify_block(base)?;
+
+        let extension_count = base[0x7e] as usize;
+        if data.len() < BLOCK_SIZE * (1 + extension_count) {
+            whatever!(
+                "edid has {} extension blocks but only {} bytes",
+                extension_count,
+                data.len()
+            );
+        }
+        let extensions = data[BLOCK_SIZE..]
+            .chunks_exact(BLOCK_SIZE)
+            .take(extension_count)
+            .map(EdidExtension::parse)
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut descriptors: Vec<_> = base[0x36..0x7e]
+            .chunks_exact(DESCRIPTOR_SIZE)
+            .map(EdidDescriptor::parse)
+            .collect();
+        while let Some(EdidDescriptor::Other {
+            tag: DESCRIPTOR_TAG_DUMMY,
+            data,
+        }) = descriptors.last()
+        {
+            if *data != [0; 14] {
+                break;
+            }
+            descriptors.pop();
+        }
+
+        let mut standard_timings = [None; 8];
+        let mut unused_standard_timing = None;
+        for (i, timing) in standard_timings.iter_mut().enumerate() {
+            let bytes = [base[0x26 + 2 * i], base[0x27 + 2 * i]];
+            *timing = StandardTiming::parse(bytes);
+            if timing.is_none() {
+                unused_standard_timing.get_or_insert(bytes);
+            }
+        }
+
+        Ok(Self {
+            manufacturer_id: decode_pnp_id([base[0x08], base[0x09]]),
+            product_code: u16::from_le_bytes([base[0x0a], base[0x0b]]),
+            serial_number: u32::from_le_bytes([base[0x0c], base[0x0d], base[0x0e], base[0x0f]]),
+            week: base[0x10],
+            year: YEAR_BASE + base[0x11] as u16,
+            version: base[0x12],
+            revision: base[0x13],
+            video_input_definition: base[0x14],
+            screen_size: (base[0x15], base[0x16]),
+            gamma: base[0x17],
+            feature_support: base[0x18],
+            chromaticity: Chromaticity::parse(&base[0x19..0x23]),
+            established_timings: [base[0x23], base[0x24], base[0x25]],
+            standard_timings,
+            unused_standard_timing: unused_standard_timing.unwrap_or(STANDARD_TIMING_UNUSED),
+            descriptors,
+            extensions,
+        })
+    }
+
+    /// Encodes the EDID, calculating each block's checksum.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        if self.descriptors.len() > 4 {
+            whatever!("edid can have at most 4 descriptors");
+        }
+        if self.extensions.len() > 254 {
+            whatever!("edid can have at most 254 extension blocks");
+        }
+        if !(YEAR_BASE..=YEAR_BASE + 255).contains(&self.year) {
+            whatever!("edid year {} is out of range", self.year);
+        }
+
+        let mut base = [0u8; BLOCK_SIZE];
+        base[..8].copy_from_slice(&HEADER);
+        base[0x08..0x0a].copy_from_slice(&encode_pnp_id(&self.manufacturer_id)?);
+        base[0x0a..0x0c].copy_from_slice(&self.product_code.to_le_bytes());
+        base[0x0c..0x10].copy_from_slice(&self.serial_number.to_le_bytes());
+        base[0x10] = self.week;
+        base[0x11] = (self.year - YEAR_BASE) as _;
+        base[0x12] = self.version;
+        base[0x13] = self.revision;
+        base[0x14] = self.video_input_definition;
+        base[0x15] = self.screen_size.0;
+        base[0x16] = self.screen_size.1;
+        base[0x17] = self.gamma;
+        base[0x18] = self.feature_support;
+        base[0x19..0x23].copy_from_slice(&self.chromaticity.to_bytes());
+        base[0x23..0x26].copy_from_slice(&self.established_timings);
+        for (i, timing) in self.standard_timings.iter().enumerate() {
+            let bytes = match timing {
+                Some(timing) => timing.to_bytes()?,
+                None => self.unused_standard_timing,
+            };
+            base[0x26 + 2 * i..0x28 + 2 * i].copy_from_slice(&bytes);
+        }
+        let dummy = EdidDescriptor::Other {
+            tag: DESCRIPTOR_TAG_DUMMY,
+            data: [0; 14],
+        };
+        for (i, descriptor) in self
+            .descriptors
+            .iter()
+            .chain(std::iter::repeat(&dummy))
+            .take(4)
+            .enumerate()
+        {
+            let offset = 0x36 + DESCRIPTOR_SIZE * i;
+            base[offset..offset + DESCRIPTOR_SIZE].copy_from_slice(&descriptor.to_bytes()?);
+        }
+        base[0x7e] = self.extensions.len() as _;
+        base[0x7f] = edid_checksum(&base);
+
+        let mut data = base.to_vec();
+        for extension in &self.extensions {
+            data.extend_from_slice(&extension.to_bytes()?);
+        }
+        Ok(data)
+    }
+
+    pub fn is_digital(&self) -> bool {
+        self.video_input_definition & 0x80 != 0
+    }
+
+    /// Returns the first detailed timing, which is the display's preferred timing.
+    pub fn preferred_timing(&self) -> Option<&DetailedTiming> {
+        match self.descriptors.first() {
+            Some(EdidDescriptor::DetailedTiming(timing)) => Some(timing),
+            _ => None,
+        }
+    }
+
+    /// Returns the product name, without any padding it was stored with.
+    pub fn product_name(&self) -> Option<&str> {
+        self.descriptors
+            .iter()
+            .find_map(|descriptor| match descriptor {
+                EdidDescriptor::ProductName(name) => name.split('\n').next(),
+                _ => None,
+            })
+            .map(|name| name.trim_end_matches(['\0', ' ']))
+    }
+
+    pub fn cea_extension(&self) -> Option<&CeaExtension> {
+        self.extensions
+            .iter()
+            .find_map(|extension| match extension {
+                EdidExtension::Cea(cea) => Some(cea),
+                _ => None,
+            })
+    }
+
+    pub fn cea_extension_mut(&mut self) -> Option<&mut CeaExtension> {
+        self.extensions
+            .iter_mut()
+            .find_map(|extension| match extension {
+                EdidExtension::Cea(cea) => Some(cea),
+                _ => None,
+            })
+    }
+}
+
+fn decode_pnp_id(bytes: [u8; 2]) -> String {
+    let id = u16::from_be_bytes(bytes);
+    [10, 5, 0]
+        .iter()
+        .map(|shift| (((id >> shift) & 0x1f) as u8 + b'A' - 1) as char)
+        .collect()
+}
+
+fn encode_pnp_id(id: &str) -> Result<[u8; 2]> {
+    let letters = id.as_bytes();
+    if letters.len() != 3 || !letters.iter().all(u8::is_ascii_uppercase) {
+        whatever!("invalid manufacturer id {:?}", id);
+    }
+    let id = letters
+        .iter()
+        .fold(0u16, |id, letter| id << 5 | (letter - b'A' + 1) as u16);
+    Ok(id.to_be_bytes())
+}
+
+/// The CIE 1931 xy coordinates of the display's primaries and white point, as raw 10-bit values
+/// where 1024 represents 1.0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Chromaticity {
+    pub red: (u16, u16),
+    pub green: (u16, u16),
+    pub blue: (u16, u16),
+    pub white: (u16, u16),
+}
+
+impl Chromaticity {
+    fn parse(bytes: &[u8]) -> Self {
+        let value = |lsb_byte: u8, lsb_shift: u8, msb: u8| {
+            (msb as u16) << 2 | ((lsb_byte >> lsb_shift) & 0x03) as u16
+        };
+        Self {
+            red: (value(bytes[0], 6, bytes[2]), value(bytes[0], 4, bytes[3])),
+            green: (value(bytes[0], 2, bytes[4]), value(bytes[0], 0, bytes[5])),
+            blue: (value(bytes[1], 6, bytes[6]), value(bytes[1], 4, bytes[7])),
+            white: (value(bytes[1], 2, bytes[8]), value(bytes[1], 0, bytes[9])),
+        }
+    }
+
+    fn to_bytes(self) -> [u8; 10] {
+        let lsbs = |a: (u16, u16), b: (u16, u16)| {
+            ((a.0 & 0x03) << 6 | (a.1 & 0x03) << 4 | (b.0 & 0x03) << 2 | (b.1 & 0x03)) as u8
+        };
+        [
+            lsbs(self.red, self.green),
+            lsbs(self.blue, self.white),
+            (self.red.0 >> 2) as _,
+            (self.red.1 >> 2) as _,
+            (self.green.0 >> 2) as _,
+            (self.green.1 >> 2) as _,
+            (self.blue.0 >> 2) as _,
+            (self.blue.1 >> 2) as _,
+            (self.white.0 >> 2) as _,
+            (self.white.1 >> 2) as _,
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StandardAspectRatio {
+    Aspect16x10,
+    Aspect4x3,
+    Aspect5x4,
+    Aspect16x9,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StandardTiming {
+    /// The horizontal resolution, which must be a multiple of 8 from 256 to 2288.
+    pub h_active: u16,
+    pub aspect_ratio: StandardAspectRatio,
+    /// The refresh rate, from 60 to 123 Hz.
+    pub refresh_rate: u8,
+}
+
+impl StandardTiming {
+    fn parse(bytes: [u8; 2]) -> Option<Self> {
+        if bytes == STANDARD_TIMING_UNUSED || bytes == [0, 0] {
+            return None;
+        }
+        Some(Self {
+            h_active: (bytes[0] as u16 + 31) * 8,
+            aspect_ratio: match bytes[1] >> 6 {
+                0 => StandardAspectRatio::Aspect16x10,
+                1 => StandardAspectRatio::Aspect4x3,
+                2 => StandardAspectRatio::Aspect5x4,
+                _ => StandardAspectRatio::Aspect16x9,
+            },
+            refresh_rate: (bytes[1] & 0x3f) + 60,
+        })
+    }
+
+    fn to_bytes(self) -> Result<[u8; 2]> {
+        if self.h_active % 8 != 0 || !(256..=2288).contains(&self.h_active) {
+            whatever!("invalid standard timing width {}", self.h_active);
+        }
+        if !(60..=123).contains(&self.refresh_rate) {
+            whatever!("invalid standard timing refresh rate {}", self.refresh_rate);
+        }
+        let aspect_ratio = match self.aspect_ratio {
+            StandardAspectRatio::Aspect16x10 => 0,
+            StandardAspectRatio::Aspect4x3 => 1,
+            StandardAspectRatio::Aspect5x4 => 2,
+            StandardAspectRatio::Aspect16x9 => 3,
+        };
+        Ok([
+            (self.h_active / 8 - 31) as _,
+            aspect_ratio << 6 | (self.refresh_rate - 60),
+        ])
+    }
+
+    pub fn v_active(&self) -> u16 {
+        match self.aspect_ratio {
+            StandardAspectRatio::Aspect16x10 => self.h_active * 10 / 16,
+            StandardAspectRatio::Aspect4x3 => self.h_active * 3 / 4,
+            StandardAspectRatio::Aspect5x4 => self.h_active * 4 / 5,
+            StandardAspectRatio::Aspect16x9 => self.h_active * 9 / 16,
+        }
+    }
+}
+
+/// An 18-byte detailed timing descriptor. For interlaced timings, the vertical values are per
+/// field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DetailedTiming {
+    /// The pixel clock in kHz, which EDID stores in multiples of 10.
+    pub pixel_clock_khz: u32,
+    pub h_active: u16,
+    pub h_blanking: u16,
+    pub v_active: u16,
+    pub v_blanking: u16,
+    pub h_front_porch: u16,
+    pub h_sync_width: u16,
+    pub v_front_porch: u8,
+    pub v_sync_width: u8,
+    pub h_image_size_mm: u16,
+    pub v_image_size_mm: u16,
+    pub h_border: u8,
+    pub v_border: u8,
+    /// The raw flags byte, which holds the interlacing, stereo, and sync signal definitions.
+    pub flags: u8,
+}
+
+impl DetailedTiming {
+    pub const FLAG_INTERLACED: u8 = 0x80;
+    /// Digital separate sync, as used by HDMI and DVI. Combine with the polarity flags.
+    pub const FLAG_DIGITAL_SEPARATE_SYNC: u8 = 0x18;
+    pub const FLAG_V_SYNC_POSITIVE: u8 = 0x04;
+    pub const FLAG_H_SYNC_POSITIVE: u8 = 0x02;
+
+    fn parse(bytes: &[u8]) -> Self {
+        let hi4 = |byte: u8, shift: u8| (((byte >> shift) & 0x0f) as u16) << 8;
+        let hi2 = |shift: u8| ((bytes[11] >> shift) & 0x03) as u16;
+        Self {
+            pixel_clock_khz: u16::from_le_bytes([bytes[0], bytes[1]]) as u32 * 10,
+            h_active: bytes[2] as u16 | hi4(bytes[4], 4),
+            h_blanking: bytes[3] as u16 | hi4(bytes[4], 0),
+            v_active: bytes[5] as u16 | hi4(bytes[7], 4),
+            v_blanking: bytes[6] as u16 | hi4(bytes[7], 0),
+            h_front_porch: bytes[8] as u16 | hi2(6) << 8,
+            h_sync_width: bytes[9] as u16 | hi2(4) << 8,
+            v_front_porch: (bytes[10] >> 4 | (hi2(2) as u8) << 4),
+            v_sync_width: (bytes[10] & 0x0f | (hi2(0) as u8) << 4),
+            h_image_size_mm: bytes[12] as u16 | hi4(bytes[14], 4),
+            v_image_size_mm: bytes[13] as u16 | hi4(bytes[14], 0),
+            h_border: bytes[15],
+            v_border: bytes[16],
+            flags: bytes[17],
+        }
+    }
+
+    fn to_bytes(self) -> Result<[u8; DESCRIPTOR_SIZE]> {
+        if self.pixel_clock_khz % 10 != 0 || !(10..=655350).contains(&self.pixel_clock_khz) {
+            whatever!(
+                "invalid detailed timing pixel clock {}",
+                self.pixel_clock_khz
+            );
+        }
+        if [
+            self.h_active,
+            self.h_blanking,
+            self.v_active,
+            self.v_blanking,
+            self.h_image_size_mm,
+            self.v_image_size_mm,
+        ]
+        .iter()
+        .any(|v| *v > 0x0fff)
+            || self.h_front_porch > 0x03ff
+            || self.h_sync_width > 0x03ff
+            || self.v_front_porch > 0x3f
+            || self.v_sync_width > 0x3f
+        {
+            whatever!("detailed timing value out of range");
+        }
+        let clock = ((self.pixel_clock_khz / 10) as u16).to_le_bytes();
+        let hi4 = |a: u16, b: u16| ((a >> 8) << 4 | (b >> 8)) as u8;
+        Ok([
+            clock[0],
+            clock[1],
+            self.h_active as _,
+            self.h_blanking as _,
+            hi4(self.h_active, self.h_blanking),
+            self.v_active as _,
+            self.v_blanking as _,
+            hi4(self.v_active, self.v_blanking),
+            self.h_front_porch as _,
+            self.h_sync_width as _,
+            (self.v_front_porch & 0x0f) << 4 | (self.v_sync_width & 0x0f),
+            ((self.h_front_porch >> 8) << 6
+                | (self.h_sync_width >> 8) << 4
+                | ((self.v_front_porch >> 4) << 2) as u16
+                | (self.v_sync_width >> 4) as u16) as _,
+            self.h_image_size_mm as _,
+            self.v_image_size_mm as _,
+            hi4(self.h_image_size_mm, self.v_image_size_mm),
+            self.h_border,
+            self.v_border,
+            self.flags,
+        ])
+    }
+
+    pub fn is_interlaced(&self) -> bool {
+        self.flags & Self::FLAG_INTERLACED != 0
+    }
+
+    pub fn h_total(&self) -> u32 {
+        self.h_active as u32 + self.h_blanking as u32
+    }
+
+    pub fn v_total(&self) -> u32 {
+        self.v_active as u32 + self.v_blanking as u32
+    }
+
+    /// Returns the refresh rate in Hz, which is the field rate for interlaced timings.
+    pub fn refresh_rate(&self) -> f64 {
+        let total = self.h_total() * self.v_total();
+        if total == 0 {
+            return 0.0;
+        }
+        self.pixel_clock_khz as f64 * 1000.0 / total as f64
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DisplayRangeLimits {
+    pub min_vertical_rate_hz: u16,
+    pub max_vertical_rate_hz: u16,
+    pub min_horizontal_rate_khz: u16,
+    pub max_horizontal_rate_khz: u16,
+    /// The maximum pixel clock in MHz, which EDID stores in multiples of 10.
+    pub max_pixel_clock_mhz: u16,
+    /// The raw video timing support flags and data, starting at byte 10 of the descriptor.
+    pub timing_support: [u8; 8],
+}
+
+impl DisplayRangeLimits {
+    fn parse(bytes: &[u8]) -> Self {
+        let offsets = bytes[4];
+        let rate = |value: u8, shift: u8, max: bool| {
+            let offset = match (offsets >> shift) & 0x03 {
+                0x02 if max => 255,
+                0x03 => 255,
+                _ => 0,
+            };
+            value as u16 + offset
+        };
+        let mut timing_support = [0; 8];
+        timing_support.copy_from_slice(&bytes[10..18]);
+        Self {
+            min_vertical_rate_hz: rate(bytes[5], 0, false),
+            max_vertical_rate_hz: rate(bytes[6], 0, true),
+            min_horizontal_rate_khz: rate(bytes[7], 2, false),
+            max_horizontal_rate_khz: rate(bytes[8], 2, true),
+            max_pixel_clock_mhz: bytes[9] as u16 * 10,
+            timing_support,
+        }
+    }
+
+    fn to_bytes(self) -> Result<[u8; DESCRIPTOR_SIZE]> {
+        let offsets = |min: u16, max: u16| -> Result<(u8, u8, u8)> {
+            if min > max || max > 510 {
+                whatever!("invalid display range {}-{}", min, max);
+            }
+            Ok(match (min > 255, max > 255) {
+                (true, _) => (0x03, (min - 255) as _, (max - 255) as _),
+                (false, true) => (0x02, min as _, (max - 255) as _),
+                (false, false) => (0x00, min as _, max as _),
+            })
+        };
+        let (v_offsets, v_min, v_max) =
+            offsets(self.min_vertical_rate_hz, self.max_vertical_rate_hz)?;
+        let (h_offsets, h_min, h_max) =
+            offsets(self.min_horizontal_rate_khz, self.max_horizontal_rate_khz)?;
+        if self.max_pixel_clock_mhz % 10 != 0 || self.max_pixel_clock_mhz > 2550 {
+            whatever!("invalid maximum pixel clock {}", self.max_pixel_clock_mhz);
+        }
+        let mut bytes = [0; DESCRIPTOR_SIZE];
+        bytes[3] = DESCRIPTOR_TAG_RANGE_LIMITS;
+        bytes[4] = h_offsets << 2 | v_offsets;
+        bytes[5..10].copy_from_slice(&[
+            v_min,
+            v_max,
+            h_min,
+            h_max,
+            (self.max_pixel_clock_mhz / 10) as _,
+        ]);
+        bytes[10..].copy_from_slice(&self.timing_support);
+        Ok(bytes)
+    }
+}
+
+/// One of the 18-byte descriptors in the EDID base block.
+///
+/// Text should be terminated by a line feed and padded with spaces. Text that's padded any other
+/// way, e.g. with NULs, keeps all 13 bytes including its padding, so that it's written back
+/// unchanged. Text that isn't ASCII is kept as `Other`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EdidDescriptor {
+    DetailedTiming(DetailedTiming),
+    /// Up to 13 ASCII characters.
+    ProductName(String),
+    /// Up to 13 ASCII characters.
+    SerialNumber(String),
+    /// Up to 13 ASCII characters.
+    Text(String),
+    RangeLimits(DisplayRangeLimits),
+    /// Any other display descriptor, with `data` holding bytes 4 through 17.
+    Other {
+        tag: u8,
+        data: [u8; 14],
+    },
+}
+
+impl EdidDescriptor {
+    fn parse(bytes: &[u8]) -> Self {
+        if bytes[0] != 0 || bytes[1] != 0 {
+            return Self::DetailedTiming(DetailedTiming::parse(bytes));
+        }
+        let text = &bytes[5..];
+        let text = match text.iter().position(|b| *b == b'\n') {
+            Some(len) if text[len + 1..].iter().all(|b| *b == b' ') => &text[..len],
+            _ => text,
+        };
+        let text = text
+            .is_ascii()
+            .then(|| String::from_utf8_lossy(text).into_owned());
+        match (bytes[3], text) {
+            (DESCRIPTOR_TAG_PRODUCT_NAME, Some(text)) => Self::ProductName(text),
+            (DESCRIPTOR_TAG_SERIAL_NUMBER, Some(text)) => Self::SerialNumber(text),
+            (DESCRIPTOR_TAG_TEXT, Some(text)) => Self::Text(text),
+            (DESCRIPTOR_TAG_RANGE_LIMITS, _) => Self::RangeLimits(DisplayRangeLimits::parse(bytes)),
+            (tag, _) => {
+                let mut data = [0; 14];
+                data.copy_from_slice(&bytes[4..]);
+                Self::Other { tag, data }
+            }
+        }
+    }
+
+    fn to_bytes(&self) -> Result<[u8; DESCRIPTOR_SIZE]> {
+        let text = |tag: u8, text: &str| -> Result<[u8; DESCRIPTOR_SIZE]> {
+            // Text with a line feed is only allowed whole, as kept when parsing.
+            if text.len() > 13 || !text.is_ascii() || (text.len() < 13 && text.contains('\n')) {
+                whatever!("invalid edid descriptor text {:?}", text);
+            }
+            // Text is terminated by a line feed and padded with spaces.
+            let mut bytes = [b' '; DESCRIPTOR_SIZE];
+            bytes[..5].copy_from_slice(&[0, 0, 0, tag, 0]);
+            bytes[5..5 + text.len()].copy_from_slice(text.as_bytes());
+            if text.len() < 13 {
+                bytes[5 + text.len()] = b'\n';
+            }
+            Ok(bytes)
+        };
+        match self {
+            Self::DetailedTiming(timing) => timing.to_bytes(),
+            Self::ProductName(name) => text(DESCRIPTOR_TAG_PRODUCT_NAME, name),
+            Self::SerialNumber(serial) => text(DESCRIPTOR_TAG_SERIAL_NUMBER, serial),
+            Self::Text(s) => text(DESCRIPTOR_TAG_TEXT, s),
+            Self::RangeLimits(limits) => limits.to_bytes(),
+            Self::Other { tag, data } => {
+                let mut bytes = [0; DESCRIPTOR_SIZE];
+                bytes[3] = *tag;
+                bytes[4..].copy_from_slice(data);
+                Ok(bytes)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EdidExtension {
+    Cea(CeaExtension),
+    /// Any other extension block. This must be 128 bytes, but its checksum is recalculated when
+    /// encoding.
+    Other(Vec<u8>),
+}
+
+impl EdidExtension {
+    fn parse(block: &[u8]) -> Result<Self> {
+        verify_block(block)?;
+        Ok(match block[0] {
+            CEA_EXTENSION_TAG => Self::Cea(CeaExtension::parse(block)?),
+            _ => Self::Other(block.to_vec()),
+        })
+    }
+
+    fn to_bytes(&self) -> Result<[u8; BLOCK_SIZE]> {
+        let mut block = match self {
+            Self::Cea(cea) => cea.to_bytes()?,
+            Self::Other(data) => {
+                let Ok(block) = data.as_slice().try_into() else {
+                    whatever!("edid extension blocks must be {} bytes", BLOCK_SIZE);
+                };
+                block
+            }
+        };
+        block[BLOCK_SIZE - 1] = edid_checksum(&block);
+        Ok(block)
+    }
+}
+
+/// A CEA-861 extension block, which is how HDMI sinks advertise their video formats, audio
+/// formats, and HDR support.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CeaExtension {
+    pub revision: u8,
+    pub underscan: bool,
+    pub basic_audio: bool,
+    pub ycbcr444: bool,
+    pub ycbcr422: bool,
+    /// The number of native formats among the detailed timings.
+    pub native_format_count: u8,
+    pub data_blocks: Vec<CeaDataBlock>,
+    pub detailed_timings: Vec<DetailedTiming>,
+}
+
+impl Default for CeaExtension {
+    fn default() -> Self {
+        Self {
+            revision: 3,
+            underscan: false,
+            basic_audio: false,
+            ycbcr444: false,
+            ycbcr422: false,
+            native_format_count: 0,
+            data_blocks: Vec::new(),
+            detailed_timings: Vec::new(),
+        }
+    }
+}
+
+impl CeaExtension {
+    fn parse(block: &[u8]) -> Result<Self> {
+        let dtd_offset = block[2] as usize;
+        if dtd_offset != 0 && !(4..BLOCK_SIZE).contains(&dtd_offset) {
+            whatever!("invalid cea extension dtd offset {}", dtd_offset);
+        }
+
+        let mut data_blocks = Vec::new();
+        if dtd_offset > 4 {
+            let mut data = &block[4..dtd_offset];
+            while let Some((header, rest)) = data.split_first() {
+                let len = (header & 0x1f) as usize;
+                if len > rest.len() {
+                    whatever!("cea data block overruns the data block collection");
+                }
+                data_blocks.push(CeaDataBlock::parse(header >> 5, &rest[..len]));
+                data = &rest[len..];
+            }
+        }
+
+        let detailed_timings = if dtd_offset == 0 {
+            Vec::new()
+        } else {
+            block[dtd_offset..BLOCK_SIZE - 1]
+                .chunks_exact(DESCRIPTOR_SIZE)
+                .take_while(|bytes| bytes[0] != 0 || bytes[1] != 0)
+                .map(DetailedTiming::parse)
+                .collect()
+        };
+
+        Ok(Self {
+            revision: block[1],
+            underscan: block[3] & 0x80 != 0,
+            basic_audio: block[3] & 0x40 != 0,
+            ycbcr444: block[3] & 0x20 != 0,
+            ycbcr422: block[3] & 0x10 != 0,
+            native_format_count: block[3] & 0x0f,
+            data_blocks,
+            detailed_timings,
+        })
+    }
+
+    fn to_bytes(&self) -> Result<[u8; BLOCK_SIZE]> {
+        let mut block = [0; BLOCK_SIZE];
+        block[0] = CEA_EXTENSION_TAG;
+        block[1] = self.revision;
+        if self.native_format_count > 0x0f {
+            whatever!("invalid native format count {}", self.native_format_count);
+        }
+        block[3] = (self.underscan as u8) << 7
+            | (self.basic_audio as u8) << 6
+            | (self.ycbcr444 as u8) << 5
+            | (self.ycbcr422 as u8) << 4
+            | self.native_format_count;
+
+        let mut offset = 4;
+        for data_block in &self.data_blocks {
+            let (tag, payload) = data_block.to_bytes()?;
+            if payload.len() > 0x1f {
+                whatever!("cea data block payload is too long");
+            }
+            if offset + 1 + payload.len() > BLOCK_SIZE - 1 {
+                whatever!("cea data blocks don't fit in the extension block");
+            }
+            block[offset] = tag << 5 | payload.len() as u8;
+            block[offset + 1..offset + 1 + payload.len()].copy_from_slice(&payload);
+            offset += 1 + payload.len();
+        }
+        block[2] = offset as _;
+
+        for timing in &self.detailed_timings {
+            if offset + DESCRIPTOR_SIZE > BLOCK_SIZE - 1 {
+                whatever!("cea detailed timings don't fit in the extension block");
+            }
+            block[offset..offset + DESCRIPTOR_SIZE].copy_from_slice(&timing.to_bytes()?);
+            offset += DESCRIPTOR_SIZE;
+        }
+        Ok(block)
+    }
+
+    pub fn video_descriptors(&self) -> impl Iterator<Item = &ShortVideoDescriptor> {
+        self.data_blocks.iter().flat_map(|block| match block {
+            CeaDataBlock::Video(descriptors) => descriptors.as_slice(),
+            _ => &[],
+        })
+    }
+
+    pub fn audio_descriptors(&self) -> impl Iterator<Item = &ShortAudioDescriptor> {
+        self.data_blocks.iter().flat_map(|block| match block {
+            CeaDataBlock::Audio(descriptors) => descriptors.as_slice(),
+            _ => &[],
+        })
+    }
+
+    pub fn hdr_static_metadata(&self) -> Option<&HdrStaticMetadataBlock> {
+        self.data_blocks.iter().find_map(|block| match block {
+            CeaDataBlock::HdrStaticMetadata(metadata) => Some(metadata),
+            _ => None,
+        })
+    }
+}
+
+/// A data block within a CEA-861 extension.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CeaDataBlock {
+    Video(Vec<ShortVideoDescriptor>),
+    Audio(Vec<ShortAudioDescriptor>),
+    HdrStaticMetadata(HdrStaticMetadataBlock),
+    /// Any other data block, e.g. the HDMI vendor-specific data block. For extended tag blocks
+    /// (tag 7), `data` starts with the extended tag.
+    Other {
+        tag: u8,
+        data: Vec<u8>,
+    },
+}
+
+impl CeaDataBlock {
+    fn parse(tag: u8, data: &[u8]) -> Self {
+        match (tag, data.first()) {
+            (CEA_TAG_VIDEO, _) => Self::Video(
+                data.iter()
+                    .map(|b| ShortVideoDescriptor::parse(*b))
+                    .collect(),
+            ),
+            (CEA_TAG_AUDIO, _) if data.len() % 3 == 0 => Self::Audio(
+                data.chunks_exact(3)
+                    .map(ShortAudioDescriptor::parse)
+                    .collect(),
+            ),
+            (CEA_TAG_EXTENDED, Some(&CEA_EXTENDED_TAG_HDR_STATIC_METADATA)) if data.len() >= 3 => {
+                Self::HdrStaticMetadata(HdrStaticMetadataBlock::parse(&data[1..]))
+            }
+            _ => Self::Other {
+                tag,
+                data: data.to_vec(),
+            },
+        }
+    }
+
+    fn to_bytes(&self) -> Result<(u8, Vec<u8>)> {
+        Ok(match self {
+            Self::Video(descriptors) => (
+                CEA_TAG_VIDEO,
+                descriptors
+                    .iter()
+                    .map(|d| d.to_byte())
+                    .collect::<Result<_>>()?,
+            ),
+            Self::Audio(descriptors) => (
+                CEA_TAG_AUDIO,
+                descriptors
+                    .iter()
+                    .map(|d| d.to_bytes())
+                    .collect::<Result<Vec<_>>>()?
+                    .concat(),
+            ),
+            Self::HdrStaticMetadata(metadata) => (CEA_TAG_EXTENDED, metadata.to_bytes()?),
+            Self::Other { tag, data } => {
+                if *tag > 7 {
+                    whatever!("invalid cea data block tag {}", tag);
+                }
+                (*tag, data.clone())
+            }
+        })
+    }
+}
+
+/// A CEA-861 video format, identified by its VIC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ShortVideoDescriptor {
+    /// The VIC, from 1 to 127 or, since CTA-861-F, from 193 to 255.
+    pub vic: u8,
+    /// Whether this is a native format of the display. Only VICs 1 through 64 can be native.
+    pub native: bool,
+}
+
+impl ShortVideoDescriptor {
+    pub fn new(vic: u8) -> Self {
+        Self { vic, native: false }
+    }
+
+    fn parse(byte: u8) -> Self {
+        match byte {
+            129..=192 => Self {
+                vic: byte & 0x7f,
+                native: true,
+            },
+            _ => Self {
+                vic: byte,
+                native: false,
+            },
+        }
+    }
+
+    fn to_byte(self) -> Result<u8> {
+        match (self.vic, self.native) {
+            (0 | 128..=192, _) => whatever!("invalid vic {}", self.vic),
+            (1..=64, true) => Ok(self.vic | 0x80),
+            (_, true) => whatever!("vic {} can't be native", self.vic),
+            (_, false) => Ok(self.vic),
+        }
+    }
+}
+
+bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct AudioSampleRates: u8 {
+        const HZ_32000 = 0x01;
+        const HZ_44100 = 0x02;
+        const HZ_48000 = 0x04;
+        const HZ_88200 = 0x08;
+        const HZ_96000 = 0x10;
+        const HZ_176400 = 0x20;
+        const HZ_192000 = 0x40;
+    }
+}
+
+/// A CEA-861 audio format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ShortAudioDescriptor {
+    /// The audio format code, e.g. 1 for LPCM.
+    pub format: u8,
+    /// The maximum number of channels, from 1 to 8.
+    pub channel_count: u8,
+    pub sample_rates: AudioSampleRates,
+    /// For LPCM, the supported bit depths (0x01 for 16, 0x02 for 20, 0x04 for 24). For other
+    /// formats, this depends on the format, e.g. the maximum bit rate divided by 8 kbps.
+    pub detail: u8,
+}
+
+impl ShortAudioDescriptor {
+    pub const FORMAT_LPCM: u8 = 1;
+
+    fn parse(bytes: &[u8]) -> Self {
+        Self {
+            format: (bytes[0] >> 3) & 0x0f,
+            channel_count: (bytes[0] & 0x07) + 1,
+            sample_rates: AudioSampleRates::from_bits_retain(bytes[1] & 0x7f),
+            detail: bytes[2],
+        }
+    }
+
+    fn to_bytes(self) -> Result<[u8; 3]> {
+        if self.format > 0x0f || !(1..=8).contains(&self.channel_count) {
+            whatever!("invalid short audio descriptor");
+        }
+        Ok([
+            self.format << 3 | (self.channel_count - 1),
+            self.sample_rates.bits() & 0x7f,
+            self.detail,
+        ])
+    }
+}
+
+bitflags! {
+    /// The electro-optical transfer functions a sink supports.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct SupportedEotfs: u8 {
+        const TRADITIONAL_SDR = 0x01;
+        const TRADITIONAL_HDR = 0x02;
+        const SMPTE_ST_2084 = 0x04;
+        const HLG = 0x08;
+    }
+}
+
+/// The HDR static metadata data block. The luminance values are the raw CEA-861.3 code values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HdrStaticMetadataBlock {
+    pub eotfs: SupportedEotfs,
+    /// The supported static metadata descriptors. Bit 0 is static metadata type 1.
+    pub metadata_descriptors: u8,
+    pub max_luminance: Option<u8>,
+    pub max_frame_average_luminance: Option<u8>,
+    pub min_luminance: Option<u8>,
+}
+
+impl HdrStaticMetadataBlock {
+    fn parse(data: &[u8]) -> Self {
+        Self {
+            eotfs: SupportedEotfs::from_bits_retain(data[0]),
+            metadata_descriptors: data[1],
+            max_luminance: data.get(2).copied(),
+            max_frame_average_luminance: data.get(3).copied(),
+            min_luminance: data.get(4).copied(),
+        }
+    }
+
+    fn to_bytes(self) -> Result<Vec<u8>> {
+        let mut data = vec![
+            CEA_EXTENDED_TAG_HDR_STATIC_METADATA,
+            self.eotfs.bits(),
+            self.metadata_descriptors,
+        ];
+        let luminances = [
+            self.max_luminance,
+            self.max_frame_average_luminance,
+            self.min_luminance,
+        ];
+        // Each luminance value is optional, but only if the ones after it are absent too.
+        let count = luminances.iter().take_while(|v| v.is_some()).count();
+        if luminances[count..].iter().any(|v| v.is_some()) {
+            whatever!("hdr static metadata luminances must be given in order");
+        }
+        data.extend(luminances.iter().flatten());
+        Ok(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn corpus() -> Vec<(String, Vec<u8>)> {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/testdata/edid");
+        let mut corpus: Vec<_> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| {
+                let path = entry.unwrap().path();
+                (
+                    path.file_name().unwrap().to_string_lossy().into_owned(),
+                    std::fs::read(&path).unwrap(),
+                )
+            })
+            .collect();
+        corpus.sort();
+        assert!(!corpus.is_empty());
+        corpus
+    }
+
+    #[test]
+    fn test_corpus_round_trip() {
+        for (name, data) in corpus() {
+            let edid = Edid::parse(&data).unwrap_or_else(|e| panic!("{}: {}", name, e));
+            assert_eq!(edid.to_bytes().unwrap(), data, "{}", name);
+            assert_eq!(edid.version, 1, "{}", name);
+            assert!(edid.preferred_timing().is_some(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_parse_1080p_hdr() {
+        let data = std::fs::read(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/edid/hdmi-1080p-hdr.bin"
+        ))
+        .unwrap();
+        let edid = Edid::parse(&data).unwrap();
+        assert_eq!(edid.manufacturer_id, "MWL");
+        assert_eq!(edid.product_name(), Some("Test 1080p"));
+        assert!(edid.is_digital());
+
+        let timing = edid.preferred_timing().unwrap();
+        assert_eq!((timing.h_active, timing.v_active), (1920, 1080));
+        assert_eq!(timing.pixel_clock_khz, 148500);
+        assert!(!timing.is_interlaced());
+        assert!((timing.refresh_rate() - 60.0).abs() < 0.01);
+
+        let cea = edid.cea_extension().unwrap();
+        assert!(cea.video_descriptors().any(|d| *d
+            == ShortVideoDescriptor {
+                vic: 16,
+                native: true
+            }));
+        let audio = cea.audio_descriptors().next().unwrap();
+        assert_eq!(audio.format, ShortAudioDescriptor::FORMAT_LPCM);
+        assert_eq!(audio.channel_count, 2);
+        assert!(audio.sample_rates.contains(AudioSampleRates::HZ_48000));
+        let hdr = cea.hdr_static_metadata().unwrap();
+        assert!(hdr.eotfs.contains(SupportedEotfs::SMPTE_ST_2084));
+        assert!(hdr.eotfs.contains(SupportedEotfs::HLG));
+    }
+
+    #[test]
+    fn test_round_trip_uncommon_encodings() {
+        let original = std::fs::read(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/edid/hdmi-1080p-hdr.bin"
+        ))
+        .unwrap();
+        let name = (0x36..0x7e)
+            .step_by(DESCRIPTOR_SIZE)
+            .find(|&offset| original[offset + 3] == DESCRIPTOR_TAG_PRODUCT_NAME)
+            .unwrap();
+        let mut svd = 4;
+        while original[BLOCK_SIZE + svd] >> 5 != CEA_TAG_VIDEO {
+            svd += 1 + (original[BLOCK_SIZE + svd] & 0x1f) as usize;
+        }
+        let svd = BLOCK_SIZE + svd + 1;
+
+        for padding in [b"Test 1080p\n\0\0", b"Test 1080p   "] {
+            let mut data = original.clone();
+            // Unused standard timings stored as 00 00 rather than 01 01.
+            data[0x26..0x36].fill(0);
+            data[name + 5..name + DESCRIPTOR_SIZE].copy_from_slice(padding);
+            // An 8-bit VIC, as used by CTA-861-F and later.
+            data[svd] = 199;
+            data[BLOCK_SIZE - 1] = edid_checksum(&data[..BLOCK_SIZE]);
+            data[2 * BLOCK_SIZE - 1] = edid_checksum(&data[BLOCK_SIZE..]);
+
+            let edid = Edid::parse(&data).unwrap();
+            assert_eq!(edid.standard_timings, [None; 8]);
+            assert_eq!(edid.product_name(), Some("Test 1080p"));
+            assert!(edid
+                .cea_extension()
+                .unwrap()
+                .video_descriptors()
+                .any(|d| *d == ShortVideoDescriptor::new(199)));
+            assert_eq!(edid.to_bytes().unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn test_checksum() {
+        for (name, data) in corpus() {
+            for block in data.chunks_exact(BLOCK_SIZE) {
+                assert_eq!(edid_checksum(block), block[BLOCK_SIZE - 1], "{}", name);
+            }
+            let mut corrupt = data.clone();
+            corrupt[0x20] ^= 0xff;
+            assert!(Edid::parse(&corrupt).is_err(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_build() {
+        let mut edid = Edid::parse(
+            &std::fs::read(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/testdata/edid/hdmi-1080p-hdr.bin"
+            ))
+            .unwrap(),
+        )
+        .unwrap();
+        edid.descriptors[1] = EdidDescriptor::ProductName("Renamed".to_string());
+        edid.cea_extension_mut()
+            .unwrap()
+            .data_blocks
+            .retain(|block| !matches!(block, CeaDataBlock::HdrStaticMetadata(_)));
+        let data = edid.to_bytes().unwrap();
+        let parsed = Edid::parse(&data).unwrap();
+        assert_eq!(parsed.product_name(), Some("Renamed"));
+        assert!(parsed
+            .cea_extension()
+            .unwrap()
+            .hdr_static_metadata()
+            .is_none());
+        assert_eq!(parsed, edid);
+
+        edid.descriptors[1] = EdidDescriptor::ProductName("Far too long a name".to_string());
+        assert!(edid.to_bytes().is_err());
+    }
+}
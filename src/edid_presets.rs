@@ -0,0 +1,390 @@
+use super::{
+    AudioSampleRates, CeaDataBlock, CeaExtension, Chromaticity, DetailedTiming, DisplayRangeLimits,
+    Edid, EdidDescriptor, EdidExtension, HdrStaticMetadataBlock, Result, ShortAudioDescriptor,
+    ShortVideoDescriptor, SupportedEotfs, UniversalCaptureFamilyChannel,
+};
+use snafu::prelude::*;
+use std::{fmt, str::FromStr};
+
+/// A named EDID for restricting what sources send, e.g. from a control panel.
+///
+/// HDMI formats don't distinguish 59.94 Hz from 60 Hz, so sources may still send either for the
+/// 59.94 presets. Their preferred timing is 59.94 Hz, which most sources honor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EdidPreset {
+    Only1080p5994,
+    Only1080p50,
+    Only1080i5994,
+    Only720p5994,
+    Uhd2160p50Hdr10,
+    Uhd2160p5994Hdr10,
+    /// Common HD formats with only stereo LPCM audio.
+    StereoPcmOnly,
+    /// Common HD and UHD formats without HDR.
+    NoHdr,
+}
+
+enum Audio {
+    Stereo,
+    Multichannel,
+}
+
+struct PresetSpec {
+    /// The VIC of the preferred detailed timing, and whether it's at the 1000/1001 rate.
+    preferred: (u8, bool),
+    /// The advertised VICs. If the first isn't the preferred timing's, it's made the preferred
+    /// format via a video format preference data block.
+    vics: &'static [u8],
+    audio: Audio,
+    uhd: bool,
+    hdr: bool,
+}
+
+const HD_VICS: &[u8] = &[16, 31, 4, 19, 5, 20];
+const HD_UHD_VICS: &[u8] = &[16, 31, 4, 19, 5, 20, 97, 96];
+
+impl EdidPreset {
+    pub const ALL: [EdidPreset; 8] = [
+        Self::Only1080p5994,
+        Self::Only1080p50,
+        Self::Only1080i5994,
+        Self::Only720p5994,
+        Self::Uhd2160p50Hdr10,
+        Self::Uhd2160p5994Hdr10,
+        Self::StereoPcmOnly,
+        Self::NoHdr,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Only1080p5994 => "1080p59.94 only",
+            Self::Only1080p50 => "1080p50 only",
+            Self::Only1080i5994 => "1080i59.94 only",
+            Self::Only720p5994 => "720p59.94 only",
+            Self::Uhd2160p50Hdr10 => "2160p50 HDR10",
+            Self::Uhd2160p5994Hdr10 => "2160p59.94 HDR10",
+            Self::StereoPcmOnly => "stereo PCM only",
+            Self::NoHdr => "no HDR",
+        }
+    }
+
+    fn spec(&self) -> PresetSpec {
+        let only = |vic: &'static [u8], ntsc| PresetSpec {
+            preferred: (vic[0], ntsc),
+            vics: vic,
+            audio: Audio::Multichannel,
+            uhd: false,
+            hdr: false,
+        };
+        match self {
+            Self::Only1080p5994 => only(&[16], true),
+            Self::Only1080p50 => only(&[31], false),
+            Self::Only1080i5994 => only(&[5], true),
+            Self::Only720p5994 => only(&[4], true),
+            // 2160p50's front porch is too long for a detailed timing, so 1080p50 is used.
+            Self::Uhd2160p50Hdr10 => PresetSpec {
+                preferred: (31, false),
+                uhd: true,
+                hdr: true,
+                ..only(&[96, 31], false)
+            },
+            Self::Uhd2160p5994Hdr10 => PresetSpec {
+                uhd: true,
+                hdr: true,
+                ..only(&[97], true)
+            },
+            Self::StereoPcmOnly => PresetSpec {
+                audio: Audio::Stereo,
+                ..only(HD_VICS, true)
+            },
+            Self::NoHdr => PresetSpec {
+                uhd: true,
+                ..only(HD_UHD_VICS, true)
+            },
+        }
+    }
+
+    /// Builds the preset's EDID, which has a single CEA-861 extension.
+    pub fn build(&self) -> Edid {
+        let spec = self.spec();
+        let (preferred_vic, ntsc) = spec.preferred;
+        let mut preferred = vic_timing(preferred_vic);
+        if ntsc {
+            // Round to the 10 kHz units that EDID stores the pixel clock in.
+            preferred.pixel_clock_khz = (preferred.pixel_clock_khz * 1000 / 1001 + 5) / 10 * 10;
+        }
+        let mut range_timings: Vec<_> = spec.vics.iter().map(|vic| vic_timing(*vic)).collect();
+        range_timings.push(preferred);
+
+        let mut data_blocks = vec![CeaDataBlock::Video(
+            spec.vics
+                .iter()
+                .map(|vic| ShortVideoDescriptor {
+                    vic: *vic,
+                    native: *vic == preferred_vic && *vic <= 64,
+                })
+                .collect(),
+        )];
+        let lpcm = |channel_count, sample_rates| ShortAudioDescriptor {
+            format: ShortAudioDescriptor::FORMAT_LPCM,
+            channel_count,
+            sample_rates,
+            // 16, 20, and 24 bits
+            detail: 0x07,
+        };
+        let basic_rates =
+            AudioSampleRates::HZ_32000 | AudioSampleRates::HZ_44100 | AudioSampleRates::HZ_48000;
+        let (audio, speakers) = match spec.audio {
+            // FL/FR
+            Audio::Stereo => (vec![lpcm(2, basic_rates)], 0x01),
+            // FL/FR, LFE, FC, RL/RR, RLC/RRC
+            Audio::Multichannel => (
+                vec![lpcm(2, basic_rates), lpcm(8, AudioSampleRates::all())],
+                0x4f,
+            ),
+        };
+        if spec.vics[0] != preferred_vic {
+            data_blocks.push(CeaDataBlock::Other {
+                tag: 7,
+                data: vec![0x0d, spec.vics[0]],
+            });
+        }
+        data_blocks.push(CeaDataBlock::Audio(audio));
+        data_blocks.push(CeaDataBlock::Other {
+            tag: 4,
+            data: vec![speakers, 0x00, 0x00],
+        });
+        // The HDMI vendor-specific data block with physical address 1.0.0.0, and its maximum TMDS
+        // clock in units of 5 MHz.
+        data_blocks.push(CeaDataBlock::Other {
+            tag: 3,
+            data: vec![
+                0x03,
+                0x0c,
+                0x00,
+                0x10,
+                0x00,
+                0x00,
+                if spec.uhd { (340 / 5) as u8 } else { 165 / 5 },
+            ],
+        });
+        if spec.uhd {
+            // The HDMI Forum vendor-specific data block, which is needed for 600 MHz TMDS clocks.
+            data_blocks.push(CeaDataBlock::Other {
+                tag: 3,
+                data: vec![0xd8, 0x5d, 0xc4, 0x01, (600 / 5) as u8, 0x80, 0x00],
+            });
+        }
+        if spec.hdr {
+            // The colorimetry data block, advertising BT.2020 RGB and YCbCr.
+            data_blocks.push(CeaDataBlock::Other {
+                tag: 7,
+                data: vec![0x05, 0xc0, 0x00],
+            });
+            data_blocks.push(CeaDataBlock::HdrStaticMetadata(HdrStaticMetadataBlock {
+                eotfs: SupportedEotfs::TRADITIONAL_SDR | SupportedEotfs::SMPTE_ST_2084,
+                metadata_descriptors: 0x01,
+                max_luminance: None,
+                max_frame_average_luminance: None,
+                min_luminance: None,
+            }));
+        }
+
+        Edid {
+            manufacturer_id: "MWL".to_string(),
+            product_code: 0,
+            serial_number: 0,
+            week: 0,
+            year: 2024,
+            version: 1,
+            revision: 4,
+            // Digital, 8 bits per color, HDMI-a
+            video_input_definition: 0xa2,
+            screen_size: (71, 40),
+            gamma: 120,
+            // RGB 4:4:4, YCbCr 4:4:4 and 4:2:2, and the preferred timing is native
+            feature_support: 0x1a,
+            // BT.709 primaries with a D65 white point
+            chromaticity: Chromaticity {
+                red: (655, 338),
+                green: (307, 614),
+                blue: (154, 61),
+                white: (320, 337),
+            },
+            // 640x480 at 60 Hz, which all HDMI sinks must support
+            established_timings: [0x20, 0x00, 0x00],
+            standard_timings: [None; 8],
+            unused_standard_timing: [0x01, 0x01],
+            descriptors: vec![
+                EdidDescriptor::DetailedTiming(preferred),
+                EdidDescriptor::ProductName("Magewell".to_string()),
+                EdidDescriptor::RangeLimits(range_limits(&range_timings)),
+            ],
+            extensions: vec![EdidExtension::Cea(CeaExtension {
+                revision: 3,
+                underscan: false,
+                basic_audio: true,
+                ycbcr444: true,
+                ycbcr422: true,
+                native_format_count: 1,
+                data_blocks,
+                detailed_timings: Vec::new(),
+            })],
+        }
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        self.build().to_bytes()
+    }
+
+    /// Writes the preset's EDID to the channel.
+    pub fn apply<C: UniversalCaptureFamilyChannel + ?Sized>(&self, channel: &mut C) -> Result<()> {
+        channel.set_edid(&self.to_bytes()?)
+    }
+}
+
+impl fmt::Display for EdidPreset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for EdidPreset {
+    type Err = super::Error;
+
+    /// Parses a preset by its name, ignoring case.
+    fn from_str(s: &str) -> Result<Self> {
+        match Self::ALL
+            .iter()
+            .find(|preset| preset.name().eq_ignore_ascii_case(s))
+        {
+            Some(preset) => Ok(*preset),
+            None => whatever!("unknown edid preset {:?}", s),
+        }
+    }
+}
+
+/// Returns the CEA-861 timing for the VICs used by the presets, at the integer frame rate.
+fn vic_timing(vic: u8) -> DetailedTiming {
+    let (pixel_clock_khz, h_active, h_blanking, h_front_porch, h_sync_width) = match vic {
+        4 => (74250, 1280, 370, 110, 40),
+        19 => (74250, 1280, 700, 440, 40),
+        5 => (74250, 1920, 280, 88, 44),
+        20 => (74250, 1920, 720, 528, 44),
+        16 => (148500, 1920, 280, 88, 44),
+        31 => (148500, 1920, 720, 528, 44),
+        97 => (594000, 3840, 560, 176, 88),
+        96 => (594000, 3840, 1440, 1056, 88),
+        _ => unreachable!("no timing for vic {}", vic),
+    };
+    let (v_active, v_blanking, v_front_porch, v_sync_width, interlaced) = match vic {
+        4 | 19 => (720, 30, 5, 5, false),
+        5 | 20 => (540, 22, 2, 5, true),
+        16 | 31 => (1080, 45, 4, 5, false),
+        _ => (2160, 90, 8, 10, false),
+    };
+    DetailedTiming {
+        pixel_clock_khz,
+        h_active,
+        h_blanking,
+        v_active,
+        v_blanking,
+        h_front_porch,
+        h_sync_width,
+        v_front_porch,
+        v_sync_width,
+        h_image_size_mm: 708,
+        v_image_size_mm: 398,
+        h_border: 0,
+        v_border: 0,
+        flags: DetailedTiming::FLAG_DIGITAL_SEPARATE_SYNC
+            | DetailedTiming::FLAG_V_SYNC_POSITIVE
+            | DetailedTiming::FLAG_H_SYNC_POSITIVE
+            | if interlaced {
+                DetailedTiming::FLAG_INTERLACED
+            } else {
+                0
+            },
+    }
+}
+
+/// Returns range limits that just cover the given timings.
+fn range_limits(timings: &[DetailedTiming]) -> DisplayRangeLimits {
+    let (mut v_min, mut v_max, mut h_min, mut h_max, mut clock_max) =
+        (f64::MAX, 0.0f64, f64::MAX, 0.0f64, 0);
+    for timing in timings {
+        let v = timing.refresh_rate();
+        let h = timing.pixel_clock_khz as f64 / timing.h_total() as f64;
+        v_min = v_min.min(v);
+        v_max = v_max.max(v);
+        h_min = h_min.min(h);
+        h_max = h_max.max(h);
+        clock_max = clock_max.max(timing.pixel_clock_khz);
+    }
+    DisplayRangeLimits {
+        min_vertical_rate_hz: v_min.floor() as u16 - 1,
+        max_vertical_rate_hz: v_max.ceil() as u16 + 1,
+        min_horizontal_rate_khz: h_min.floor() as u16 - 1,
+        max_horizontal_rate_khz: h_max.ceil() as u16 + 1,
+        max_pixel_clock_mhz: clock_max.div_ceil(10_000) as u16 * 10,
+        // Range limits only, followed by the usual line feed and padding.
+        timing_support: [0x01, 0x0a, 0x20, 0x20, 0x20, 0x20, 0x20, 0x20],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presets_round_trip() {
+        for preset in EdidPreset::ALL {
+            let edid = preset.build();
+            let data = preset.to_bytes().unwrap();
+            assert_eq!(Edid::parse(&data).unwrap(), edid, "{}", preset);
+            assert_eq!(preset.name().parse::<EdidPreset>().unwrap(), preset);
+        }
+        assert!("nonexistent".parse::<EdidPreset>().is_err());
+    }
+
+    #[test]
+    fn test_preset_contents() {
+        let edid = EdidPreset::Only1080p5994.build();
+        let timing = edid.preferred_timing().unwrap();
+        assert_eq!((timing.h_active, timing.v_active), (1920, 1080));
+        assert!((timing.refresh_rate() - 59.94).abs() < 0.01);
+        let cea = edid.cea_extension().unwrap();
+        assert_eq!(
+            cea.video_descriptors().copied().collect::<Vec<_>>(),
+            vec![ShortVideoDescriptor {
+                vic: 16,
+                native: true
+            }]
+        );
+        assert!(cea.hdr_static_metadata().is_none());
+
+        let edid = EdidPreset::Uhd2160p50Hdr10.build();
+        let cea = edid.cea_extension().unwrap();
+        assert_eq!(cea.video_descriptors().next().unwrap().vic, 96);
+        assert!(cea
+            .hdr_static_metadata()
+            .unwrap()
+            .eotfs
+            .contains(SupportedEotfs::SMPTE_ST_2084));
+
+        let edid = EdidPreset::StereoPcmOnly.build();
+        assert!(edid
+            .cea_extension()
+            .unwrap()
+            .audio_descriptors()
+            .all(|d| d.format == ShortAudioDescriptor::FORMAT_LPCM && d.channel_count == 2));
+
+        let edid = EdidPreset::NoHdr.build();
+        assert!(edid
+            .cea_extension()
+            .unwrap()
+            .hdr_static_metadata()
+            .is_none());
+    }
+}
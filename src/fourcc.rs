@@ -1,7 +1,9 @@
 use super::sys;
+use std::fmt;
 
 /// A four character code representing a pixel format. See
 /// vendor/Magewell_Capture_SDK_Linux_3.3.1.1313/Include/MWFOURCC.h for detailed information.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub struct FourCC(u32);
 
 impl FourCC {
@@ -22,4 +24,33 @@ impl FourCC {
             sys::MWRsLibFourCCCalcImageSize(self.0, width as _, height as _, stride as _) as _
         }
     }
+
+    /// Returns false for planar formats such as NV12 or I420.
+    pub fn is_packed(&self) -> bool {
+        unsafe { sys::MWRsLibFourCCIsPacked(self.0) != 0 }
+    }
+}
+
+impl From<u32> for FourCC {
+    fn from(v: u32) -> Self {
+        Self(v)
+    }
+}
+
+impl fmt::Debug for FourCC {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let chars: String = self
+            .0
+            .to_le_bytes()
+            .iter()
+            .map(|&b| {
+                if b.is_ascii_graphic() || b == b' ' {
+                    b as char
+                } else {
+                    '?'
+                }
+            })
+            .collect();
+        write!(f, "FourCC({:?})", chars)
+    }
 }
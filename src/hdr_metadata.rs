@@ -0,0 +1,183 @@
+use super::{
+    AviColorimetry, AviInfoFrame, Eotf, HdrInfoFrame, InfoFrame, InfoFrameId, InputSpecificStatus,
+    MasteringMetadata, NotifyEvents, NotifyHandle, NotifyMonitor, ProEcoCaptureFamilyChannel,
+    Result, UniversalCaptureFamilyChannel,
+};
+
+/// The HDR signaling in effect when a frame was captured, as needed by encoders to write e.g.
+/// SEI messages or `colr` and `mdcv` boxes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct HdrMetadata {
+    /// The transfer function. This is `TraditionalSdr` if the source doesn't send a Dynamic Range
+    /// and Mastering InfoFrame.
+    pub transfer: Eotf,
+    /// The primaries, as signaled by the AVI InfoFrame.
+    pub colorimetry: AviColorimetry,
+    pub mastering: Option<MasteringMetadata>,
+}
+
+impl HdrMetadata {
+    pub fn new(avi: Option<&AviInfoFrame>, hdr: Option<&HdrInfoFrame>) -> Self {
+        Self {
+            transfer: hdr.map(|hdr| hdr.eotf).unwrap_or_default(),
+            colorimetry: avi.map(|avi| avi.colorimetry).unwrap_or_default(),
+            mastering: hdr.and_then(|hdr| hdr.mastering),
+        }
+    }
+
+    /// Returns true if the transfer function is PQ or HLG.
+    pub fn is_hdr(&self) -> bool {
+        matches!(self.transfer, Eotf::Pq | Eotf::Hlg)
+    }
+
+    /// Builds the metadata from raw InfoFrame packets. Packets that can't be parsed, e.g. due to a
+    /// bad checksum, are treated as absent.
+    pub fn from_packets<'a>(packets: impl IntoIterator<Item = &'a [u8]>) -> Self {
+        let mut avi = None;
+        let mut hdr = None;
+        for infoframe in packets.into_iter().filter_map(|p| InfoFrame::parse(p).ok()) {
+            match infoframe {
+                InfoFrame::Avi(infoframe) => avi = Some(infoframe),
+                InfoFrame::Hdr(infoframe) => hdr = Some(infoframe),
+                _ => {}
+            }
+        }
+        Self::new(avi.as_ref(), hdr.as_ref())
+    }
+
+    /// Reads the metadata from the channel's current InfoFrames. Inputs other than HDMI have no
+    /// InfoFrames, so their metadata is the default. InfoFrames that can't be fetched or parsed
+    /// are treated as absent, so a bad InfoFrame from the source doesn't interrupt capture.
+    pub fn read<C: UniversalCaptureFamilyChannel + ?Sized>(channel: &C) -> Result<Self> {
+        if !matches!(
+            channel.get_input_specific_status()?,
+            InputSpecificStatus::Hdmi(_)
+        ) {
+            return Ok(Self::default());
+        }
+        let valid = channel.get_hdmi_infoframe_valid_flags()?;
+        // An InfoFrame can also disappear between reading the flags and fetching its packet.
+        let packets: Vec<_> = [InfoFrameId::Avi, InfoFrameId::Hdr]
+            .into_iter()
+            .filter(|id| valid.contains(id.mask()))
+            .filter_map(|id| channel.get_hdmi_infoframe_packet(id).ok())
+            .collect();
+        Ok(Self::from_packets(packets.iter().map(Vec::as_slice)))
+    }
+}
+
+/// Keeps track of a channel's HDR metadata by re-reading its InfoFrames whenever the input
+/// specific status changes.
+pub struct HdrMetadataTracker {
+    handle: NotifyHandle,
+    metadata: HdrMetadata,
+}
+
+impl HdrMetadataTracker {
+    pub fn new<C: ProEcoCaptureFamilyChannel + ?Sized>(channel: &C) -> Result<Self> {
+        let handle = channel.register_notify(NotifyEvents::INPUT_SPECIFIC_CHANGE)?;
+        match HdrMetadata::read(channel) {
+            Ok(metadata) => Ok(Self { handle, metadata }),
+            Err(e) => {
+                let _ = channel.unregister_notify(handle);
+                Err(e)
+            }
+        }
+    }
+
+    /// Returns the metadata as of the last `poll`.
+    pub fn metadata(&self) -> HdrMetadata {
+        self.metadata
+    }
+}
+
+impl NotifyMonitor for HdrMetadataTracker {
+    type Output = bool;
+
+    /// Re-reads the metadata if the input specific status has changed, returning true if the
+    /// metadata changed.
+    fn poll<C: ProEcoCaptureFamilyChannel + ?Sized>(&mut self, channel: &C) -> Result<bool> {
+        if channel.get_notify_status(self.handle)?.is_empty() {
+            return Ok(false);
+        }
+        let metadata = HdrMetadata::read(channel)?;
+        let changed = metadata != self.metadata;
+        self.metadata = metadata;
+        Ok(changed)
+    }
+
+    fn close<C: ProEcoCaptureFamilyChannel + ?Sized>(self, channel: &C) -> Result<()> {
+        channel.unregister_notify(self.handle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hdr_metadata() {
+        assert_eq!(HdrMetadata::new(None, None), HdrMetadata::default());
+        assert!(!HdrMetadata::default().is_hdr());
+
+        // BT.2020 with PQ and mastering metadata.
+        let avi = [
+            0x82, 0x02, 0x0d, 0xed, 0x5e, 0xe8, 0x60, 0x61, 0x40, 0x8a, 0x00, 0xae, 0x03, 0x00,
+            0x00, 0x00, 0x00,
+        ];
+        let InfoFrame::Avi(avi) = InfoFrame::parse(&avi).unwrap() else {
+            panic!("expected avi infoframe");
+        };
+        let mastering = MasteringMetadata {
+            display_primaries: [(13250, 34500), (7500, 3000), (34000, 16000)],
+            white_point: (15635, 16450),
+            max_luminance: 1000,
+            min_luminance: 50,
+            max_content_light_level: 1000,
+            max_frame_average_light_level: 400,
+        };
+        let hdr = HdrInfoFrame {
+            eotf: Eotf::Pq,
+            mastering: Some(mastering),
+        };
+        let metadata = HdrMetadata::new(Some(&avi), Some(&hdr));
+        assert!(metadata.is_hdr());
+        assert_eq!(metadata.transfer, Eotf::Pq);
+        assert_eq!(metadata.colorimetry, AviColorimetry::Bt2020);
+        assert_eq!(metadata.mastering, Some(mastering));
+
+        // HLG doesn't require mastering metadata.
+        let hdr = HdrInfoFrame {
+            eotf: Eotf::Hlg,
+            mastering: None,
+        };
+        let metadata = HdrMetadata::new(Some(&avi), Some(&hdr));
+        assert!(metadata.is_hdr());
+        assert_eq!(metadata.mastering, None);
+    }
+
+    #[test]
+    fn test_from_packets() {
+        let avi = [
+            0x82, 0x02, 0x0d, 0xed, 0x5e, 0xe8, 0x60, 0x61, 0x40, 0x8a, 0x00, 0xae, 0x03, 0x00,
+            0x00, 0x00, 0x00,
+        ];
+        let hdr = [
+            0x87, 0x01, 0x1a, 0x91, 0x02, 0x00, 0x34, 0x21, 0xaa, 0x9b, 0x96, 0x19, 0xfc, 0x08,
+            0x48, 0x8a, 0x08, 0x39, 0x13, 0x3d, 0x42, 0x40, 0xe8, 0x03, 0x32, 0x00, 0xe8, 0x03,
+            0x90, 0x01,
+        ];
+        let metadata = HdrMetadata::from_packets([&avi[..], &hdr[..]]);
+        assert_eq!(metadata.transfer, Eotf::Pq);
+        assert_eq!(metadata.colorimetry, AviColorimetry::Bt2020);
+        assert!(metadata.mastering.is_some());
+
+        // An HDR InfoFrame with a bad checksum is treated as absent.
+        let mut corrupt = hdr;
+        corrupt[3] ^= 0xff;
+        let metadata = HdrMetadata::from_packets([&avi[..], &corrupt[..]]);
+        assert_eq!(metadata.transfer, Eotf::TraditionalSdr);
+        assert_eq!(metadata.colorimetry, AviColorimetry::Bt2020);
+        assert_eq!(metadata.mastering, None);
+    }
+}
@@ -0,0 +1,540 @@
+use super::{HdmiPixelEncoding, QuantizationRange, Result};
+use snafu::prelude::*;
+
+const HEADER_SIZE: usize = 4;
+const MAX_PAYLOAD_SIZE: usize = 27;
+
+const TYPE_VENDOR_SPECIFIC: u8 = 0x81;
+const TYPE_AVI: u8 = 0x82;
+const TYPE_SPD: u8 = 0x83;
+const TYPE_AUDIO: u8 = 0x84;
+const TYPE_HDR: u8 = 0x87;
+
+/// The IEEE registration ID of HDMI 1.4b vendor-specific InfoFrames.
+pub const HDMI14B_REGISTRATION_ID: u32 = 0x000c03;
+
+/// A parsed HDMI InfoFrame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InfoFrame {
+    Avi(AviInfoFrame),
+    Audio(AudioInfoFrame),
+    Spd(SpdInfoFrame),
+    VendorSpecific(VendorSpecificInfoFrame),
+    Hdr(HdrInfoFrame),
+    /// Any other InfoFrame, e.g. MPEG source or NTSC VBI.
+    Other {
+        packet_type: u8,
+        version: u8,
+        payload: Vec<u8>,
+    },
+}
+
+impl InfoFrame {
+    /// Parses a packet consisting of the three header bytes, the checksum, and the payload. The
+    /// checksum is verified, and any bytes beyond the length given in the header are ignored.
+    pub fn parse(packet: &[u8]) -> Result<Self> {
+        if packet.len() < HEADER_SIZE {
+            whatever!("infoframe packet is too short");
+        }
+        let (packet_type, version) = (packet[0], packet[1]);
+        let len = (packet[2] & 0x1f) as usize;
+        if len > MAX_PAYLOAD_SIZE {
+            whatever!("infoframe length {} is too large", len);
+        }
+        if packet.len() < HEADER_SIZE + len {
+            whatever!("infoframe length {} exceeds the packet size", len);
+        }
+        let sum = packet[..HEADER_SIZE + len]
+            .iter()
+            .fold(0u8, |sum, b| sum.wrapping_add(*b));
+        if sum != 0 {
+            whatever!("invalid checksum for infoframe type {:#04x}", packet_type);
+        }
+
+        let payload = &packet[HEADER_SIZE..HEADER_SIZE + len];
+        // Fields beyond the payload's length are zero, which the parsers treat as "no data".
+        let mut padded = [0u8; MAX_PAYLOAD_SIZE];
+        padded[..len].copy_from_slice(payload);
+        Ok(match packet_type {
+            TYPE_AVI => Self::Avi(AviInfoFrame::parse(&padded)),
+            TYPE_AUDIO => Self::Audio(AudioInfoFrame::parse(&padded)),
+            TYPE_SPD => Self::Spd(SpdInfoFrame::parse(&padded)),
+            TYPE_VENDOR_SPECIFIC => {
+                Self::VendorSpecific(VendorSpecificInfoFrame::parse(payload, &padded))
+            }
+            TYPE_HDR => Self::Hdr(HdrInfoFrame::parse(&padded)),
+            _ => Self::Other {
+                packet_type,
+                version,
+                payload: payload.to_vec(),
+            },
+        })
+    }
+}
+
+/// The colorimetry signaled by an AVI InfoFrame, combining its colorimetry and extended
+/// colorimetry fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AviColorimetry {
+    #[default]
+    NoData,
+    /// SMPTE 170M, i.e. BT.601.
+    Bt601,
+    Bt709,
+    XvYcc601,
+    XvYcc709,
+    SYcc601,
+    OpYcc601,
+    OpRgb,
+    /// BT.2020 with constant luminance.
+    Bt2020ConstantLuminance,
+    Bt2020,
+    /// Signaled via the additional colorimetry extension, e.g. DCI-P3.
+    Additional,
+}
+
+impl AviColorimetry {
+    fn parse(colorimetry: u8, extended: u8) -> Self {
+        match (colorimetry, extended) {
+            (0, _) => Self::NoData,
+            (1, _) => Self::Bt601,
+            (2, _) => Self::Bt709,
+            (_, 0) => Self::XvYcc601,
+            (_, 1) => Self::XvYcc709,
+            (_, 2) => Self::SYcc601,
+            (_, 3) => Self::OpYcc601,
+            (_, 4) => Self::OpRgb,
+            (_, 5) => Self::Bt2020ConstantLuminance,
+            (_, 6) => Self::Bt2020,
+            _ => Self::Additional,
+        }
+    }
+}
+
+/// The auxiliary video information InfoFrame, which describes the video being sent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AviInfoFrame {
+    pub pixel_encoding: HdmiPixelEncoding,
+    pub colorimetry: AviColorimetry,
+    /// The CEA-861 video identification code, or 0 if not a CEA-861 format.
+    pub vic: u8,
+    /// The picture aspect ratio as (width, height), if signaled.
+    pub picture_aspect_ratio: Option<(u8, u8)>,
+    /// The active format description code, if present.
+    pub active_format: Option<u8>,
+    /// The RGB quantization range. `Unknown` means the default for the video format.
+    pub rgb_quantization_range: QuantizationRange,
+    /// The YCbCr quantization range.
+    pub ycc_quantization_range: QuantizationRange,
+    pub it_content: bool,
+    /// The IT content type, e.g. 3 for game, which is only meaningful if `it_content` is set.
+    pub content_type: u8,
+    /// The raw scan information: 1 for overscanned, 2 for underscanned, or 0 if not signaled.
+    pub scan_info: u8,
+    /// The raw non-uniform picture scaling.
+    pub scaling: u8,
+    /// The number of times each pixel is repeated, which is 1 unless pixel repetition is used.
+    pub pixel_repetition: u8,
+    /// The last line of the top bar and the first line of the bottom bar, if signaled.
+    pub horizontal_bars: Option<(u16, u16)>,
+    /// The last pixel of the left bar and the first pixel of the right bar, if signaled.
+    pub vertical_bars: Option<(u16, u16)>,
+}
+
+impl AviInfoFrame {
+    fn parse(p: &[u8; MAX_PAYLOAD_SIZE]) -> Self {
+        let word = |i: usize| u16::from_le_bytes([p[i], p[i + 1]]);
+        Self {
+            pixel_encoding: HdmiPixelEncoding::from(((p[0] >> 5) & 0x03) as u32),
+            colorimetry: AviColorimetry::parse(p[1] >> 6, (p[2] >> 4) & 0x07),
+            vic: p[3],
+            picture_aspect_ratio: match (p[1] >> 4) & 0x03 {
+                1 => Some((4, 3)),
+                2 => Some((16, 9)),
+                _ => None,
+            },
+            active_format: (p[0] & 0x10 != 0).then_some(p[1] & 0x0f),
+            rgb_quantization_range: match (p[2] >> 2) & 0x03 {
+                1 => QuantizationRange::Limited,
+                2 => QuantizationRange::Full,
+                _ => QuantizationRange::Unknown,
+            },
+            ycc_quantization_range: match p[4] >> 6 {
+                0 => QuantizationRange::Limited,
+                1 => QuantizationRange::Full,
+                _ => QuantizationRange::Unknown,
+            },
+            it_content: p[2] & 0x80 != 0,
+            content_type: (p[4] >> 4) & 0x03,
+            scan_info: p[0] & 0x03,
+            scaling: p[2] & 0x03,
+            pixel_repetition: (p[4] & 0x0f) + 1,
+            horizontal_bars: (p[0] & 0x08 != 0).then(|| (word(5), word(7))),
+            vertical_bars: (p[0] & 0x04 != 0).then(|| (word(9), word(11))),
+        }
+    }
+}
+
+/// The audio InfoFrame. Fields are `None` when the sink should refer to the stream itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AudioInfoFrame {
+    /// The raw audio coding type, e.g. 1 for LPCM.
+    pub coding_type: Option<u8>,
+    pub channel_count: Option<u8>,
+    pub sample_rate: Option<u32>,
+    pub bits_per_sample: Option<u8>,
+    /// The CEA-861 speaker allocation.
+    pub channel_allocation: u8,
+    /// The attenuation applied when downmixing, in dB.
+    pub level_shift_db: u8,
+    pub downmix_inhibit: bool,
+    /// The raw LFE playback level.
+    pub lfe_playback_level: u8,
+}
+
+impl AudioInfoFrame {
+    fn parse(p: &[u8; MAX_PAYLOAD_SIZE]) -> Self {
+        Self {
+            coding_type: Some(p[0] >> 4).filter(|t| *t != 0),
+            channel_count: Some(p[0] & 0x07).filter(|c| *c != 0).map(|c| c + 1),
+            sample_rate: match (p[1] >> 2) & 0x07 {
+                1 => Some(32000),
+                2 => Some(44100),
+                3 => Some(48000),
+                4 => Some(88200),
+                5 => Some(96000),
+                6 => Some(176400),
+                7 => Some(192000),
+                _ => None,
+            },
+            bits_per_sample: match p[1] & 0x03 {
+                1 => Some(16),
+                2 => Some(20),
+                3 => Some(24),
+                _ => None,
+            },
+            channel_allocation: p[3],
+            level_shift_db: (p[4] >> 3) & 0x0f,
+            downmix_inhibit: p[4] & 0x80 != 0,
+            lfe_playback_level: p[4] & 0x03,
+        }
+    }
+}
+
+/// The source product description InfoFrame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpdInfoFrame {
+    pub vendor_name: String,
+    pub product_description: String,
+    /// The raw source device information, e.g. 1 for a digital STB or 8 for a game console.
+    pub source_information: u8,
+}
+
+impl SpdInfoFrame {
+    fn parse(p: &[u8; MAX_PAYLOAD_SIZE]) -> Self {
+        let text = |bytes: &[u8]| {
+            let len = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+            String::from_utf8_lossy(&bytes[..len])
+                .trim_end()
+                .to_string()
+        };
+        Self {
+            vendor_name: text(&p[0..8]),
+            product_description: text(&p[8..24]),
+            source_information: p[24],
+        }
+    }
+}
+
+/// The 3D structure signaled by an HDMI 1.4b vendor-specific InfoFrame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stereo3dStructure {
+    FramePacking,
+    FieldAlternative,
+    LineAlternative,
+    SideBySideFull,
+    TopAndBottom,
+    /// Side-by-side with half horizontal resolution, along with the raw subsampling method.
+    SideBySideHalf(u8),
+    Other(u8),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hdmi14bVideoFormat {
+    None,
+    /// A 4K format identified by its HDMI VIC rather than a CEA-861 VIC.
+    ExtendedResolution {
+        hdmi_vic: u8,
+    },
+    ThreeD {
+        structure: Stereo3dStructure,
+        metadata_present: bool,
+    },
+    Other(u8),
+}
+
+impl Hdmi14bVideoFormat {
+    /// Returns the CEA-861 VIC equivalent to an HDMI VIC.
+    pub fn cea_vic(&self) -> Option<u8> {
+        match self {
+            Self::ExtendedResolution { hdmi_vic: 1 } => Some(95),
+            Self::ExtendedResolution { hdmi_vic: 2 } => Some(94),
+            Self::ExtendedResolution { hdmi_vic: 3 } => Some(93),
+            Self::ExtendedResolution { hdmi_vic: 4 } => Some(98),
+            _ => None,
+        }
+    }
+
+    fn parse(p: &[u8; MAX_PAYLOAD_SIZE]) -> Self {
+        match p[3] >> 5 {
+            0 => Self::None,
+            1 => Self::ExtendedResolution { hdmi_vic: p[4] },
+            2 => Self::ThreeD {
+                structure: match p[4] >> 4 {
+                    0 => Stereo3dStructure::FramePacking,
+                    1 => Stereo3dStructure::FieldAlternative,
+                    2 => Stereo3dStructure::LineAlternative,
+                    3 => Stereo3dStructure::SideBySideFull,
+                    6 => Stereo3dStructure::TopAndBottom,
+                    8 => Stereo3dStructure::SideBySideHalf(p[5] >> 4),
+                    structure => Stereo3dStructure::Other(structure),
+                },
+                metadata_present: p[4] & 0x08 != 0,
+            },
+            format => Self::Other(format),
+        }
+    }
+}
+
+/// A vendor-specific InfoFrame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VendorSpecificInfoFrame {
+    pub registration_id: u32,
+    /// The payload following the registration ID.
+    pub data: Vec<u8>,
+    /// The video format, for HDMI 1.4b InfoFrames.
+    pub hdmi_video_format: Option<Hdmi14bVideoFormat>,
+}
+
+impl VendorSpecificInfoFrame {
+    fn parse(payload: &[u8], p: &[u8; MAX_PAYLOAD_SIZE]) -> Self {
+        let registration_id = u32::from_le_bytes([p[0], p[1], p[2], 0]);
+        Self {
+            registration_id,
+            data: payload.get(3..).unwrap_or_default().to_vec(),
+            hdmi_video_format: (registration_id == HDMI14B_REGISTRATION_ID)
+                .then(|| Hdmi14bVideoFormat::parse(p)),
+        }
+    }
+}
+
+/// The transfer function signaled by a Dynamic Range and Mastering InfoFrame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Eotf {
+    #[default]
+    TraditionalSdr,
+    TraditionalHdr,
+    /// SMPTE ST 2084, i.e. PQ.
+    Pq,
+    Hlg,
+    Other(u8),
+}
+
+/// SMPTE ST 2086 mastering display metadata along with content light levels, as carried by
+/// static metadata type 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MasteringMetadata {
+    /// The CIE 1931 xy coordinates of the display primaries in units of 0.00002, in the order
+    /// they appear in the InfoFrame. This is usually green, blue, red.
+    pub display_primaries: [(u16, u16); 3],
+    /// The white point in units of 0.00002.
+    pub white_point: (u16, u16),
+    /// The maximum display mastering luminance in cd/m².
+    pub max_luminance: u16,
+    /// The minimum display mastering luminance in units of 0.0001 cd/m².
+    pub min_luminance: u16,
+    /// MaxCLL in cd/m², or 0 if unknown.
+    pub max_content_light_level: u16,
+    /// MaxFALL in cd/m², or 0 if unknown.
+    pub max_frame_average_light_level: u16,
+}
+
+/// The Dynamic Range and Mastering InfoFrame, which signals HDR.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HdrInfoFrame {
+    pub eotf: Eotf,
+    /// The mastering metadata, if the InfoFrame carries static metadata type 1.
+    pub mastering: Option<MasteringMetadata>,
+}
+
+impl HdrInfoFrame {
+    fn parse(p: &[u8; MAX_PAYLOAD_SIZE]) -> Self {
+        let word = |i: usize| u16::from_le_bytes([p[i], p[i + 1]]);
+        let point = |i: usize| (word(i), word(i + 2));
+        Self {
+            eotf: match p[0] & 0x07 {
+                0 => Eotf::TraditionalSdr,
+                1 => Eotf::TraditionalHdr,
+                2 => Eotf::Pq,
+                3 => Eotf::Hlg,
+                eotf => Eotf::Other(eotf),
+            },
+            mastering: (p[1] & 0x07 == 0).then(|| MasteringMetadata {
+                display_primaries: [point(2), point(6), point(10)],
+                white_point: point(14),
+                max_luminance: word(18),
+                min_luminance: word(20),
+                max_content_light_level: word(22),
+                max_frame_average_light_level: word(24),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_avi() {
+        // 1080p59.94 YCbCr 4:2:2, BT.709, 16:9, limited range.
+        let packet = [
+            0x82, 0x02, 0x0d, 0x83, 0x30, 0xa8, 0x04, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00,
+        ];
+        let InfoFrame::Avi(avi) = InfoFrame::parse(&packet).unwrap() else {
+            panic!("expected avi infoframe");
+        };
+        assert_eq!(avi.pixel_encoding, HdmiPixelEncoding::Yuv422);
+        assert_eq!(avi.colorimetry, AviColorimetry::Bt709);
+        assert_eq!(avi.vic, 16);
+        assert_eq!(avi.picture_aspect_ratio, Some((16, 9)));
+        assert_eq!(avi.active_format, Some(8));
+        assert_eq!(avi.rgb_quantization_range, QuantizationRange::Limited);
+        assert_eq!(avi.ycc_quantization_range, QuantizationRange::Limited);
+        assert_eq!(avi.pixel_repetition, 1);
+        assert_eq!(avi.horizontal_bars, None);
+
+        // BT.2020 via the extended colorimetry, with bars.
+        let packet = [
+            0x82, 0x02, 0x0d, 0xed, 0x5e, 0xe8, 0x60, 0x61, 0x40, 0x8a, 0x00, 0xae, 0x03, 0x00,
+            0x00, 0x00, 0x00,
+        ];
+        let InfoFrame::Avi(avi) = InfoFrame::parse(&packet).unwrap() else {
+            panic!("expected avi infoframe");
+        };
+        assert_eq!(avi.pixel_encoding, HdmiPixelEncoding::Yuv444);
+        assert_eq!(avi.colorimetry, AviColorimetry::Bt2020);
+        assert_eq!(avi.vic, 97);
+        assert_eq!(avi.ycc_quantization_range, QuantizationRange::Full);
+        assert_eq!(avi.horizontal_bars, Some((138, 942)));
+        assert_eq!(avi.vertical_bars, Some((0, 0)));
+    }
+
+    #[test]
+    fn test_hdr() {
+        // HDR10 with BT.2020 primaries, D65, 1000/0.005 cd/m² mastering, MaxCLL 1000, MaxFALL
+        // 400.
+        let packet = [
+            0x87, 0x01, 0x1a, 0x91, 0x02, 0x00, 0x34, 0x21, 0xaa, 0x9b, 0x96, 0x19, 0xfc, 0x08,
+            0x48, 0x8a, 0x08, 0x39, 0x13, 0x3d, 0x42, 0x40, 0xe8, 0x03, 0x32, 0x00, 0xe8, 0x03,
+            0x90, 0x01,
+        ];
+        let InfoFrame::Hdr(hdr) = InfoFrame::parse(&packet).unwrap() else {
+            panic!("expected hdr infoframe");
+        };
+        assert_eq!(hdr.eotf, Eotf::Pq);
+        let mastering = hdr.mastering.unwrap();
+        assert_eq!(
+            mastering.display_primaries,
+            [(8500, 39850), (6550, 2300), (35400, 14600)]
+        );
+        assert_eq!(mastering.white_point, (15635, 16450));
+        assert_eq!(mastering.max_luminance, 1000);
+        assert_eq!(mastering.min_luminance, 50);
+        assert_eq!(mastering.max_content_light_level, 1000);
+        assert_eq!(mastering.max_frame_average_light_level, 400);
+    }
+
+    #[test]
+    fn test_spd() {
+        let mut packet = vec![0x83, 0x01, 0x19, 0x00];
+        packet.extend_from_slice(b"Magewell");
+        packet.extend_from_slice(b"Test Source\0\0\0\0\0");
+        packet.push(0x09);
+        packet[3] = packet
+            .iter()
+            .fold(0u8, |sum, b| sum.wrapping_add(*b))
+            .wrapping_neg();
+        let InfoFrame::Spd(spd) = InfoFrame::parse(&packet).unwrap() else {
+            panic!("expected spd infoframe");
+        };
+        assert_eq!(spd.vendor_name, "Magewell");
+        assert_eq!(spd.product_description, "Test Source");
+        assert_eq!(spd.source_information, 9);
+    }
+
+    #[test]
+    fn test_vendor_specific() {
+        // HDMI VIC 1, i.e. 2160p29.97.
+        let packet = [0x81, 0x01, 0x05, 0x49, 0x03, 0x0c, 0x00, 0x20, 0x01];
+        let InfoFrame::VendorSpecific(vsif) = InfoFrame::parse(&packet).unwrap() else {
+            panic!("expected vendor-specific infoframe");
+        };
+        assert_eq!(vsif.registration_id, HDMI14B_REGISTRATION_ID);
+        let format = vsif.hdmi_video_format.unwrap();
+        assert_eq!(
+            format,
+            Hdmi14bVideoFormat::ExtendedResolution { hdmi_vic: 1 }
+        );
+        assert_eq!(format.cea_vic(), Some(95));
+
+        // 3D side-by-side half.
+        let packet = [0x81, 0x01, 0x06, 0x99, 0x03, 0x0c, 0x00, 0x40, 0x80, 0x10];
+        let InfoFrame::VendorSpecific(vsif) = InfoFrame::parse(&packet).unwrap() else {
+            panic!("expected vendor-specific infoframe");
+        };
+        assert_eq!(
+            vsif.hdmi_video_format,
+            Some(Hdmi14bVideoFormat::ThreeD {
+                structure: Stereo3dStructure::SideBySideHalf(1),
+                metadata_present: false,
+            })
+        );
+    }
+
+    #[test]
+    fn test_audio() {
+        // LPCM, 8 channels, 48 kHz, 24 bits, 7.1 allocation.
+        let packet = [
+            0x84, 0x01, 0x0a, 0x38, 0x17, 0x0f, 0x00, 0x13, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+        let InfoFrame::Audio(audio) = InfoFrame::parse(&packet).unwrap() else {
+            panic!("expected audio infoframe");
+        };
+        assert_eq!(audio.coding_type, Some(1));
+        assert_eq!(audio.channel_count, Some(8));
+        assert_eq!(audio.sample_rate, Some(48000));
+        assert_eq!(audio.bits_per_sample, Some(24));
+        assert_eq!(audio.channel_allocation, 0x13);
+    }
+
+    #[test]
+    fn test_invalid() {
+        let mut packet = [
+            0x84, 0x01, 0x0a, 0x38, 0x17, 0x0f, 0x00, 0x13, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+        packet[3] ^= 0x01;
+        assert!(InfoFrame::parse(&packet).is_err());
+        assert!(InfoFrame::parse(&packet[..8]).is_err());
+        assert!(InfoFrame::parse(&[0x84, 0x01]).is_err());
+
+        // A length beyond the largest payload, with a valid checksum.
+        let mut packet = [0; HEADER_SIZE + 31];
+        packet[..3].copy_from_slice(&[0x84, 0x01, 0x1f]);
+        packet[3] = 0u8.wrapping_sub(0x84 + 0x01 + 0x1f);
+        assert!(InfoFrame::parse(&packet).is_err());
+    }
+}
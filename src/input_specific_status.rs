@@ -0,0 +1,467 @@
+use super::{sys, VideoInputType};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SdiType {
+    Sd,
+    Hd,
+    Level3GA,
+    Level3GBDualLink,
+    Level3GBDualStream,
+    DualLinkChannel1,
+    DualLinkChannel2,
+    Mode6G1,
+    Mode6G2,
+    Other(u32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SdiLevel {
+    A,
+    B,
+}
+
+impl SdiType {
+    /// Returns the 3G-SDI mapping level, if this is a 3G signal.
+    pub fn level(&self) -> Option<SdiLevel> {
+        match self {
+            Self::Level3GA => Some(SdiLevel::A),
+            Self::Level3GBDualLink | Self::Level3GBDualStream => Some(SdiLevel::B),
+            _ => None,
+        }
+    }
+}
+
+impl From<sys::SDI_TYPE> for SdiType {
+    fn from(t: sys::SDI_TYPE) -> Self {
+        match t {
+            sys::_SDI_TYPE_SDI_TYPE_SD => Self::Sd,
+            sys::_SDI_TYPE_SDI_TYPE_HD => Self::Hd,
+            sys::_SDI_TYPE_SDI_TYPE_3GA => Self::Level3GA,
+            sys::_SDI_TYPE_SDI_TYPE_3GB_DL => Self::Level3GBDualLink,
+            sys::_SDI_TYPE_SDI_TYPE_3GB_DS => Self::Level3GBDualStream,
+            sys::_SDI_TYPE_SDI_TYPE_DL_CH1 => Self::DualLinkChannel1,
+            sys::_SDI_TYPE_SDI_TYPE_DL_CH2 => Self::DualLinkChannel2,
+            sys::_SDI_TYPE_SDI_TYPE_6G_MODE1 => Self::Mode6G1,
+            sys::_SDI_TYPE_SDI_TYPE_6G_MODE2 => Self::Mode6G2,
+            _ => Self::Other(t),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SdiScanningFormat {
+    Interlaced,
+    SegmentedFrame,
+    Progressive,
+    Other(u32),
+}
+
+impl From<sys::SDI_SCANNING_FORMAT> for SdiScanningFormat {
+    fn from(f: sys::SDI_SCANNING_FORMAT) -> Self {
+        match f {
+            sys::_SDI_SCANNING_FORMAT_SDI_SCANING_INTERLACED => Self::Interlaced,
+            sys::_SDI_SCANNING_FORMAT_SDI_SCANING_SEGMENTED_FRAME => Self::SegmentedFrame,
+            sys::_SDI_SCANNING_FORMAT_SDI_SCANING_PROGRESSIVE => Self::Progressive,
+            _ => Self::Other(f),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SdiSamplingStruct {
+    YCbCr422,
+    YCbCr444,
+    Rgb444,
+    YCbCr420,
+    YCbCrA4224,
+    YCbCrA4444,
+    RgbA4444,
+    YCbCrD4224,
+    YCbCrD4444,
+    RgbD4444,
+    Xyz444,
+    Other(u32),
+}
+
+impl From<sys::SDI_SAMPLING_STRUCT> for SdiSamplingStruct {
+    fn from(s: sys::SDI_SAMPLING_STRUCT) -> Self {
+        match s {
+            sys::_SDI_SAMPLING_STRUCT_SDI_SAMPLING_422_YCbCr => Self::YCbCr422,
+            sys::_SDI_SAMPLING_STRUCT_SDI_SAMPLING_444_YCbCr => Self::YCbCr444,
+            sys::_SDI_SAMPLING_STRUCT_SDI_SAMPLING_444_RGB => Self::Rgb444,
+            sys::_SDI_SAMPLING_STRUCT_SDI_SAMPLING_420_YCbCr => Self::YCbCr420,
+            sys::_SDI_SAMPLING_STRUCT_SDI_SAMPLING_4224_YCbCrA => Self::YCbCrA4224,
+            sys::_SDI_SAMPLING_STRUCT_SDI_SAMPLING_4444_YCbCrA => Self::YCbCrA4444,
+            sys::_SDI_SAMPLING_STRUCT_SDI_SAMPLING_4444_RGBA => Self::RgbA4444,
+            sys::_SDI_SAMPLING_STRUCT_SDI_SAMPLING_4224_YCbCrD => Self::YCbCrD4224,
+            sys::_SDI_SAMPLING_STRUCT_SDI_SAMPLING_4444_YCbCrD => Self::YCbCrD4444,
+            sys::_SDI_SAMPLING_STRUCT_SDI_SAMPLING_4444_RGBD => Self::RgbD4444,
+            sys::_SDI_SAMPLING_STRUCT_SDI_SAMPLING_444_XYZ => Self::Xyz444,
+            _ => Self::Other(s),
+        }
+    }
+}
+
+pub struct SdiSpecificStatus {
+    inner: sys::MWCAP_SDI_SPECIFIC_STATUS,
+}
+
+impl SdiSpecificStatus {
+    pub fn sdi_type(&self) -> SdiType {
+        self.inner.sdiType.into()
+    }
+
+    pub fn scanning_format(&self) -> SdiScanningFormat {
+        self.inner.sdiScanningFormat.into()
+    }
+
+    /// Returns the bit depth, e.g. 10 for 10-bit video, or `None` if the SDK reports a depth it
+    /// doesn't define.
+    pub fn bit_depth(&self) -> Option<u8> {
+        match self.inner.sdiBitDepth {
+            sys::_SDI_BIT_DEPTH_SDI_BIT_DEPTH_8BIT => Some(8),
+            sys::_SDI_BIT_DEPTH_SDI_BIT_DEPTH_10BIT => Some(10),
+            sys::_SDI_BIT_DEPTH_SDI_BIT_DEPTH_12BIT => Some(12),
+            _ => None,
+        }
+    }
+
+    pub fn sampling_struct(&self) -> SdiSamplingStruct {
+        self.inner.sdiSamplingStruct.into()
+    }
+
+    /// Returns the raw SMPTE ST 352 payload ID, if present.
+    pub fn st352_payload_id(&self) -> Option<u32> {
+        (self.inner.bST352DataValid != 0).then_some(self.inner.dwST352Data)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HdmiPixelEncoding {
+    Rgb444,
+    Yuv422,
+    Yuv444,
+    Yuv420,
+    Other(u32),
+}
+
+impl From<sys::HDMI_PXIEL_ENCODING> for HdmiPixelEncoding {
+    fn from(e: sys::HDMI_PXIEL_ENCODING) -> Self {
+        match e {
+            sys::_HDMI_PIXEL_ENCODING_HDMI_ENCODING_RGB_444 => Self::Rgb444,
+            sys::_HDMI_PIXEL_ENCODING_HDMI_ENCODING_YUV_422 => Self::Yuv422,
+            sys::_HDMI_PIXEL_ENCODING_HDMI_ENCODING_YUV_444 => Self::Yuv444,
+            sys::_HDMI_PIXEL_ENCODING_HDMI_ENCODING_YUV_420 => Self::Yuv420,
+            _ => Self::Other(e),
+        }
+    }
+}
+
+/// The vertical timing of a single field. Progressive signals only have one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldTiming {
+    pub sync_width: u16,
+    pub front_porch: u16,
+    pub back_porch: u16,
+    pub active: u16,
+    pub total: u16,
+}
+
+pub struct HdmiVideoTiming {
+    inner: sys::MWCAP_HDMI_VIDEO_TIMING,
+}
+
+impl HdmiVideoTiming {
+    pub fn is_interlaced(&self) -> bool {
+        self.inner.bInterlaced != 0
+    }
+
+    pub fn frame_duration(&self) -> Duration {
+        Duration::from_nanos(100 * self.inner.dwFrameDuration as u64)
+    }
+
+    pub fn h_sync_width(&self) -> u16 {
+        self.inner.wHSyncWidth
+    }
+
+    pub fn h_front_porch(&self) -> u16 {
+        self.inner.wHFrontPorch
+    }
+
+    pub fn h_back_porch(&self) -> u16 {
+        self.inner.wHBackPorch
+    }
+
+    pub fn h_active(&self) -> u16 {
+        self.inner.wHActive
+    }
+
+    pub fn h_total(&self) -> u16 {
+        self.inner.wHTotalWidth
+    }
+
+    /// Returns the vertical timing of the top field, or of the frame for progressive signals.
+    pub fn field0(&self) -> FieldTiming {
+        FieldTiming {
+            sync_width: self.inner.wField0VSyncWidth,
+            front_porch: self.inner.wField0VFrontPorch,
+            back_porch: self.inner.wField0VBackPorch,
+            active: self.inner.wField0VActive,
+            total: self.inner.wField0VTotalHeight,
+        }
+    }
+
+    /// Returns the vertical timing of the bottom field, if the signal is interlaced.
+    pub fn field1(&self) -> Option<FieldTiming> {
+        self.is_interlaced().then(|| FieldTiming {
+            sync_width: self.inner.wField1VSyncWidth,
+            front_porch: self.inner.wField1VFrontPorch,
+            back_porch: self.inner.wField1VBackPorch,
+            active: self.inner.wField1VActive,
+            total: self.inner.wField1VTotalHeight,
+        })
+    }
+}
+
+pub struct HdmiSpecificStatus {
+    inner: sys::MWCAP_HDMI_SPECIFIC_STATUS,
+}
+
+impl HdmiSpecificStatus {
+    /// Returns false if the source is sending DVI rather than HDMI.
+    pub fn is_hdmi_mode(&self) -> bool {
+        self.inner.bHDMIMode != 0
+    }
+
+    pub fn is_hdcp(&self) -> bool {
+        self.inner.bHDCP != 0
+    }
+
+    pub fn bit_depth(&self) -> u8 {
+        self.inner.byBitDepth
+    }
+
+    pub fn pixel_encoding(&self) -> HdmiPixelEncoding {
+        self.inner.pixelEncoding.into()
+    }
+
+    /// Returns the CEA-861 video identification code.
+    pub fn vic(&self) -> u8 {
+        self.inner.byVIC
+    }
+
+    pub fn is_it_content(&self) -> bool {
+        self.inner.bITContent != 0
+    }
+
+    /// Returns the HDMI 3D structure, if the signal is 3D.
+    pub fn structure_3d(&self) -> Option<u8> {
+        (self.inner.b3DFormat != 0).then_some(self.inner.by3DStructure)
+    }
+
+    pub fn side_by_side_half_sub_sampling(&self) -> u8 {
+        self.inner.bySideBySideHalfSubSampling
+    }
+
+    pub fn video_timing(&self) -> HdmiVideoTiming {
+        HdmiVideoTiming {
+            inner: self.inner.videoTiming,
+        }
+    }
+}
+
+pub struct VideoSyncInfo {
+    inner: sys::MWCAP_VIDEO_SYNC_INFO,
+}
+
+impl VideoSyncInfo {
+    pub fn sync_type(&self) -> u8 {
+        self.inner.bySyncType
+    }
+
+    pub fn is_h_sync_polarity_positive(&self) -> bool {
+        self.inner.bHSPolarity != 0
+    }
+
+    pub fn is_v_sync_polarity_positive(&self) -> bool {
+        self.inner.bVSPolarity != 0
+    }
+
+    pub fn is_interlaced(&self) -> bool {
+        self.inner.bInterlaced != 0
+    }
+
+    pub fn frame_duration(&self) -> Duration {
+        Duration::from_nanos(100 * self.inner.dwFrameDuration as u64)
+    }
+
+    pub fn v_sync_line_count(&self) -> u16 {
+        self.inner.wVSyncLineCount
+    }
+
+    pub fn frame_line_count(&self) -> u16 {
+        self.inner.wFrameLineCount
+    }
+}
+
+pub struct VideoTiming {
+    inner: sys::MWCAP_VIDEO_TIMING,
+}
+
+impl VideoTiming {
+    pub fn timing_type(&self) -> u32 {
+        self.inner.dwType
+    }
+
+    pub fn pixel_clock(&self) -> u32 {
+        self.inner.dwPixelClock
+    }
+
+    pub fn is_interlaced(&self) -> bool {
+        self.inner.bInterlaced != 0
+    }
+
+    pub fn h_active(&self) -> u16 {
+        self.inner.wHActive
+    }
+
+    pub fn h_front_porch(&self) -> u16 {
+        self.inner.wHFrontPorch
+    }
+
+    pub fn h_sync_width(&self) -> u16 {
+        self.inner.wHSyncWidth
+    }
+
+    pub fn h_back_porch(&self) -> u16 {
+        self.inner.wHBackPorch
+    }
+
+    pub fn v_active(&self) -> u16 {
+        self.inner.wVActive
+    }
+
+    pub fn v_front_porch(&self) -> u16 {
+        self.inner.wVFrontPorch
+    }
+
+    pub fn v_sync_width(&self) -> u16 {
+        self.inner.wVSyncWidth
+    }
+
+    pub fn v_back_porch(&self) -> u16 {
+        self.inner.wVBackPorch
+    }
+}
+
+/// Status for VGA and component inputs.
+pub struct ComponentSpecificStatus {
+    inner: sys::MWCAP_COMPONENT_SPECIFIC_STATUS,
+}
+
+impl ComponentSpecificStatus {
+    pub fn sync_info(&self) -> VideoSyncInfo {
+        VideoSyncInfo {
+            inner: self.inner.syncInfo,
+        }
+    }
+
+    pub fn is_tri_level_sync(&self) -> bool {
+        self.inner.bTriLevelSync != 0
+    }
+
+    pub fn video_timing(&self) -> VideoTiming {
+        VideoTiming {
+            inner: self.inner.videoTiming,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SdVideoStandard {
+    None,
+    NtscM,
+    Ntsc433,
+    PalM,
+    Pal60,
+    PalCombN,
+    PalBghid,
+    Secam,
+    Secam60,
+    Other(u32),
+}
+
+impl From<sys::MWCAP_SD_VIDEO_STANDARD> for SdVideoStandard {
+    fn from(s: sys::MWCAP_SD_VIDEO_STANDARD) -> Self {
+        match s {
+            sys::_MWCAP_SD_VIDEO_STANDARD_MWCAP_SD_VIDEO_NONE => Self::None,
+            sys::_MWCAP_SD_VIDEO_STANDARD_MWCAP_SD_VIDEO_NTSC_M => Self::NtscM,
+            sys::_MWCAP_SD_VIDEO_STANDARD_MWCAP_SD_VIDEO_NTSC_433 => Self::Ntsc433,
+            sys::_MWCAP_SD_VIDEO_STANDARD_MWCAP_SD_VIDEO_PAL_M => Self::PalM,
+            sys::_MWCAP_SD_VIDEO_STANDARD_MWCAP_SD_VIDEO_PAL_60 => Self::Pal60,
+            sys::_MWCAP_SD_VIDEO_STANDARD_MWCAP_SD_VIDEO_PAL_COMBN => Self::PalCombN,
+            sys::_MWCAP_SD_VIDEO_STANDARD_MWCAP_SD_VIDEO_PAL_BGHID => Self::PalBghid,
+            sys::_MWCAP_SD_VIDEO_STANDARD_MWCAP_SD_VIDEO_SECAM => Self::Secam,
+            sys::_MWCAP_SD_VIDEO_STANDARD_MWCAP_SD_VIDEO_SECAM_60 => Self::Secam60,
+            _ => Self::Other(s),
+        }
+    }
+}
+
+/// Status for CVBS and Y/C (S-Video) inputs.
+pub struct CvbsYcSpecificStatus {
+    inner: sys::MWCAP_CVBS_YC_SPECIFIC_STATUS,
+}
+
+impl CvbsYcSpecificStatus {
+    pub fn standard(&self) -> SdVideoStandard {
+        self.inner.standard.into()
+    }
+
+    pub fn is_50hz(&self) -> bool {
+        self.inner.b50Hz != 0
+    }
+}
+
+/// Input-specific status, as returned by `MWGetInputSpecificStatus`. The variant depends on the
+/// type of the current video input.
+pub enum InputSpecificStatus {
+    /// There's no valid input signal.
+    None,
+    Hdmi(HdmiSpecificStatus),
+    Sdi(SdiSpecificStatus),
+    Component(ComponentSpecificStatus),
+    CvbsYc(CvbsYcSpecificStatus),
+    /// The input type doesn't have any specific status.
+    Other(VideoInputType),
+}
+
+impl From<sys::MWCAP_INPUT_SPECIFIC_STATUS> for InputSpecificStatus {
+    fn from(status: sys::MWCAP_INPUT_SPECIFIC_STATUS) -> Self {
+        if status.bValid == 0 {
+            return Self::None;
+        }
+        let input_type = VideoInputType::from(status.dwVideoInputType);
+        // The union member that's valid is determined by the input type.
+        unsafe {
+            match input_type {
+                VideoInputType::Hdmi => Self::Hdmi(HdmiSpecificStatus {
+                    inner: status.__bindgen_anon_1.hdmiStatus,
+                }),
+                VideoInputType::Sdi => Self::Sdi(SdiSpecificStatus {
+                    inner: status.__bindgen_anon_1.sdiStatus,
+                }),
+                VideoInputType::Vga | VideoInputType::Component => {
+                    Self::Component(ComponentSpecificStatus {
+                        inner: status.__bindgen_anon_1.vgaComponentStatus,
+                    })
+                }
+                VideoInputType::Cvbs | VideoInputType::Yc => Self::CvbsYc(CvbsYcSpecificStatus {
+                    inner: status.__bindgen_anon_1.cvbsYcStatus,
+                }),
+                _ => Self::Other(input_type),
+            }
+        }
+    }
+}
@@ -9,3 +9,8 @@ DWORD MWRsLibFourCCCalcMinStride(DWORD dwFOURCC, int cx, DWORD dwAlign) {
     // This function is inlined, so we need to wrap it to make it accessible to Rust.
     return FOURCC_CalcMinStride(dwFOURCC, cx, dwAlign);
 }
+
+BOOLEAN MWRsLibFourCCIsPacked(DWORD dwFOURCC) {
+    // This function is inlined, so we need to wrap it to make it accessible to Rust.
+    return FOURCC_IsPacked(dwFOURCC);
+}
@@ -4,3 +4,4 @@
 
 DWORD MWRsLibFourCCCalcImageSize(DWORD dwFOURCC, int cx, int cy, DWORD cbStride);
 DWORD MWRsLibFourCCCalcMinStride(DWORD dwFOURCC, int cx, DWORD dwAlign);
+BOOLEAN MWRsLibFourCCIsPacked(DWORD dwFOURCC);
@@ -1,8 +1,9 @@
 use snafu::prelude::*;
 use std::{
+    ffi::{CStr, CString},
     mem::MaybeUninit,
     ops::Deref,
-    os::raw::c_void,
+    os::raw::{c_char, c_void},
     sync::{Mutex, OnceLock},
 };
 
@@ -17,12 +18,45 @@ pub mod sys {
     include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
 }
 
+#[cfg(feature = "mp4")]
+pub mod mp4_sys {
+    #![allow(
+        clippy::all,
+        non_upper_case_globals,
+        non_snake_case,
+        non_camel_case_types,
+        unused
+    )]
+    include!(concat!(env!("OUT_DIR"), "/mp4_bindings.rs"));
+}
+
+#[cfg(feature = "cc708")]
+pub mod cc708_sys {
+    #![allow(
+        clippy::all,
+        non_upper_case_globals,
+        non_snake_case,
+        non_camel_case_types,
+        unused
+    )]
+    include!(concat!(env!("OUT_DIR"), "/cc708_bindings.rs"));
+}
+
 #[cfg(feature = "dep-stubs")]
 mod dep_stubs;
 
 mod fourcc;
 pub use fourcc::*;
 
+mod input_specific_status;
+pub use input_specific_status::*;
+
+mod device;
+pub use device::*;
+
+mod device_watcher;
+pub use device_watcher::*;
+
 mod eco_channel;
 pub use eco_channel::*;
 
@@ -35,6 +69,56 @@ pub use universal_capture_family::*;
 mod pro_eco_capture_family;
 pub use pro_eco_capture_family::*;
 
+mod signal_monitor;
+pub use signal_monitor::*;
+
+mod edid;
+pub use edid::*;
+
+mod edid_presets;
+pub use edid_presets::*;
+
+mod infoframe;
+pub use infoframe::*;
+
+mod hdr_metadata;
+pub use hdr_metadata::*;
+
+mod anc;
+pub use anc::*;
+
+mod timecode;
+pub use timecode::*;
+
+mod y4m;
+pub use y4m::*;
+
+mod wav;
+pub use wav::*;
+
+mod raw_recording;
+pub use raw_recording::*;
+
+#[cfg(feature = "closed-captions")]
+mod captions;
+#[cfg(feature = "closed-captions")]
+pub use captions::*;
+
+#[cfg(feature = "closed-captions")]
+mod caption_writer;
+#[cfg(feature = "closed-captions")]
+pub use caption_writer::*;
+
+#[cfg(feature = "cc708")]
+mod cc708;
+#[cfg(feature = "cc708")]
+pub use cc708::*;
+
+#[cfg(feature = "mp4")]
+mod mp4;
+#[cfg(feature = "mp4")]
+pub use mp4::*;
+
 // Contains simple wrappers around the Magewell SDK types.
 mod types;
 pub use types::*;
@@ -65,6 +149,42 @@ pub fn init() -> bool {
 /// device list. This mutex synchronizes access to those functions.
 pub static DEVICE_LIST_MUTEX: Mutex<()> = Mutex::new(());
 
+/// Refreshes the SDK's global device list. The caller must hold `DEVICE_LIST_MUTEX`.
+fn refresh_device_list() -> Result<()> {
+    unsafe {
+        if sys::MWRefreshDevice() != sys::_MW_RESULT__MW_SUCCEEDED {
+            whatever!("unable to refresh device list");
+        }
+    }
+    Ok(())
+}
+
+/// Gets the info for the channel at the given index of the SDK's global device list. The caller
+/// must hold `DEVICE_LIST_MUTEX`.
+fn get_channel_info_by_index(index: i32) -> Result<ChannelInfo> {
+    let mut info = MaybeUninit::uninit();
+    unsafe {
+        if sys::MWGetChannelInfoByIndex(index, info.as_mut_ptr()) != sys::_MW_RESULT__MW_SUCCEEDED {
+            whatever!("unable to get channel info");
+        }
+        Ok(info.assume_init().into())
+    }
+}
+
+/// Gets the device path for the channel at the given index of the SDK's global device list. The
+/// caller must hold `DEVICE_LIST_MUTEX`.
+fn get_device_path_by_index(index: i32) -> Result<CString> {
+    // The SDK doesn't tell us how big this buffer needs to be. Its examples use 128 bytes, so we
+    // use the SDK's `_MAX_PATH` to be safe.
+    let mut path = [0 as c_char; 512];
+    unsafe {
+        if sys::MWGetDevicePath(index, path.as_mut_ptr()) != sys::_MW_RESULT__MW_SUCCEEDED {
+            whatever!("unable to get device path");
+        }
+        Ok(CStr::from_ptr(path.as_ptr()).to_owned())
+    }
+}
+
 /// Returns info for all available capture channels.
 pub fn get_channel_info() -> Result<Vec<ChannelInfo>> {
     if !init() {
@@ -75,26 +195,33 @@ pub fn get_channel_info() -> Result<Vec<ChannelInfo>> {
         .lock()
         .expect("the lock must never be poisoned");
 
-    unsafe {
-        if sys::MWRefreshDevice() != sys::_MW_RESULT__MW_SUCCEEDED {
-            whatever!("unable to refresh device list");
-        }
+    refresh_device_list()?;
+
+    let channel_count = unsafe { sys::MWGetChannelCount() };
+
+    (0..channel_count)
+        .map(get_channel_info_by_index)
+        .collect::<Result<Vec<_>>>()
+}
+
+/// Returns the device paths for all available capture channels along with their info. Unlike
+/// board indices, device paths don't depend on enumeration order, so they can be used to refer to
+/// a physical input via `Channel::open_by_path`.
+pub fn get_device_paths() -> Result<Vec<(CString, ChannelInfo)>> {
+    if !init() {
+        whatever!("unable to initialize magewell api");
     }
 
+    let _lock = DEVICE_LIST_MUTEX
+        .lock()
+        .expect("the lock must never be poisoned");
+
+    refresh_device_list()?;
+
     let channel_count = unsafe { sys::MWGetChannelCount() };
 
     (0..channel_count)
-        .map(|i| -> Result<ChannelInfo> {
-            let mut info = MaybeUninit::uninit();
-            unsafe {
-                if sys::MWGetChannelInfoByIndex(i, info.as_mut_ptr())
-                    != sys::_MW_RESULT__MW_SUCCEEDED
-                {
-                    whatever!("unable to get channel info");
-                }
-                Ok(info.assume_init().into())
-            }
-        })
+        .map(|i| Ok((get_device_path_by_index(i)?, get_channel_info_by_index(i)?)))
         .collect::<Result<Vec<_>>>()
 }
 
@@ -123,6 +250,9 @@ impl Drop for ChannelHandle {
 
 impl Channel {
     /// Opens an Eco or Pro device based on the board and channel index.
+    ///
+    /// Board indices depend on enumeration order and the board's rotary switch, so for a stable
+    /// way of referring to a channel, see `open_by_path` or `open_by_serial`.
     pub fn open(board_index: u8, channel_index: u8) -> Result<Self> {
         if !init() {
             whatever!("unable to initialize magewell api");
@@ -133,19 +263,82 @@ impl Channel {
                 .lock()
                 .expect("the lock must never be poisoned");
 
-            unsafe {
-                if sys::MWRefreshDevice() != sys::_MW_RESULT__MW_SUCCEEDED {
-                    whatever!("unable to refresh device list");
+            refresh_device_list()?;
+
+            let handle = unsafe { sys::MWOpenChannel(board_index as _, channel_index as _) };
+            if handle.is_null() {
+                whatever!("unable to open channel");
+            }
+            ChannelHandle(handle)
+        };
+
+        Self::from_handle(handle)
+    }
+
+    /// Opens an Eco or Pro device based on its device path, as returned by `get_device_paths`.
+    pub fn open_by_path(path: &CStr) -> Result<Self> {
+        if !init() {
+            whatever!("unable to initialize magewell api");
+        }
+
+        let handle = {
+            let _lock = DEVICE_LIST_MUTEX
+                .lock()
+                .expect("the lock must never be poisoned");
+
+            refresh_device_list()?;
+
+            let handle = unsafe { sys::MWOpenChannelByPath(path.as_ptr()) };
+            if handle.is_null() {
+                whatever!("unable to open channel by path {:?}", path);
+            }
+            ChannelHandle(handle)
+        };
+
+        Self::from_handle(handle)
+    }
+
+    /// Opens an Eco or Pro device based on the board's serial number and the channel index. The
+    /// serial number is compared against `ChannelInfo::board_serial_number`.
+    pub fn open_by_serial(serial: &str, channel_index: u8) -> Result<Self> {
+        if !init() {
+            whatever!("unable to initialize magewell api");
+        }
+
+        let handle = {
+            let _lock = DEVICE_LIST_MUTEX
+                .lock()
+                .expect("the lock must never be poisoned");
+
+            refresh_device_list()?;
+
+            let channel_count = unsafe { sys::MWGetChannelCount() };
+            let mut index = None;
+            for i in 0..channel_count {
+                let info = get_channel_info_by_index(i)?;
+                if info.board_serial_number().to_str() == Ok(serial)
+                    && info.channel_index() == channel_index
+                {
+                    index = Some(i);
+                    break;
                 }
             }
+            let Some(index) = index else {
+                whatever!("no channel {} found for board {}", channel_index, serial);
+            };
 
-            let handle = unsafe { sys::MWOpenChannel(board_index as _, channel_index as _) };
+            let path = get_device_path_by_index(index)?;
+            let handle = unsafe { sys::MWOpenChannelByPath(path.as_ptr()) };
             if handle.is_null() {
                 whatever!("unable to open channel");
             }
             ChannelHandle(handle)
         };
 
+        Self::from_handle(handle)
+    }
+
+    fn from_handle(handle: ChannelHandle) -> Result<Self> {
         let info: ChannelInfo = {
             let mut info = MaybeUninit::uninit();
             unsafe {
@@ -158,10 +351,9 @@ impl Channel {
             }
         };
 
-        Ok(if info.family_name().to_str() == Ok("Eco Capture") {
-            Channel::Eco(EcoChannel::new(handle, info)?)
-        } else {
-            Channel::Pro(ProChannel::new(handle, info)?)
+        Ok(match info.family() {
+            Family::Eco => Channel::Eco(EcoChannel::new(handle, info)?),
+            _ => Channel::Pro(ProChannel::new(handle, info)?),
         })
     }
 }
@@ -213,6 +405,68 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_devices() {
+        for device in get_devices().unwrap() {
+            println!(
+                "board {} ({}), family = {:?}, channels = {}",
+                device.board_index(),
+                device.board_serial_number().to_str().unwrap(),
+                device.family(),
+                device.channels().len(),
+            );
+            if let FamilyInfo::Pro(info) | FamilyInfo::Eco(info) = device.family_info() {
+                println!(
+                    "pcie link = {:?} x{}, max payload = {}",
+                    info.link_type(),
+                    info.link_width(),
+                    info.max_payload_size(),
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_device_watcher() {
+        let expected = get_channel_info().unwrap().len();
+        let watcher = DeviceWatcher::new(std::time::Duration::from_secs(1)).unwrap();
+        for _ in 0..expected {
+            match watcher.recv().unwrap() {
+                DeviceEvent::Added { path, info } => {
+                    println!(
+                        "added [{}:{}] {:?}",
+                        info.board_index(),
+                        info.channel_index(),
+                        path
+                    );
+                }
+                DeviceEvent::Removed { .. } => panic!("unexpected removal"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_open_by_path() {
+        for (path, info) in get_device_paths().unwrap() {
+            println!(
+                "[{}:{}] {:?}",
+                info.board_index(),
+                info.channel_index(),
+                path
+            );
+            let ch = Channel::open_by_path(&path).unwrap();
+            assert_eq!(ch.info().board_index(), info.board_index());
+            assert_eq!(ch.info().channel_index(), info.channel_index());
+
+            let ch = Channel::open_by_serial(
+                info.board_serial_number().to_str().unwrap(),
+                info.channel_index(),
+            )
+            .unwrap();
+            assert_eq!(ch.info().board_index(), info.board_index());
+        }
+    }
+
     #[test]
     fn test_channel() {
         if get_channel_info().unwrap().is_empty() {
@@ -240,6 +494,84 @@ mod tests {
             video_status.image_height(),
             video_status.frame_duration(),
         );
+        println!(
+            "interlaced = {}, frame rate = {:?}, color format = {:?}, quantization = {:?}",
+            video_status.is_interlaced(),
+            video_status.frame_rate().map(|r| r.to_string()),
+            video_status.color_format(),
+            video_status.quantization_range(),
+        );
+
+        let proc_settings = ch.get_video_proc_settings().unwrap();
+        println!("video proc settings = {:?}", proc_settings);
+        ch.set_video_proc_settings(&proc_settings).unwrap();
+        println!(
+            "input colorimetry = {:?}, aspect ratio = {:?}",
+            ch.get_input_colorimetry().unwrap(),
+            ch.get_video_input_aspect_ratio().unwrap(),
+        );
+
+        let connection_format = ch.get_video_capture_connection_format().unwrap();
+        println!(
+            "connection format: connected = {}, {}x{} {:?}",
+            connection_format.is_connected(),
+            connection_format.width(),
+            connection_format.height(),
+            connection_format.format(),
+        );
+        let process_settings = ch.get_video_capture_process_settings().unwrap();
+        println!("video process settings = {:?}", process_settings);
+        ch.set_video_capture_process_settings(&process_settings)
+            .unwrap();
+
+        let video_caps = ch.get_video_caps().unwrap();
+        println!(
+            "max input = {}x{}, max output = {}x{}",
+            video_caps.max_input_width(),
+            video_caps.max_input_height(),
+            video_caps.max_output_width(),
+            video_caps.max_output_height(),
+        );
+        ch.validate_capture_config(
+            video_status.image_width(),
+            video_status.image_height(),
+            FourCC::new('B', 'G', 'R', ' '),
+            None,
+        )
+        .unwrap();
+
+        for source in ch.get_video_input_sources().unwrap() {
+            println!("video input = {:?}", source);
+        }
+        println!(
+            "current video input = {:?}",
+            ch.get_video_input_source().unwrap()
+        );
+
+        match ch.get_input_specific_status().unwrap() {
+            InputSpecificStatus::Hdmi(status) => println!(
+                "hdmi hdcp = {}, bit depth = {}, encoding = {:?}, vic = {}",
+                status.is_hdcp(),
+                status.bit_depth(),
+                status.pixel_encoding(),
+                status.vic(),
+            ),
+            InputSpecificStatus::Sdi(status) => println!(
+                "sdi type = {:?}, scanning = {:?}, bit depth = {:?}",
+                status.sdi_type(),
+                status.scanning_format(),
+                status.bit_depth(),
+            ),
+            _ => {}
+        }
+
+        if let InputSpecificStatus::Hdmi(_) = ch.get_input_specific_status().unwrap() {
+            let edid = ch.get_edid().unwrap();
+            println!("edid = {:?}", Edid::parse(&edid));
+            for infoframe in ch.get_hdmi_infoframes().unwrap() {
+                println!("infoframe = {:?}", infoframe);
+            }
+        }
 
         // Try capturing some audio.
         let mut audio_frame = AudioCaptureFrame::default();
@@ -282,12 +614,26 @@ mod tests {
                     stride,
                 );
 
-                ch.start_video_capture(
+                ch.start_video_capture(&EcoCaptureConfig::new(
                     video_status.image_width(),
                     video_status.image_height(),
                     format,
-                )
+                ))
                 .unwrap();
+                ch.update_capture_settings(&EcoCaptureSettings::default())
+                    .unwrap();
+
+                ch.enable_follow_source(std::time::Duration::from_millis(500), 4)
+                    .unwrap();
+                ch.enable_hdr_metadata().unwrap();
+                if let InputSpecificStatus::Sdi(_) = ch.get_input_specific_status().unwrap() {
+                    ch.enable_anc(&[
+                        AncSubscription::vanc(AncId::AFD),
+                        AncSubscription::vanc(AncId::CEA_708),
+                        AncSubscription::vanc(AncId::ATC),
+                    ])
+                    .unwrap();
+                }
 
                 let mut frame = EcoVideoCaptureFrame::new(image_size, stride);
 
@@ -297,11 +643,19 @@ mod tests {
                         ch.wait().unwrap();
                         if let Some(status) = ch.get_video_capture_status().unwrap() {
                             assert!(status.timestamp() > start_time);
+                            println!("hdr metadata = {:?}", status.hdr_metadata());
+                            println!("timecode = {:?}", status.timecode());
+                            for packet in status.anc_packets() {
+                                println!("anc packet = {:?}", packet);
+                            }
                             break status.into_frame();
                         }
                     }
                 }
 
+                ch.disable_anc().unwrap();
+                ch.disable_hdr_metadata().unwrap();
+                ch.disable_follow_source().unwrap();
                 ch.stop_video_capture().unwrap();
             }
             _ => {
@@ -0,0 +1,586 @@
+use super::{mp4_sys as sys, Result};
+use snafu::prelude::*;
+use std::{ffi::CString, os::unix::ffi::OsStrExt, path::Path, time::Duration};
+
+// The timescale of the video and subtitle tracks, and of audio tracks with an unknown sample rate.
+const TIMESCALE: u32 = 90_000;
+
+fn check(status: sys::mw_mp4_status_t, what: &str) -> Result<()> {
+    let reason = match status {
+        sys::_mw_mp4_status_MW_MP4_STATUS_SUCCESS => return Ok(()),
+        sys::_mw_mp4_status_MW_MP4_STATUS_INVALID_HANDLE => "invalid handle",
+        sys::_mw_mp4_status_MW_MP4_STATUS_FILE_NAME_TOO_LONG => "file name too long",
+        sys::_mw_mp4_status_MW_MP4_STATUS_INVALID_PARAMETER => "invalid parameter",
+        sys::_mw_mp4_status_MW_MP4_STATUS_INVALID_CODEC_TYPE => "invalid codec type",
+        sys::_mw_mp4_status_MW_MP4_STATUS_INVALID_SUBTITLE_TYPE => "invalid subtitle type",
+        sys::_mw_mp4_status_MW_MP4_STATUS_INVALID_ADTS_STREAM => "invalid adts stream",
+        sys::_mw_mp4_status_MW_MP4_STATUS_INVALID_VIDEO_STREAM => "invalid video stream",
+        sys::_mw_mp4_status_MW_MP4_STATUS_INVALID_VIDEO_TRACK => "invalid video track",
+        sys::_mw_mp4_status_MW_MP4_STATUS_UNSUPPORTED => "unsupported",
+        sys::_mw_mp4_status_MW_MP4_STATUS_OPERATOR_NOT_PERMITTED => "operation not permitted",
+        sys::_mw_mp4_status_MW_MP4_STATUS_NO_SUCH_FILE_OR_DIR => "no such file or directory",
+        sys::_mw_mp4_status_MW_MP4_STATUS_PERMISSION_DENIED => "permission denied",
+        sys::_mw_mp4_status_MW_MP4_STATUS_DEVICE_OR_RESOURCE_BUSY => "device or resource busy",
+        sys::_mw_mp4_status_MW_MP4_STATUS_TOO_MANY_OPEN_FILES => "too many open files",
+        sys::_mw_mp4_status_MW_MP4_STATUS_NO_SPACE_LEFT => "no space left on device",
+        sys::_mw_mp4_status_MW_MP4_STATUS_READONLY_FILE_SYSTEM => "read-only file system",
+        sys::_mw_mp4_status_MW_MP4_STATUS_FILE_NOT_OPEN => "file not open",
+        _ => "unexpected error",
+    };
+    whatever!("unable to {}: {}", what, reason)
+}
+
+fn path_to_cstring(path: &Path) -> Result<CString> {
+    CString::new(path.as_os_str().as_bytes()).whatever_context("path contains a nul byte")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mp4VideoCodec {
+    H264,
+    Hevc,
+}
+
+/// The video track of an MP4 file. The parameter sets may be left empty if they're contained in
+/// the stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mp4VideoConfig {
+    pub codec: Mp4VideoCodec,
+    pub width: u16,
+    pub height: u16,
+    pub sps: Vec<u8>,
+    pub pps: Vec<u8>,
+    /// The video parameter set, for HEVC.
+    pub vps: Vec<u8>,
+}
+
+impl Mp4VideoConfig {
+    pub fn new(codec: Mp4VideoCodec, width: u16, height: u16) -> Self {
+        Self {
+            codec,
+            width,
+            height,
+            sps: Vec::new(),
+            pps: Vec::new(),
+            vps: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mp4AudioCodec {
+    /// Raw AAC access units, which require the sample rate, channels, and profile to be given.
+    Aac,
+    /// AAC access units with ADTS headers, from which the format is read.
+    AdtsAac,
+}
+
+/// The audio track of an MP4 file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mp4AudioConfig {
+    pub codec: Mp4AudioCodec,
+    pub sample_rate: u16,
+    pub channels: u16,
+    /// The AAC audio object type, e.g. 2 for AAC-LC.
+    pub profile: u8,
+}
+
+impl Mp4AudioConfig {
+    pub fn aac(sample_rate: u16, channels: u16, profile: u8) -> Self {
+        Self {
+            codec: Mp4AudioCodec::Aac,
+            sample_rate,
+            channels,
+            profile,
+        }
+    }
+
+    pub fn adts_aac() -> Self {
+        Self {
+            codec: Mp4AudioCodec::AdtsAac,
+            sample_rate: 0,
+            channels: 0,
+            profile: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mp4SubtitleCodec {
+    Cea608,
+    Cea708,
+}
+
+/// Writes encoded video, audio, and captions to an MP4 file using the SDK's muxer.
+///
+/// Samples are given device timestamps, e.g. those of captured frames, and are placed in the file
+/// relative to the origin given when it's created.
+pub struct Mp4Writer {
+    handle: sys::mw_mp4_handle_t,
+    origin: Duration,
+    audio_timescale: u32,
+}
+
+// The handle isn't tied to the thread that opened it.
+unsafe impl Send for Mp4Writer {}
+
+impl Mp4Writer {
+    /// Creates a file whose tracks all start at `origin`, a device timestamp such as that of the
+    /// first captured frame. Samples with earlier timestamps are rejected.
+    ///
+    /// The SDK's muxer refuses to open files unless a Magewell capture device is present.
+    pub fn create<P: AsRef<Path>>(path: P, origin: Duration) -> Result<Self> {
+        let path = path_to_cstring(path.as_ref())?;
+        let handle = unsafe { sys::mw_mp4_open(path.as_ptr()) };
+        if handle.is_null() {
+            whatever!(
+                "unable to open mp4 file; the sdk's muxer requires a magewell device to be present"
+            );
+        }
+        Ok(Self {
+            handle,
+            origin,
+            audio_timescale: TIMESCALE,
+        })
+    }
+
+    pub fn set_video(&mut self, config: &Mp4VideoConfig) -> Result<()> {
+        // The SDK takes mutable pointers, but doesn't modify the parameter sets.
+        let ptr = |v: &Vec<u8>| {
+            if v.is_empty() {
+                std::ptr::null_mut()
+            } else {
+                v.as_ptr() as *mut u8
+            }
+        };
+        let info = sys::mw_mp4_video_info_t {
+            codec_type: match config.codec {
+                Mp4VideoCodec::H264 => sys::_mw_mp4_video_type_MW_MP4_VIDEO_TYPE_H264,
+                Mp4VideoCodec::Hevc => sys::_mw_mp4_video_type_MW_MP4_VIDEO_TYPE_HEVC,
+            },
+            timescale: TIMESCALE,
+            width: config.width,
+            height: config.height,
+            __bindgen_anon_1: match config.codec {
+                Mp4VideoCodec::H264 => sys::_mw_mp4_video_info__bindgen_ty_1 {
+                    h264: sys::mw_mp4_h264_parameter_set_t {
+                        sps: ptr(&config.sps),
+                        sps_size: config.sps.len() as _,
+                        pps: ptr(&config.pps),
+                        pps_size: config.pps.len() as _,
+                    },
+                },
+                Mp4VideoCodec::Hevc => sys::_mw_mp4_video_info__bindgen_ty_1 {
+                    hevc: sys::mw_mp4_hevc_parameter_set_t {
+                        sps: ptr(&config.sps),
+                        sps_size: config.sps.len() as _,
+                        pps: ptr(&config.pps),
+                        pps_size: config.pps.len() as _,
+                        vps: ptr(&config.vps),
+                        vps_size: config.vps.len() as _,
+                    },
+                },
+            },
+        };
+        check(
+            unsafe { sys::mw_mp4_set_video(self.handle, &info) },
+            "set mp4 video track",
+        )
+    }
+
+    pub fn set_audio(&mut self, config: &Mp4AudioConfig) -> Result<()> {
+        let timescale = match config.sample_rate {
+            0 => TIMESCALE,
+            sample_rate => sample_rate as u32,
+        };
+        let info = sys::mw_mp4_audio_info_t {
+            codec_type: match config.codec {
+                Mp4AudioCodec::Aac => sys::_mw_mp4_audio_type_MW_MP4_AUDIO_TYPE_AAC,
+                Mp4AudioCodec::AdtsAac => sys::_mw_mp4_audio_type_MW_MP4_AUDIO_TYPE_ADTS_AAC,
+            },
+            timescale,
+            sample_rate: config.sample_rate,
+            channels: config.channels,
+            profile: config.profile,
+        };
+        check(
+            unsafe { sys::mw_mp4_set_audio(self.handle, &info) },
+            "set mp4 audio track",
+        )?;
+        self.audio_timescale = timescale;
+        Ok(())
+    }
+
+    pub fn set_subtitle(&mut self, codec: Mp4SubtitleCodec) -> Result<()> {
+        let info = sys::mw_mp4_subtitle_info_t {
+            codec_type: match codec {
+                Mp4SubtitleCodec::Cea608 => sys::_mw_mp4_subtitle_type_MW_MP4_SUBTITLE_TYPE_CC608,
+                Mp4SubtitleCodec::Cea708 => sys::_mw_mp4_subtitle_type_MW_MP4_SUBTITLE_TYPE_CC708,
+            },
+            timescale: TIMESCALE,
+        };
+        check(
+            unsafe { sys::mw_mp4_set_subtitle(self.handle, &info) },
+            "set mp4 subtitle track",
+        )
+    }
+
+    /// Writes an H.264 or HEVC access unit in Annex B format.
+    pub fn write_video(&mut self, access_unit: &[u8], timestamp: Duration) -> Result<()> {
+        let timestamp = self.timestamp(timestamp, TIMESCALE)?;
+        check(
+            unsafe {
+                sys::mw_mp4_write_video(
+                    self.handle,
+                    access_unit.as_ptr(),
+                    access_unit.len() as _,
+                    timestamp,
+                )
+            },
+            "write mp4 video",
+        )
+    }
+
+    pub fn write_audio(&mut self, access_unit: &[u8], timestamp: Duration) -> Result<()> {
+        let timestamp = self.timestamp(timestamp, self.audio_timescale)?;
+        check(
+            unsafe {
+                sys::mw_mp4_write_audio(
+                    self.handle,
+                    access_unit.as_ptr(),
+                    access_unit.len() as _,
+                    timestamp,
+                )
+            },
+            "write mp4 audio",
+        )
+    }
+
+    /// Writes caption data, e.g. CEA-608 byte pairs.
+    pub fn write_subtitle(&mut self, data: &[u8], timestamp: Duration) -> Result<()> {
+        let timestamp = self.timestamp(timestamp, TIMESCALE)?;
+        check(
+            unsafe {
+                sys::mw_mp4_write_subtitle(self.handle, data.as_ptr(), data.len() as _, timestamp)
+            },
+            "write mp4 subtitle",
+        )
+    }
+
+    /// Finishes writing the file. Dropping the writer also closes the file, but ignores errors.
+    pub fn close(mut self) -> Result<()> {
+        let handle = std::mem::replace(&mut self.handle, std::ptr::null_mut());
+        check(unsafe { sys::mw_mp4_close(handle) }, "close mp4 file")
+    }
+
+    /// Recovers an MP4 file that wasn't closed, e.g. due to power loss. If `delete_original` is
+    /// false, the original file is kept alongside the repaired one.
+    pub fn repair<P: AsRef<Path>>(path: P, delete_original: bool) -> Result<()> {
+        let path = path_to_cstring(path.as_ref())?;
+        check(
+            unsafe { sys::mw_mp4_repair(path.as_ptr(), delete_original) },
+            "repair mp4 file",
+        )
+    }
+
+    fn timestamp(&self, timestamp: Duration, timescale: u32) -> Result<u64> {
+        let offset = match timestamp.checked_sub(self.origin) {
+            Some(offset) => offset,
+            None => whatever!("sample at {:?} is before the file's origin", timestamp),
+        };
+        Ok((offset.as_nanos() * timescale as u128 / 1_000_000_000) as u64)
+    }
+}
+
+impl Drop for Mp4Writer {
+    fn drop(&mut self) {
+        if !self.handle.is_null() {
+            unsafe { sys::mw_mp4_close(self.handle) };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct BitWriter {
+        bytes: Vec<u8>,
+        bits: usize,
+    }
+
+    impl BitWriter {
+        fn new(nal_header: u8) -> Self {
+            Self {
+                bytes: vec![nal_header],
+                bits: 8,
+            }
+        }
+
+        fn bit(&mut self, bit: bool) {
+            if self.bits % 8 == 0 {
+                self.bytes.push(0);
+            }
+            if bit {
+                *self.bytes.last_mut().unwrap() |= 0x80 >> (self.bits % 8);
+            }
+            self.bits += 1;
+        }
+
+        fn bits(&mut self, value: u32, count: usize) {
+            for i in (0..count).rev() {
+                self.bit(value >> i & 1 != 0);
+            }
+        }
+
+        fn ue(&mut self, value: u32) {
+            let value = value + 1;
+            let len = 32 - value.leading_zeros() as usize;
+            self.bits(0, len - 1);
+            self.bits(value, len);
+        }
+
+        fn finish(mut self) -> Vec<u8> {
+            self.bit(true);
+            while self.bits % 8 != 0 {
+                self.bit(false);
+            }
+            self.bytes
+        }
+    }
+
+    // A baseline profile SPS for a 16x16 image.
+    fn sps() -> Vec<u8> {
+        let mut w = BitWriter::new(0x67);
+        w.bits(66, 8);
+        w.bits(0xc0, 8);
+        w.bits(10, 8);
+        w.ue(0); // seq_parameter_set_id
+        w.ue(0); // log2_max_frame_num_minus4
+        w.ue(2); // pic_order_cnt_type
+        w.ue(1); // max_num_ref_frames
+        w.bit(false); // gaps_in_frame_num_value_allowed_flag
+        w.ue(0); // pic_width_in_mbs_minus1
+        w.ue(0); // pic_height_in_map_units_minus1
+        w.bit(true); // frame_mbs_only_flag
+        w.bit(true); // direct_8x8_inference_flag
+        w.bit(false); // frame_cropping_flag
+        w.bit(false); // vui_parameters_present_flag
+        w.finish()
+    }
+
+    fn pps() -> Vec<u8> {
+        let mut w = BitWriter::new(0x68);
+        w.ue(0); // pic_parameter_set_id
+        w.ue(0); // seq_parameter_set_id
+        w.bits(0, 2); // entropy_coding_mode_flag, bottom_field_pic_order_in_frame_present_flag
+        w.ue(0); // num_slice_groups_minus1
+        w.ue(0); // num_ref_idx_l0_default_active_minus1
+        w.ue(0); // num_ref_idx_l1_default_active_minus1
+        w.bits(0, 3); // weighted_pred_flag, weighted_bipred_idc
+        w.bits(0b111, 3); // pic_init_qp_minus26, pic_init_qs_minus26, chroma_qp_index_offset
+        w.bit(true); // deblocking_filter_control_present_flag
+        w.bits(0, 2); // constrained_intra_pred_flag, redundant_pic_cnt_present_flag
+        w.finish()
+    }
+
+    fn annex_b(nals: &[&[u8]]) -> Vec<u8> {
+        nals.iter()
+            .flat_map(|nal| [&[0, 0, 0, 1][..], nal].concat())
+            .collect()
+    }
+
+    fn adts_frame() -> Vec<u8> {
+        // AAC-LC, 48 kHz, stereo, with a silent raw data block.
+        let payload = [0x21, 0x10, 0x04, 0x60, 0x8c, 0x1c];
+        let len = 7 + payload.len();
+        let mut frame = vec![
+            0xff,
+            0xf1,
+            0x4c,
+            0x80 | (len >> 11) as u8,
+            (len >> 3) as u8,
+            ((len & 7) << 5) as u8 | 0x1f,
+            0xfc,
+        ];
+        frame.extend_from_slice(&payload);
+        frame
+    }
+
+    // Returns the type and contents of each box in `data`.
+    fn boxes(mut data: &[u8]) -> Vec<(String, &[u8])> {
+        let mut boxes = Vec::new();
+        while data.len() >= 8 {
+            let mut size = u32::from_be_bytes(data[..4].try_into().unwrap()) as usize;
+            let box_type = String::from_utf8_lossy(&data[4..8]).to_string();
+            let mut header = 8;
+            if size == 1 {
+                size = u64::from_be_bytes(data[8..16].try_into().unwrap()) as usize;
+                header = 16;
+            } else if size == 0 {
+                size = data.len();
+            }
+            boxes.push((box_type, &data[header..size]));
+            data = &data[size..];
+        }
+        boxes
+    }
+
+    // Returns the contents of the first box found by following `path` from `data`.
+    fn find<'a>(data: &'a [u8], path: &[&str]) -> &'a [u8] {
+        path.iter().fold(data, |data, box_type| {
+            boxes(data)
+                .into_iter()
+                .find(|(t, _)| t == box_type)
+                .unwrap_or_else(|| panic!("missing {} box", box_type))
+                .1
+        })
+    }
+
+    fn u32_at(data: &[u8], offset: usize) -> u32 {
+        u32::from_be_bytes(data[offset..offset + 4].try_into().unwrap())
+    }
+
+    // A track's handler type, sample count, and sample deltas, as (count, delta) runs.
+    type Track = (String, u32, Vec<(u32, u32)>);
+
+    fn tracks(moov: &[u8]) -> Vec<Track> {
+        boxes(moov)
+            .into_iter()
+            .filter(|(t, _)| t == "trak")
+            .map(|(_, trak)| {
+                let hdlr = find(trak, &["mdia", "hdlr"]);
+                let stbl = find(trak, &["mdia", "minf", "stbl"]);
+                let stsz = find(stbl, &["stsz"]);
+                let stts = find(stbl, &["stts"]);
+                let deltas = (0..u32_at(stts, 4) as usize)
+                    .map(|i| (u32_at(stts, 8 + i * 8), u32_at(stts, 12 + i * 8)))
+                    .collect();
+                (
+                    String::from_utf8_lossy(&hdlr[8..12]).to_string(),
+                    u32_at(stsz, 8),
+                    deltas,
+                )
+            })
+            .collect()
+    }
+
+    fn mp4_box(box_type: &str, contents: &[u8]) -> Vec<u8> {
+        let size = (8 + contents.len()) as u32;
+        [&size.to_be_bytes(), box_type.as_bytes(), contents].concat()
+    }
+
+    fn track(handler: &str, samples: u32, deltas: &[(u32, u32)]) -> Vec<u8> {
+        let hdlr = mp4_box("hdlr", &[&[0; 8], handler.as_bytes(), &[0; 13]].concat());
+        let stsz = mp4_box("stsz", &[&[0; 8][..], &samples.to_be_bytes()].concat());
+        let mut stts = vec![0; 4];
+        stts.extend_from_slice(&(deltas.len() as u32).to_be_bytes());
+        for (count, delta) in deltas {
+            stts.extend_from_slice(&count.to_be_bytes());
+            stts.extend_from_slice(&delta.to_be_bytes());
+        }
+        let stbl = mp4_box("stbl", &[stsz, mp4_box("stts", &stts)].concat());
+        let minf = mp4_box("minf", &stbl);
+        mp4_box("trak", &mp4_box("mdia", &[hdlr, minf].concat()))
+    }
+
+    #[test]
+    fn test_tracks() {
+        let video = track("vide", 25, &[(25, 3600)]);
+        // A 64-bit box size, as used for large boxes.
+        let mut audio = 1u32.to_be_bytes().to_vec();
+        let audio_trak = track("soun", 47, &[(46, 1920), (1, 1919)]);
+        audio.extend_from_slice(b"trak");
+        audio.extend_from_slice(&(audio_trak.len() as u64 + 8).to_be_bytes());
+        audio.extend_from_slice(&audio_trak[8..]);
+        let moov = [
+            mp4_box("mvhd", &[0; 100]),
+            video,
+            audio,
+            // A box size of zero extends to the end of the data.
+            [&[0; 4][..], b"free", &[0; 4]].concat(),
+        ]
+        .concat();
+        assert_eq!(
+            boxes(&moov)
+                .iter()
+                .map(|(t, _)| t.as_str())
+                .collect::<Vec<_>>(),
+            ["mvhd", "trak", "trak", "free"]
+        );
+        assert_eq!(
+            tracks(&moov),
+            [
+                ("vide".to_string(), 25, vec![(25, 3600)]),
+                ("soun".to_string(), 47, vec![(46, 1920), (1, 1919)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_timestamp() {
+        // A writer without a handle has nothing to close when it's dropped.
+        let writer = Mp4Writer {
+            handle: std::ptr::null_mut(),
+            origin: Duration::from_secs(1000),
+            audio_timescale: 48000,
+        };
+        let at = |nanos| Duration::from_secs(1000) + Duration::from_nanos(nanos);
+        assert_eq!(writer.timestamp(at(0), TIMESCALE).unwrap(), 0);
+        assert_eq!(writer.timestamp(at(40_000_000), TIMESCALE).unwrap(), 3600);
+        // Timestamps are rounded down.
+        assert_eq!(writer.timestamp(at(20_833), 48000).unwrap(), 0);
+        assert_eq!(writer.timestamp(at(20_834), 48000).unwrap(), 1);
+        assert_eq!(
+            writer.timestamp(at(86_400_000_000_000), TIMESCALE).unwrap(),
+            86_400 * 90_000
+        );
+        assert!(writer
+            .timestamp(Duration::from_secs(999), TIMESCALE)
+            .is_err());
+    }
+
+    #[test]
+    #[ignore = "the SDK's muxer only opens files while a Magewell device is present"]
+    fn test_mp4_writer() {
+        let path =
+            std::env::temp_dir().join(format!("magewell-capture-test-{}.mp4", std::process::id()));
+        let start = Duration::from_secs(1000);
+        let mut writer = Mp4Writer::create(&path, start).unwrap();
+        let mut video = Mp4VideoConfig::new(Mp4VideoCodec::H264, 16, 16);
+        video.sps = sps();
+        video.pps = pps();
+        writer.set_video(&video).unwrap();
+        writer.set_audio(&Mp4AudioConfig::adts_aac()).unwrap();
+
+        let frame_duration = Duration::from_secs(1) / 25;
+        let audio_duration = Duration::from_secs(1024) / 48000;
+        let idr = [0x65, 0x88, 0x84, 0x00, 0x33, 0xff];
+        let au = annex_b(&[&sps(), &pps(), &idr]);
+        assert!(writer.write_video(&au, start - frame_duration).is_err());
+        for i in 0..25 {
+            writer.write_video(&au, start + frame_duration * i).unwrap();
+        }
+        for i in 0..47 {
+            writer
+                .write_audio(&adts_frame(), start + audio_duration * i)
+                .unwrap();
+        }
+        writer.close().unwrap();
+
+        let data = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let top = boxes(&data);
+        let types: Vec<_> = top.iter().map(|(t, _)| t.as_str()).collect();
+        assert_eq!(types.first(), Some(&"ftyp"));
+        assert!(types.contains(&"mdat"));
+        let tracks = tracks(find(&data, &["moov"]));
+        assert_eq!(tracks.len(), 2);
+        assert_eq!(tracks[0], ("vide".to_string(), 25, vec![(25, 3600)]));
+        // ADTS audio without a sample rate uses the 90 kHz timescale, with timestamps rounded down.
+        let (handler, samples, deltas) = &tracks[1];
+        assert_eq!((handler.as_str(), *samples), ("soun", 47));
+        assert_eq!(deltas.iter().map(|&(n, _)| n).sum::<u32>(), 47);
+        let duration: u32 = deltas.iter().map(|&(n, delta)| n * delta).sum();
+        assert!((47 * 1920 - 1..=47 * 1920).contains(&duration));
+    }
+}
@@ -1,34 +1,312 @@
 use super::{
-    sys, ChannelHandle, ChannelInfo, ProEcoCaptureFamilyChannel, Result,
-    UniversalCaptureFamilyChannel,
+    close_monitor, poll_monitor, sys, AncMonitor, AncPacket, AncSubscription,
+    AspectRatioConvertMode, ChannelHandle, ChannelInfo, DeinterlaceMode, Field, FourCC,
+    HdrMetadata, HdrMetadataTracker, ProEcoCaptureFamilyChannel, ProVideoCaptureInfo,
+    QuantizationRange, Result, SaturationRange, UniversalCaptureFamilyChannel, VideoBufferInfo,
+    VideoColorFormat, VideoFrameId, VideoFrameInfo,
 };
 use snafu::prelude::*;
-use std::ffi::c_void;
+use std::{ffi::c_void, mem::MaybeUninit};
+
+/// Parameters for `ProChannel::capture_video_frame`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProVideoCaptureParams {
+    pub width: u16,
+    pub height: u16,
+    pub format: FourCC,
+    pub stride: usize,
+    pub deinterlace_mode: DeinterlaceMode,
+    /// Whether the image is stored bottom row first.
+    pub bottom_up: bool,
+}
+
+impl ProVideoCaptureParams {
+    pub fn new(width: u16, height: u16, format: FourCC, stride: usize) -> Self {
+        Self {
+            width,
+            height,
+            format,
+            stride,
+            deinterlace_mode: DeinterlaceMode::Blend,
+            bottom_up: false,
+        }
+    }
+}
 
 pub struct ProChannel {
     handle: ChannelHandle,
     info: ChannelInfo,
     event: sys::MWCAP_PTR,
+    // signaled when a frame has been captured to memory
+    capture_event: sys::MWCAP_PTR,
+    hdr_metadata: Option<HdrMetadataTracker>,
+    anc: Option<AncMonitor>,
+    // packets received since the previous capture
+    anc_packets: Vec<AncPacket>,
 }
 
 impl Drop for ProChannel {
     fn drop(&mut self) {
-        unsafe { sys::MWCloseEvent(self.event) };
+        unsafe {
+            sys::MWCloseEvent(self.event);
+            sys::MWCloseEvent(self.capture_event);
+        }
     }
 }
 
+// The SDK's INFINITE timeout for `MWWaitEvent`.
+const INFINITE: i32 = -1;
+
 impl ProChannel {
     pub(crate) fn new(handle: ChannelHandle, info: ChannelInfo) -> Result<Self> {
         let event = unsafe { sys::MWCreateEvent() };
         if event == 0 {
             whatever!("unable to create event");
         }
+        let capture_event = unsafe { sys::MWCreateEvent() };
+        if capture_event == 0 {
+            unsafe { sys::MWCloseEvent(event) };
+            whatever!("unable to create event");
+        }
         Ok(Self {
             handle,
             info,
             event,
+            capture_event,
+            hdr_metadata: None,
+            anc: None,
+            anc_packets: Vec::new(),
         })
     }
+
+    pub fn start_video_capture(&mut self) -> Result<()> {
+        unsafe {
+            if sys::MWStartVideoCapture(self.handle(), self.capture_event)
+                != sys::_MW_RESULT__MW_SUCCEEDED
+            {
+                whatever!("unable to start video capture");
+            }
+        }
+        Ok(())
+    }
+
+    pub fn stop_video_capture(&mut self) -> Result<()> {
+        unsafe {
+            if sys::MWStopVideoCapture(self.handle()) != sys::_MW_RESULT__MW_SUCCEEDED {
+                whatever!("unable to stop video capture");
+            }
+        }
+        Ok(())
+    }
+
+    /// Blocks until an event registered via `register_notify` (e.g. `VIDEO_FRAME_BUFFERED`)
+    /// occurs.
+    pub fn wait(&self) -> Result<()> {
+        if unsafe { sys::MWWaitEvent(self.event, INFINITE) } != 1 {
+            whatever!("unable to wait for event");
+        }
+        Ok(())
+    }
+
+    /// Enables HDR metadata tracking. The HDR and AVI InfoFrames are re-read whenever the input
+    /// specific status changes, and the resulting metadata is attached to each captured frame.
+    pub fn enable_hdr_metadata(&mut self) -> Result<()> {
+        if self.hdr_metadata.is_some() {
+            whatever!("hdr metadata already enabled");
+        }
+        self.hdr_metadata = Some(HdrMetadataTracker::new(&*self)?);
+        Ok(())
+    }
+
+    pub fn disable_hdr_metadata(&mut self) -> Result<()> {
+        close_monitor(self, |channel| &mut channel.hdr_metadata)
+    }
+
+    /// Enables capturing the given types of ANC packets from SDI inputs. Each captured frame or
+    /// field is given the packets received since the previous capture.
+    pub fn enable_anc(&mut self, subscriptions: &[AncSubscription]) -> Result<()> {
+        if self.anc.is_some() {
+            whatever!("anc already enabled");
+        }
+        self.anc = Some(AncMonitor::new(&*self, subscriptions)?);
+        Ok(())
+    }
+
+    pub fn disable_anc(&mut self) -> Result<()> {
+        close_monitor(self, |channel| &mut channel.anc)?;
+        self.anc_packets.clear();
+        Ok(())
+    }
+
+    /// Captures a frame from the device's frame buffer into `buf`, blocking until it's complete.
+    /// Video capture must be started first.
+    ///
+    /// `VideoFrameId::NextBuffered` and `VideoFrameId::NextBuffering` aren't supported, since the
+    /// frame's info can't be read before it's buffered.
+    pub fn capture_video_frame(
+        &mut self,
+        id: VideoFrameId,
+        buf: &mut [u8],
+        params: &ProVideoCaptureParams,
+    ) -> Result<ProVideoCaptureInfo> {
+        let buffer_info = self.get_video_buffer_info()?;
+        let index = match id {
+            VideoFrameId::NewestBuffered => buffer_info.newest_buffered(),
+            VideoFrameId::NewestBuffering => buffer_info.newest_buffering(),
+            VideoFrameId::Index(index) => index,
+            VideoFrameId::NextBuffered | VideoFrameId::NextBuffering => {
+                whatever!("unable to capture a frame that hasn't been buffered yet")
+            }
+        };
+        let frame_info = self.get_video_frame_info(index)?;
+        // A frame is buffered once its last field is, and takes its timecode from its first.
+        let (first_field, last_field) = if !frame_info.is_interlaced() {
+            (Field::Top, Field::Top)
+        } else if frame_info.is_top_field_first() {
+            (Field::Top, Field::Bottom)
+        } else {
+            (Field::Bottom, Field::Top)
+        };
+        let hdr_metadata = self.poll_monitors()?;
+        self.capture(index, buf, params)?;
+        Ok(ProVideoCaptureInfo::new(
+            None,
+            frame_info.field_buffered_time(last_field),
+            frame_info.timecode(first_field),
+            hdr_metadata,
+            std::mem::take(&mut self.anc_packets),
+        ))
+    }
+
+    fn capture(&mut self, index: u8, buf: &mut [u8], params: &ProVideoCaptureParams) -> Result<()> {
+        let image_size = params
+            .format
+            .image_size(params.width, params.height, params.stride);
+        if image_size == 0 || buf.len() < image_size {
+            whatever!("buffer is too small for the requested image");
+        }
+        unsafe {
+            if sys::MWCaptureVideoFrameToVirtualAddressEx(
+                self.handle(),
+                VideoFrameId::Index(index).into(),
+                buf.as_mut_ptr(),
+                buf.len() as _,
+                params.stride as _,
+                params.bottom_up as _,
+                0,
+                params.format.as_u32(),
+                params.width as _,
+                params.height as _,
+                0,
+                0,
+                0,
+                std::ptr::null(),
+                0,
+                100,
+                0,
+                100,
+                0,
+                params.deinterlace_mode.into(),
+                AspectRatioConvertMode::Ignore.into(),
+                std::ptr::null(),
+                std::ptr::null(),
+                0,
+                0,
+                VideoColorFormat::Unknown.into(),
+                QuantizationRange::Unknown.into(),
+                SaturationRange::Unknown.into(),
+            ) != sys::_MW_RESULT__MW_SUCCEEDED
+            {
+                whatever!("unable to capture video frame");
+            }
+        }
+        self.wait_for_capture()
+    }
+
+    pub fn get_video_buffer_info(&self) -> Result<VideoBufferInfo> {
+        unsafe {
+            let mut info = MaybeUninit::uninit();
+            if sys::MWGetVideoBufferInfo(self.handle(), info.as_mut_ptr())
+                != sys::_MW_RESULT__MW_SUCCEEDED
+            {
+                whatever!("unable to get video buffer info");
+            }
+            Ok(info.assume_init().into())
+        }
+    }
+
+    pub fn get_video_frame_info(&self, index: u8) -> Result<VideoFrameInfo> {
+        unsafe {
+            let mut info = MaybeUninit::uninit();
+            if sys::MWGetVideoFrameInfo(self.handle(), index, info.as_mut_ptr())
+                != sys::_MW_RESULT__MW_SUCCEEDED
+            {
+                whatever!("unable to get video frame info");
+            }
+            Ok(info.assume_init().into())
+        }
+    }
+
+    /// Captures the most recently buffered field of an interlaced source into `buf`.
+    ///
+    /// This is intended to be invoked each time `VIDEO_FIELD_BUFFERED` is notified, which yields
+    /// fields at the source's field rate. `params.height` should be the field height, i.e. half
+    /// the frame height. `params.deinterlace_mode` is ignored.
+    pub fn capture_video_field(
+        &mut self,
+        buf: &mut [u8],
+        params: &ProVideoCaptureParams,
+    ) -> Result<ProVideoCaptureInfo> {
+        let buffer_info = self.get_video_buffer_info()?;
+        let index = buffer_info.newest_buffered();
+        let field = buffer_info.buffered_field();
+        let frame_info = self.get_video_frame_info(index)?;
+        let params = ProVideoCaptureParams {
+            deinterlace_mode: match field {
+                Field::Top => DeinterlaceMode::TopField,
+                Field::Bottom => DeinterlaceMode::BottomField,
+            },
+            ..*params
+        };
+        let hdr_metadata = self.poll_monitors()?;
+        self.capture(index, buf, &params)?;
+        Ok(ProVideoCaptureInfo::new(
+            Some(field),
+            frame_info.field_buffered_time(field),
+            frame_info.timecode(field),
+            hdr_metadata,
+            std::mem::take(&mut self.anc_packets),
+        ))
+    }
+
+    /// Polls the channel's monitors, returning the HDR metadata to attach to a capture.
+    fn poll_monitors(&mut self) -> Result<Option<HdrMetadata>> {
+        poll_monitor(self, |channel| &mut channel.hdr_metadata)?;
+        if let Some(packets) = poll_monitor(self, |channel| &mut channel.anc)? {
+            self.anc_packets.extend(packets);
+        }
+        Ok(self.hdr_metadata.as_ref().map(|tracker| tracker.metadata()))
+    }
+
+    fn wait_for_capture(&mut self) -> Result<()> {
+        loop {
+            if unsafe { sys::MWWaitEvent(self.capture_event, INFINITE) } != 1 {
+                whatever!("unable to wait for capture event");
+            }
+            let status = unsafe {
+                let mut status = MaybeUninit::uninit();
+                if sys::MWGetVideoCaptureStatus(self.handle(), status.as_mut_ptr())
+                    != sys::_MW_RESULT__MW_SUCCEEDED
+                {
+                    whatever!("unable to get video capture status");
+                }
+                status.assume_init()
+            };
+            if status.bFrameCompleted != 0 {
+                return Ok(());
+            }
+        }
+    }
 }
 
 unsafe impl UniversalCaptureFamilyChannel for ProChannel {
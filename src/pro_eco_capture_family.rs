@@ -1,9 +1,15 @@
-use super::{sys, AudioCaptureFrame, NotifyEvents, Result, UniversalCaptureFamilyChannel};
+use super::{
+    sys, validate_proc_value, AudioCaptureFrame, NotifyEvents, Result,
+    UniversalCaptureFamilyChannel, VideoProcSettings,
+};
 use snafu::prelude::*;
-use std::{os::raw::c_longlong, time::Duration};
+use std::{
+    os::raw::{c_int, c_longlong, c_void},
+    time::Duration,
+};
 
 #[derive(Clone, Copy)]
-pub struct NotifyHandle(sys::MWCAP_PTR);
+pub struct NotifyHandle(pub(crate) sys::MWCAP_PTR);
 
 /// # Safety
 /// The pointers returned by implementations of this trait must be valid.
@@ -22,6 +28,105 @@ pub unsafe trait ProEcoCaptureFamilyChannel: UniversalCaptureFamilyChannel {
         }
     }
 
+    /// Returns the board temperature in degrees Celsius.
+    fn get_temperature(&self) -> Result<f32> {
+        let mut temp = 0;
+        unsafe {
+            if sys::MWGetTemperature(self.handle(), &mut temp as *mut _)
+                != sys::_MW_RESULT__MW_SUCCEEDED
+            {
+                whatever!("unable to get temperature");
+            }
+        }
+        // The SDK reports the temperature in tenths of a degree.
+        Ok(temp as f32 / 10.0)
+    }
+
+    /// Returns the brightness, contrast, hue, and saturation applied to captured video.
+    fn get_video_proc_settings(&self) -> Result<VideoProcSettings> {
+        let get = |name: &str,
+                   f: unsafe extern "C" fn(*mut c_void, *mut c_int) -> sys::MW_RESULT|
+         -> Result<i16> {
+            let mut value: c_int = 0;
+            unsafe {
+                if f(self.handle(), &mut value as *mut _) != sys::_MW_RESULT__MW_SUCCEEDED {
+                    whatever!("unable to get video {}", name);
+                }
+            }
+            Ok(value as _)
+        };
+        Ok(VideoProcSettings {
+            brightness: get("brightness", sys::MWGetVideoBrightness)?,
+            contrast: get("contrast", sys::MWGetVideoContrast)?,
+            hue: get("hue", sys::MWGetVideoHue)?,
+            saturation: get("saturation", sys::MWGetVideoSaturation)?,
+        })
+    }
+
+    /// Applies all of the given settings, e.g. ones previously returned by
+    /// `get_video_proc_settings`. Nothing is applied if any of them are out of range.
+    fn set_video_proc_settings(&self, settings: &VideoProcSettings) -> Result<()> {
+        settings.validate()?;
+        self.set_video_brightness(settings.brightness)?;
+        self.set_video_contrast(settings.contrast)?;
+        self.set_video_hue(settings.hue)?;
+        self.set_video_saturation(settings.saturation)
+    }
+
+    fn set_video_brightness(&self, brightness: i16) -> Result<()> {
+        validate_proc_value(
+            "brightness",
+            brightness,
+            VideoProcSettings::BRIGHTNESS_RANGE,
+        )?;
+        unsafe {
+            if sys::MWSetVideoBrightness(self.handle(), brightness as _)
+                != sys::_MW_RESULT__MW_SUCCEEDED
+            {
+                whatever!("unable to set video brightness");
+            }
+        }
+        Ok(())
+    }
+
+    fn set_video_contrast(&self, contrast: i16) -> Result<()> {
+        validate_proc_value("contrast", contrast, VideoProcSettings::CONTRAST_RANGE)?;
+        unsafe {
+            if sys::MWSetVideoContrast(self.handle(), contrast as _)
+                != sys::_MW_RESULT__MW_SUCCEEDED
+            {
+                whatever!("unable to set video contrast");
+            }
+        }
+        Ok(())
+    }
+
+    fn set_video_hue(&self, hue: i16) -> Result<()> {
+        validate_proc_value("hue", hue, VideoProcSettings::HUE_RANGE)?;
+        unsafe {
+            if sys::MWSetVideoHue(self.handle(), hue as _) != sys::_MW_RESULT__MW_SUCCEEDED {
+                whatever!("unable to set video hue");
+            }
+        }
+        Ok(())
+    }
+
+    fn set_video_saturation(&self, saturation: i16) -> Result<()> {
+        validate_proc_value(
+            "saturation",
+            saturation,
+            VideoProcSettings::SATURATION_RANGE,
+        )?;
+        unsafe {
+            if sys::MWSetVideoSaturation(self.handle(), saturation as _)
+                != sys::_MW_RESULT__MW_SUCCEEDED
+            {
+                whatever!("unable to set video saturation");
+            }
+        }
+        Ok(())
+    }
+
     /// Causes `wait` to return any time the specified events (e.g.
     /// `MWCAP_NOTIFY_AUDIO_FRAME_BUFFERED`) occur. Returns a handle that can be used to
     /// unregister.
@@ -44,6 +149,21 @@ pub unsafe trait ProEcoCaptureFamilyChannel: UniversalCaptureFamilyChannel {
         Ok(())
     }
 
+    /// Returns the events that have occurred for the given handle since the last call, e.g. to
+    /// find out why `wait` returned.
+    fn get_notify_status(&self, handle: NotifyHandle) -> Result<NotifyEvents> {
+        let mut status = 0;
+        unsafe {
+            if sys::MWGetNotifyStatus(self.handle(), handle.0, &mut status as *mut _)
+                != sys::_MW_RESULT__MW_SUCCEEDED
+            {
+                whatever!("unable to get notify status");
+            }
+        }
+        // The upper 32 bits are used for events that can't be registered via `register_notify`.
+        Ok(NotifyEvents::from_bits_truncate(status as u32))
+    }
+
     fn start_audio_capture(&mut self) -> Result<()> {
         unsafe {
             if sys::MWStartAudioCapture(self.handle()) != sys::_MW_RESULT__MW_SUCCEEDED {
@@ -76,3 +196,43 @@ pub unsafe trait ProEcoCaptureFamilyChannel: UniversalCaptureFamilyChannel {
         }
     }
 }
+
+/// Something that watches a channel through a notification registered with `register_notify`,
+/// such as `SignalMonitor`.
+///
+/// Notifications share the channel's event with capture, so `poll` should be invoked whenever
+/// `wait` returns. Monitors don't borrow the channel, so `close` must be invoked to unregister
+/// them.
+pub trait NotifyMonitor: Sized {
+    type Output;
+
+    /// Handles any notifications received since the last call.
+    fn poll<C: ProEcoCaptureFamilyChannel + ?Sized>(&mut self, channel: &C)
+        -> Result<Self::Output>;
+
+    fn close<C: ProEcoCaptureFamilyChannel + ?Sized>(self, channel: &C) -> Result<()>;
+}
+
+/// Polls one of a channel's monitors, if it's enabled. The monitor is taken out of the channel
+/// while it's polled, since it's polled with the channel.
+pub(crate) fn poll_monitor<C: ProEcoCaptureFamilyChannel, M: NotifyMonitor>(
+    channel: &mut C,
+    monitor: fn(&mut C) -> &mut Option<M>,
+) -> Result<Option<M::Output>> {
+    let Some(mut taken) = monitor(channel).take() else {
+        return Ok(None);
+    };
+    let result = taken.poll(&*channel);
+    *monitor(channel) = Some(taken);
+    result.map(Some)
+}
+
+pub(crate) fn close_monitor<C: ProEcoCaptureFamilyChannel, M: NotifyMonitor>(
+    channel: &mut C,
+    monitor: fn(&mut C) -> &mut Option<M>,
+) -> Result<()> {
+    match monitor(channel).take() {
+        Some(taken) => taken.close(&*channel),
+        None => Ok(()),
+    }
+}
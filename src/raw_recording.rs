@@ -0,0 +1,321 @@
+use super::{AudioCaptureFrame, EcoVideoCaptureStatus, FourCC, Rational, Result};
+use snafu::prelude::*;
+use std::{
+    io::{ErrorKind, Read, Write},
+    time::Duration,
+};
+
+const FILE_MAGIC: &[u8; 8] = b"MWRAW\x00\x01\n";
+const PACKET_MAGIC: &[u8; 4] = b"MWPK";
+
+const VIDEO_STREAM: u8 = 0;
+const AUDIO_STREAM: u8 = 1;
+
+/// A stream of a raw recording.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RawStream {
+    /// Uncompressed video frames, as captured into a frame buffer.
+    Video {
+        format: FourCC,
+        width: u16,
+        height: u16,
+        stride: u32,
+        frame_rate: Rational,
+    },
+    /// Audio capture frames, stored as all of their 32-bit samples regardless of the signal's
+    /// channel count and bit depth.
+    Audio {
+        sample_rate: u32,
+        channels: u16,
+        bits_per_sample: u16,
+    },
+}
+
+/// A captured frame of a raw recording.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawPacket {
+    /// The index of the packet's stream.
+    pub stream: usize,
+    /// The device time at which the frame was captured.
+    pub timestamp: Duration,
+    pub data: Vec<u8>,
+}
+
+impl RawPacket {
+    /// Returns the samples of an audio packet, in the same order as `AudioCaptureFrame::samples`.
+    pub fn audio_samples(&self) -> Vec<u32> {
+        self.data
+            .chunks_exact(4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect()
+    }
+}
+
+/// Writes captured frames to a simple timestamped container, which preserves the exact data and
+/// device timestamp of every frame for replay with `RawReader`.
+///
+/// The file starts with the stream descriptions, followed by packets with a sync code, the stream
+/// index, the timestamp in nanoseconds, and the size of the data. All values are little-endian.
+pub struct RawWriter<W: Write> {
+    out: W,
+    streams: Vec<RawStream>,
+}
+
+impl<W: Write> RawWriter<W> {
+    pub fn new(mut out: W, streams: &[RawStream]) -> Result<Self> {
+        let mut header = FILE_MAGIC.to_vec();
+        header.extend_from_slice(&(streams.len() as u32).to_le_bytes());
+        for stream in streams {
+            match *stream {
+                RawStream::Video {
+                    format,
+                    width,
+                    height,
+                    stride,
+                    frame_rate,
+                } => {
+                    header.push(VIDEO_STREAM);
+                    header.extend_from_slice(&format.as_u32().to_le_bytes());
+                    header.extend_from_slice(&width.to_le_bytes());
+                    header.extend_from_slice(&height.to_le_bytes());
+                    header.extend_from_slice(&stride.to_le_bytes());
+                    header.extend_from_slice(&frame_rate.num.to_le_bytes());
+                    header.extend_from_slice(&frame_rate.den.to_le_bytes());
+                }
+                RawStream::Audio {
+                    sample_rate,
+                    channels,
+                    bits_per_sample,
+                } => {
+                    header.push(AUDIO_STREAM);
+                    header.extend_from_slice(&sample_rate.to_le_bytes());
+                    header.extend_from_slice(&channels.to_le_bytes());
+                    header.extend_from_slice(&bits_per_sample.to_le_bytes());
+                }
+            }
+        }
+        out.write_all(&header)
+            .whatever_context("unable to write raw recording header")?;
+        Ok(Self {
+            out,
+            streams: streams.to_vec(),
+        })
+    }
+
+    pub fn write_packet(&mut self, stream: usize, timestamp: Duration, data: &[u8]) -> Result<()> {
+        if stream >= self.streams.len() {
+            whatever!("invalid stream index {}", stream);
+        }
+        let Ok(size) = u32::try_from(data.len()) else {
+            whatever!("packet is too large");
+        };
+        let mut header = PACKET_MAGIC.to_vec();
+        header.extend_from_slice(&(stream as u32).to_le_bytes());
+        header.extend_from_slice(&(timestamp.as_nanos() as u64).to_le_bytes());
+        header.extend_from_slice(&size.to_le_bytes());
+        self.out
+            .write_all(&header)
+            .and_then(|_| self.out.write_all(data))
+            .whatever_context("unable to write raw recording packet")
+    }
+
+    pub fn write_video_frame(
+        &mut self,
+        stream: usize,
+        status: &EcoVideoCaptureStatus,
+    ) -> Result<()> {
+        self.write_packet(stream, status.timestamp(), status.frame().as_slice())
+    }
+
+    pub fn write_audio_frame(&mut self, stream: usize, frame: &AudioCaptureFrame) -> Result<()> {
+        let data: Vec<u8> = frame
+            .samples()
+            .iter()
+            .flat_map(|sample| sample.to_le_bytes())
+            .collect();
+        self.write_packet(stream, frame.timestamp(), &data)
+    }
+
+    /// Flushes and returns the underlying writer.
+    pub fn into_inner(mut self) -> Result<W> {
+        self.out
+            .flush()
+            .whatever_context("unable to flush raw recording")?;
+        Ok(self.out)
+    }
+}
+
+/// Reads a recording written by `RawWriter`.
+pub struct RawReader<R: Read> {
+    input: R,
+    streams: Vec<RawStream>,
+}
+
+impl<R: Read> RawReader<R> {
+    pub fn new(mut input: R) -> Result<Self> {
+        let mut magic = [0; 8];
+        read_exact(&mut input, &mut magic)?;
+        if &magic != FILE_MAGIC {
+            whatever!("not a raw recording");
+        }
+        let count = read_u32(&mut input)?;
+        let mut streams = Vec::new();
+        for _ in 0..count {
+            let mut kind = [0];
+            read_exact(&mut input, &mut kind)?;
+            streams.push(match kind[0] {
+                VIDEO_STREAM => RawStream::Video {
+                    format: read_u32(&mut input)?.into(),
+                    width: read_u16(&mut input)?,
+                    height: read_u16(&mut input)?,
+                    stride: read_u32(&mut input)?,
+                    frame_rate: Rational {
+                        num: read_u32(&mut input)?,
+                        den: read_u32(&mut input)?,
+                    },
+                },
+                AUDIO_STREAM => RawStream::Audio {
+                    sample_rate: read_u32(&mut input)?,
+                    channels: read_u16(&mut input)?,
+                    bits_per_sample: read_u16(&mut input)?,
+                },
+                kind => whatever!("unknown raw recording stream type {}", kind),
+            });
+        }
+        Ok(Self { input, streams })
+    }
+
+    pub fn streams(&self) -> &[RawStream] {
+        &self.streams
+    }
+
+    /// Reads the next packet, or returns `None` at the end of the recording.
+    pub fn read_packet(&mut self) -> Result<Option<RawPacket>> {
+        let mut magic = [0; 4];
+        match self.input.read_exact(&mut magic) {
+            Ok(()) => {}
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e).whatever_context("unable to read raw recording"),
+        }
+        if &magic != PACKET_MAGIC {
+            whatever!("raw recording is corrupt");
+        }
+        let stream = read_u32(&mut self.input)? as usize;
+        if stream >= self.streams.len() {
+            whatever!("invalid stream index {}", stream);
+        }
+        let timestamp = Duration::from_nanos(read_u64(&mut self.input)?);
+        // The size isn't trusted, so the data is only allocated as it's read.
+        let size = read_u32(&mut self.input)? as usize;
+        let mut data = Vec::new();
+        (&mut self.input)
+            .take(size as u64)
+            .read_to_end(&mut data)
+            .whatever_context("unable to read raw recording")?;
+        if data.len() < size {
+            whatever!("raw recording is truncated");
+        }
+        Ok(Some(RawPacket {
+            stream,
+            timestamp,
+            data,
+        }))
+    }
+}
+
+fn read_exact<R: Read>(input: &mut R, buf: &mut [u8]) -> Result<()> {
+    input
+        .read_exact(buf)
+        .whatever_context("unable to read raw recording")
+}
+
+fn read_u16<R: Read>(input: &mut R) -> Result<u16> {
+    let mut buf = [0; 2];
+    read_exact(input, &mut buf)?;
+    Ok(u16::from_le_bytes(buf))
+}
+
+fn read_u32<R: Read>(input: &mut R) -> Result<u32> {
+    let mut buf = [0; 4];
+    read_exact(input, &mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_u64<R: Read>(input: &mut R) -> Result<u64> {
+    let mut buf = [0; 8];
+    read_exact(input, &mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let streams = [
+            RawStream::Video {
+                format: FourCC::new('N', 'V', '1', '2'),
+                width: 1920,
+                height: 1080,
+                stride: 1920,
+                frame_rate: Rational::new(60000, 1001),
+            },
+            RawStream::Audio {
+                sample_rate: 48000,
+                channels: 2,
+                bits_per_sample: 24,
+            },
+        ];
+        let packets = [
+            RawPacket {
+                stream: 0,
+                timestamp: Duration::from_nanos(123_456_789_100),
+                data: vec![1, 2, 3],
+            },
+            RawPacket {
+                stream: 1,
+                timestamp: Duration::from_nanos(123_456_790_000),
+                data: [0x12345678u32, 0x9abcdef0]
+                    .iter()
+                    .flat_map(|s| s.to_le_bytes())
+                    .collect(),
+            },
+        ];
+
+        let mut writer = RawWriter::new(Vec::new(), &streams).unwrap();
+        for packet in &packets {
+            writer
+                .write_packet(packet.stream, packet.timestamp, &packet.data)
+                .unwrap();
+        }
+        assert!(writer.write_packet(2, Duration::ZERO, &[]).is_err());
+        let recording = writer.into_inner().unwrap();
+
+        let mut reader = RawReader::new(recording.as_slice()).unwrap();
+        assert_eq!(reader.streams(), &streams);
+        assert_eq!(reader.read_packet().unwrap().as_ref(), Some(&packets[0]));
+        let audio = reader.read_packet().unwrap().unwrap();
+        assert_eq!(audio, packets[1]);
+        assert_eq!(audio.audio_samples(), vec![0x12345678, 0x9abcdef0]);
+        assert_eq!(reader.read_packet().unwrap(), None);
+
+        // A truncated packet is an error rather than the end of the recording.
+        let mut reader = RawReader::new(&recording[..recording.len() - 1]).unwrap();
+        reader.read_packet().unwrap();
+        assert!(reader.read_packet().is_err());
+
+        // So is a packet whose size runs past the end of the recording.
+        let mut recording = RawWriter::new(Vec::new(), &streams)
+            .unwrap()
+            .into_inner()
+            .unwrap();
+        recording.extend_from_slice(PACKET_MAGIC);
+        recording.extend_from_slice(&0u32.to_le_bytes());
+        recording.extend_from_slice(&0u64.to_le_bytes());
+        recording.extend_from_slice(&u32::MAX.to_le_bytes());
+        recording.extend_from_slice(&[1, 2, 3]);
+        let mut reader = RawReader::new(recording.as_slice()).unwrap();
+        assert!(reader.read_packet().is_err());
+    }
+}
@@ -0,0 +1,431 @@
+use super::{
+    AudioSignalStatus, NotifyEvents, NotifyHandle, NotifyMonitor, ProEcoCaptureFamilyChannel,
+    Rational, Result, VideoSignalState, VideoSignalStatus,
+};
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+/// The parts of a video signal that matter to a capture pipeline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VideoFormat {
+    pub width: u16,
+    pub height: u16,
+    pub interlaced: bool,
+    pub frame_rate: Option<Rational>,
+}
+
+impl From<&VideoSignalStatus> for VideoFormat {
+    fn from(status: &VideoSignalStatus) -> Self {
+        Self {
+            width: status.image_width(),
+            height: status.image_height(),
+            interlaced: status.is_interlaced(),
+            frame_rate: status.frame_rate(),
+        }
+    }
+}
+
+/// Formats as e.g. "1080p59.94" or "1080i50". As is conventional, interlaced formats use the
+/// field rate.
+impl fmt::Display for VideoFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{}",
+            self.height,
+            if self.interlaced { 'i' } else { 'p' }
+        )?;
+        if let Some(rate) = self.frame_rate {
+            let rate = rate.as_f64() * if self.interlaced { 2.0 } else { 1.0 };
+            let rate = format!("{:.2}", rate);
+            write!(f, "{}", rate.trim_end_matches('0').trim_end_matches('.'))?;
+        }
+        Ok(())
+    }
+}
+
+/// The parts of an audio signal that matter to a capture pipeline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AudioFormat {
+    pub sample_rate: u32,
+    pub channel_count: u32,
+    pub bits_per_sample: u8,
+    pub is_lpcm: bool,
+}
+
+impl From<&AudioSignalStatus> for AudioFormat {
+    fn from(status: &AudioSignalStatus) -> Self {
+        Self {
+            sample_rate: status.sample_rate(),
+            channel_count: status.channel_count(),
+            bits_per_sample: status.bits_per_sample(),
+            is_lpcm: status.is_lpcm(),
+        }
+    }
+}
+
+impl fmt::Display for AudioFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} Hz, {} channels, {}-bit{}",
+            self.sample_rate,
+            self.channel_count,
+            self.bits_per_sample,
+            if self.is_lpcm { "" } else { " (non-PCM)" }
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignalEvent {
+    VideoAcquired(VideoFormat),
+    VideoLost,
+    VideoUnsupported,
+    VideoFormatChanged { from: VideoFormat, to: VideoFormat },
+    AudioAcquired(AudioFormat),
+    AudioLost,
+    AudioFormatChanged { from: AudioFormat, to: AudioFormat },
+}
+
+impl fmt::Display for SignalEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::VideoAcquired(format) => write!(f, "video signal acquired: {}", format),
+            Self::VideoLost => write!(f, "video signal lost"),
+            Self::VideoUnsupported => write!(f, "video signal unsupported"),
+            Self::VideoFormatChanged { from, to } => {
+                write!(f, "video format changed from {} to {}", from, to)
+            }
+            Self::AudioAcquired(format) => write!(f, "audio signal acquired: {}", format),
+            Self::AudioLost => write!(f, "audio signal lost"),
+            Self::AudioFormatChanged { from, to } => {
+                write!(f, "audio format changed from {} to {}", from, to)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VideoState {
+    None,
+    Locking,
+    Unsupported,
+    Locked(VideoFormat),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Snapshot {
+    video: VideoState,
+    audio: Option<AudioFormat>,
+}
+
+impl Snapshot {
+    fn new(video: &VideoSignalStatus, audio: &AudioSignalStatus) -> Self {
+        Self {
+            video: match video.state() {
+                VideoSignalState::Locked => VideoState::Locked(video.into()),
+                VideoSignalState::Locking => VideoState::Locking,
+                VideoSignalState::Unsupported => VideoState::Unsupported,
+                VideoSignalState::None | VideoSignalState::Other => VideoState::None,
+            },
+            audio: match audio.sample_rate() {
+                0 => None,
+                _ if audio.channel_count() == 0 => None,
+                _ => Some(audio.into()),
+            },
+        }
+    }
+
+    fn read<C: ProEcoCaptureFamilyChannel + ?Sized>(channel: &C) -> Result<Self> {
+        Ok(Self::new(
+            &channel.get_video_signal_status()?,
+            &channel.get_audio_signal_status()?,
+        ))
+    }
+
+    fn is_stable(&self) -> bool {
+        self.video != VideoState::Locking
+    }
+
+    /// Returns the events describing the transition from `self` to `next`.
+    fn diff(&self, next: &Self) -> Vec<SignalEvent> {
+        let mut events = Vec::new();
+        match (self.video, next.video) {
+            (a, b) if a == b => {}
+            (VideoState::Locked(from), VideoState::Locked(to)) => {
+                events.push(SignalEvent::VideoFormatChanged { from, to })
+            }
+            (_, VideoState::Locked(format)) => events.push(SignalEvent::VideoAcquired(format)),
+            (_, VideoState::Unsupported) => events.push(SignalEvent::VideoUnsupported),
+            (_, VideoState::None | VideoState::Locking) => events.push(SignalEvent::VideoLost),
+        }
+        match (self.audio, next.audio) {
+            (a, b) if a == b => {}
+            (Some(from), Some(to)) => events.push(SignalEvent::AudioFormatChanged { from, to }),
+            (_, Some(format)) => events.push(SignalEvent::AudioAcquired(format)),
+            (_, None) => events.push(SignalEvent::AudioLost),
+        }
+        events
+    }
+}
+
+/// Watches a channel's input signal and reports changes once they've settled.
+///
+/// When a source renegotiates, the card typically goes through `Locking` and fires several signal
+/// change notifications before the new format is stable. The monitor only reports a change once
+/// the signal has stayed the same for the debounce period, and never reports the intermediate
+/// `Locking` state.
+///
+/// Besides whenever `wait` returns, `poll` should be invoked again at `next_deadline` if one is
+/// returned.
+pub struct SignalMonitor {
+    handle: NotifyHandle,
+    debounce: Duration,
+    reported: Snapshot,
+    pending: Option<(Snapshot, Instant)>,
+}
+
+impl SignalMonitor {
+    pub fn new<C: ProEcoCaptureFamilyChannel + ?Sized>(
+        channel: &C,
+        debounce: Duration,
+    ) -> Result<Self> {
+        let handle = channel.register_notify(
+            NotifyEvents::VIDEO_SIGNAL_CHANGE
+                | NotifyEvents::AUDIO_SIGNAL_CHANGE
+                | NotifyEvents::INPUT_SPECIFIC_CHANGE,
+        )?;
+        let snapshot = match Snapshot::read(channel) {
+            Ok(snapshot) => snapshot,
+            Err(e) => {
+                let _ = channel.unregister_notify(handle);
+                return Err(e);
+            }
+        };
+        Ok(Self::with_snapshot(
+            handle,
+            debounce,
+            snapshot,
+            Instant::now(),
+        ))
+    }
+
+    fn with_snapshot(
+        handle: NotifyHandle,
+        debounce: Duration,
+        snapshot: Snapshot,
+        now: Instant,
+    ) -> Self {
+        let mut monitor = Self {
+            handle,
+            debounce,
+            reported: Snapshot {
+                video: VideoState::None,
+                audio: None,
+            },
+            pending: None,
+        };
+        if snapshot.is_stable() {
+            monitor.reported = snapshot;
+        } else {
+            monitor.pending = Some((snapshot, now));
+        }
+        monitor
+    }
+
+    /// Returns the last reported video format, or `None` if there's no usable video signal.
+    pub fn video_format(&self) -> Option<VideoFormat> {
+        match self.reported.video {
+            VideoState::Locked(format) => Some(format),
+            _ => None,
+        }
+    }
+
+    /// Returns the last reported audio format, or `None` if there's no audio signal.
+    pub fn audio_format(&self) -> Option<AudioFormat> {
+        self.reported.audio
+    }
+
+    /// Returns the time by which `poll` should be invoked again, if a change is waiting to settle.
+    ///
+    /// There's no deadline while the signal is locking, since it can't settle until the channel is
+    /// notified of another change.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.pending
+            .filter(|(snapshot, _)| snapshot.is_stable())
+            .map(|(_, since)| since + self.debounce)
+    }
+
+    fn update(&mut self, snapshot: Snapshot, now: Instant) -> Vec<SignalEvent> {
+        let since = match self.pending {
+            Some((pending, since)) if pending == snapshot => since,
+            _ if snapshot == self.reported => {
+                // The signal went back to what it was before it settled.
+                self.pending = None;
+                return Vec::new();
+            }
+            _ => {
+                self.pending = Some((snapshot, now));
+                now
+            }
+        };
+        if !snapshot.is_stable() || now.duration_since(since) < self.debounce {
+            return Vec::new();
+        }
+        self.pending = None;
+        let events = self.reported.diff(&snapshot);
+        self.reported = snapshot;
+        events
+    }
+}
+
+impl NotifyMonitor for SignalMonitor {
+    type Output = Vec<SignalEvent>;
+
+    /// Checks for signal changes, returning any that have settled since the last call.
+    fn poll<C: ProEcoCaptureFamilyChannel + ?Sized>(
+        &mut self,
+        channel: &C,
+    ) -> Result<Vec<SignalEvent>> {
+        let notified = !channel.get_notify_status(self.handle)?.is_empty();
+        if !notified && self.pending.is_none() {
+            return Ok(Vec::new());
+        }
+        let snapshot = Snapshot::read(channel)?;
+        Ok(self.update(snapshot, Instant::now()))
+    }
+
+    fn close<C: ProEcoCaptureFamilyChannel + ?Sized>(self, channel: &C) -> Result<()> {
+        channel.unregister_notify(self.handle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn video(height: u16, interlaced: bool, num: u32, den: u32) -> VideoFormat {
+        VideoFormat {
+            width: height * 16 / 9,
+            height,
+            interlaced,
+            frame_rate: Some(Rational::new(num, den)),
+        }
+    }
+
+    fn audio(sample_rate: u32) -> AudioFormat {
+        AudioFormat {
+            sample_rate,
+            channel_count: 2,
+            bits_per_sample: 24,
+            is_lpcm: true,
+        }
+    }
+
+    #[test]
+    fn test_video_format_display() {
+        assert_eq!(video(1080, false, 60000, 1001).to_string(), "1080p59.94");
+        assert_eq!(video(2160, false, 50, 1).to_string(), "2160p50");
+        assert_eq!(video(1080, true, 30000, 1001).to_string(), "1080i59.94");
+        assert_eq!(video(720, false, 24000, 1001).to_string(), "720p23.98");
+    }
+
+    #[test]
+    fn test_debounce() {
+        let debounce = Duration::from_millis(500);
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+
+        let hd = Snapshot {
+            video: VideoState::Locked(video(1080, false, 60000, 1001)),
+            audio: Some(audio(48000)),
+        };
+        let locking = Snapshot {
+            video: VideoState::Locking,
+            ..hd
+        };
+        let uhd = Snapshot {
+            video: VideoState::Locked(video(2160, false, 50, 1)),
+            ..hd
+        };
+
+        let mut monitor = SignalMonitor::with_snapshot(NotifyHandle(0), debounce, hd, start);
+        assert_eq!(monitor.next_deadline(), None);
+
+        // A renegotiation that bounces through locking is reported once, after it settles.
+        assert!(monitor.update(locking, at(0)).is_empty());
+        assert!(monitor.update(uhd, at(100)).is_empty());
+        assert!(monitor.update(locking, at(200)).is_empty());
+        assert!(monitor.update(uhd, at(300)).is_empty());
+        assert_eq!(monitor.next_deadline(), Some(at(800)));
+        assert!(monitor.update(uhd, at(700)).is_empty());
+        assert_eq!(
+            monitor.update(uhd, at(800)),
+            vec![SignalEvent::VideoFormatChanged {
+                from: video(1080, false, 60000, 1001),
+                to: video(2160, false, 50, 1),
+            }]
+        );
+        assert_eq!(monitor.video_format(), Some(video(2160, false, 50, 1)));
+        assert!(monitor.update(uhd, at(2000)).is_empty());
+
+        // A glitch that recovers before settling isn't reported.
+        assert!(monitor.update(locking, at(3000)).is_empty());
+        assert!(monitor.update(uhd, at(3100)).is_empty());
+        assert_eq!(monitor.next_deadline(), None);
+
+        // A stuck locking state is never reported.
+        assert!(monitor.update(locking, at(4000)).is_empty());
+        assert!(monitor.update(locking, at(9000)).is_empty());
+        assert_eq!(monitor.next_deadline(), None);
+
+        let lost = Snapshot {
+            video: VideoState::None,
+            audio: Some(audio(44100)),
+        };
+        assert!(monitor.update(lost, at(9100)).is_empty());
+        assert_eq!(
+            monitor.update(lost, at(9600)),
+            vec![
+                SignalEvent::VideoLost,
+                SignalEvent::AudioFormatChanged {
+                    from: audio(48000),
+                    to: audio(44100),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_locking_deadline() {
+        let debounce = Duration::from_millis(500);
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+
+        let hd = Snapshot {
+            video: VideoState::Locked(video(1080, false, 60000, 1001)),
+            audio: None,
+        };
+        let locking = Snapshot {
+            video: VideoState::Locking,
+            ..hd
+        };
+
+        // A monitor that starts out locking has no deadline, even once the debounce has passed.
+        let mut monitor = SignalMonitor::with_snapshot(NotifyHandle(0), debounce, locking, start);
+        assert_eq!(monitor.next_deadline(), None);
+        assert!(monitor.update(locking, at(1000)).is_empty());
+        assert_eq!(monitor.next_deadline(), None);
+
+        // The debounce starts over once the signal locks.
+        assert!(monitor.update(hd, at(1200)).is_empty());
+        assert_eq!(monitor.next_deadline(), Some(at(1700)));
+        assert_eq!(
+            monitor.update(hd, at(1700)),
+            vec![SignalEvent::VideoAcquired(video(1080, false, 60000, 1001))]
+        );
+    }
+}
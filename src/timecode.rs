@@ -0,0 +1,131 @@
+use std::fmt;
+
+/// A SMPTE 12M timecode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timecode {
+    pub hours: u8,
+    pub minutes: u8,
+    pub seconds: u8,
+    pub frames: u8,
+    pub drop_frame: bool,
+    /// The field mark, which is set for the second field of a frame in VITC.
+    pub field: bool,
+    /// The eight binary groups, with the first in the least significant nibble, if known.
+    pub user_bits: Option<u32>,
+}
+
+impl Timecode {
+    /// Decodes a timecode from its BCD bytes as laid out in the SMPTE 12M time address, i.e. with
+    /// the flags in the upper bits of the tens digits. Returns `None` if a digit is out of range.
+    pub fn from_bcd(frames: u8, seconds: u8, minutes: u8, hours: u8) -> Option<Self> {
+        let bcd = |b: u8, tens_mask: u8, max: u8| {
+            let (tens, units) = ((b >> 4) & tens_mask, b & 0x0f);
+            (units < 10)
+                .then_some(tens * 10 + units)
+                .filter(|v| *v <= max)
+        };
+        Some(Self {
+            hours: bcd(hours, 0x03, 23)?,
+            minutes: bcd(minutes, 0x07, 59)?,
+            seconds: bcd(seconds, 0x07, 59)?,
+            frames: bcd(frames, 0x03, 39)?,
+            drop_frame: frames & 0x40 != 0,
+            field: seconds & 0x80 != 0,
+            user_bits: None,
+        })
+    }
+
+    /// Decodes a SMPTE 12-2 ancillary timecode (ATC) packet's user data words, returning the
+    /// payload type along with the timecode.
+    pub fn from_atc(data: &[u8]) -> Option<(AtcType, Self)> {
+        if data.len() < 16 {
+            return None;
+        }
+        // Each word carries a nibble of the 64-bit timecode in bits 4-7, and a bit of the two
+        // distributed binary bit groups in bit 3.
+        let nibble = |i: usize| (data[i] >> 4) & 0x0f;
+        let byte = |i: usize| nibble(i + 2) << 4 | nibble(i);
+        let mut timecode = Self::from_bcd(byte(0), byte(4), byte(8), byte(12))?;
+        timecode.user_bits = Some((0..8).fold(0, |bits, group| {
+            bits | (nibble(group * 2 + 1) as u32) << (group * 4)
+        }));
+        let dbb1 = (0..8).fold(0, |bits, i| bits | ((data[i] >> 3) & 1) << i);
+        Some((dbb1.into(), timecode))
+    }
+}
+
+/// Formats as "hh:mm:ss:ff", or "hh:mm:ss;ff" for drop-frame timecode.
+impl fmt::Display for Timecode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:02}:{:02}:{:02}{}{:02}",
+            self.hours,
+            self.minutes,
+            self.seconds,
+            if self.drop_frame { ';' } else { ':' },
+            self.frames
+        )
+    }
+}
+
+/// The payload type of an ATC packet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AtcType {
+    Ltc,
+    /// VITC from the first field.
+    Vitc1,
+    /// VITC from the second field.
+    Vitc2,
+    Other(u8),
+}
+
+impl From<u8> for AtcType {
+    fn from(dbb1: u8) -> Self {
+        match dbb1 {
+            0x00 => Self::Ltc,
+            0x01 => Self::Vitc1,
+            0x02 => Self::Vitc2,
+            _ => Self::Other(dbb1),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_bcd() {
+        let tc = Timecode::from_bcd(0x29, 0x59, 0x30, 0x10).unwrap();
+        assert_eq!(
+            (tc.hours, tc.minutes, tc.seconds, tc.frames),
+            (10, 30, 59, 29)
+        );
+        assert!(!tc.drop_frame);
+        assert_eq!(tc.to_string(), "10:30:59:29");
+
+        let tc = Timecode::from_bcd(0x40 | 0x02, 0x80, 0x01, 0x23).unwrap();
+        assert!(tc.drop_frame);
+        assert!(tc.field);
+        assert_eq!(tc.to_string(), "23:01:00;02");
+
+        assert_eq!(Timecode::from_bcd(0x0a, 0, 0, 0), None);
+        assert_eq!(Timecode::from_bcd(0, 0x60, 0, 0), None);
+        assert_eq!(Timecode::from_bcd(0, 0, 0, 0x24), None);
+    }
+
+    #[test]
+    fn test_from_atc() {
+        // 01:02:03;04 VITC1 with user bits 0x87654321.
+        let nibbles = [4, 1, 4, 2, 3, 3, 0, 4, 2, 5, 0, 6, 1, 7, 0, 8];
+        let mut data: Vec<u8> = nibbles.iter().map(|n| n << 4).collect();
+        data[0] |= 0x08;
+        let (atc_type, tc) = Timecode::from_atc(&data).unwrap();
+        assert_eq!(atc_type, AtcType::Vitc1);
+        assert_eq!(tc.to_string(), "01:02:03;04");
+        assert_eq!(tc.user_bits, Some(0x87654321));
+
+        assert_eq!(Timecode::from_atc(&data[..15]), None);
+    }
+}
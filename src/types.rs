@@ -1,11 +1,13 @@
-use super::sys;
+use super::{sys, AncPacket, FourCC, HdrMetadata, Result, Timecode};
 use bitflags::bitflags;
-use std::{ffi::CStr, os::raw::c_char, time::Duration};
+use snafu::prelude::*;
+use std::{ffi::CStr, ops::RangeInclusive, os::raw::c_char, time::Duration};
 
 fn bytes_to_cstr(bytes: &[c_char]) -> &CStr {
     unsafe { CStr::from_ptr(bytes.as_ptr()) }
 }
 
+#[derive(Clone)]
 pub struct ChannelInfo {
     inner: sys::MWCAP_CHANNEL_INFO,
 }
@@ -35,6 +37,14 @@ impl ChannelInfo {
         bytes_to_cstr(&self.inner.szFamilyName)
     }
 
+    pub fn family(&self) -> Family {
+        self.inner.wFamilyID.into()
+    }
+
+    pub fn product_id(&self) -> ProductId {
+        self.inner.wProductID.into()
+    }
+
     pub fn firmware_version(&self) -> (u16, u16) {
         let v = self.inner.dwFirmwareVersion;
         ((v >> 16) as _, v as _)
@@ -67,6 +77,135 @@ impl From<sys::MWCAP_CHANNEL_INFO> for ChannelInfo {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Family {
+    Pro,
+    Eco,
+    Usb,
+    Other(u16),
+}
+
+impl From<u16> for Family {
+    fn from(id: u16) -> Self {
+        match id as sys::MW_FAMILY_ID {
+            sys::_MW_FAMILY_ID_MW_FAMILY_ID_PRO_CAPTURE => Self::Pro,
+            // The SDK still refers to the Eco family by its old "value" name.
+            sys::_MW_FAMILY_ID_MW_FAMILY_ID_VALUE_CAPTURE => Self::Eco,
+            sys::_MW_FAMILY_ID_MW_FAMILY_ID_USB_CAPTURE => Self::Usb,
+            _ => Self::Other(id),
+        }
+    }
+}
+
+/// The known Magewell products. See `MWCAP_PRODUCT_ID` in
+/// vendor/Magewell_Capture_SDK_Linux_3.3.1.1313/Include/LibMWCapture/MWCaptureExtension.h.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProductId {
+    ProCaptureAio,
+    ProCaptureDvi,
+    ProCaptureHdmi,
+    ProCaptureSdi,
+    ProCaptureDualSdi,
+    ProCaptureDualDvi,
+    ProCaptureDualHdmi,
+    ProCaptureQuadSdi,
+    ProCaptureQuadHdmi,
+    ProCaptureMiniHdmi,
+    ProCaptureHdmi4K,
+    ProCaptureMiniSdi,
+    ProCaptureAio4KPlus,
+    ProCaptureHdmi4KPlus,
+    ProCaptureDvi4K,
+    ProCaptureAio4K,
+    ProCaptureSdi4KPlus,
+    ProCaptureDualHdmi4KPlus,
+    ProCaptureDualSdi4KPlus,
+    EcoCaptureOctaSdi,
+    EcoCaptureDualHdmiM2,
+    EcoCaptureHdmi4KM2,
+    EcoCaptureDualSdiM2,
+    EcoCaptureQuadSdiM2,
+    UsbCaptureHdmiPlus,
+    UsbCaptureSdiPlus,
+    UsbCaptureHdmi,
+    UsbCaptureSdi,
+    UsbCaptureDvi,
+    UsbCaptureHdmi4KPlus,
+    UsbCaptureSdi4KPlus,
+    UsbCaptureAio,
+    UsbCaptureAio4K,
+    Other(u16),
+}
+
+impl From<u16> for ProductId {
+    fn from(id: u16) -> Self {
+        match id as sys::MWCAP_PRODUCT_ID {
+            sys::_MWCAP_PRODUCT_ID_MWCAP_PRODUCT_ID_PRO_CAPTURE_AIO => Self::ProCaptureAio,
+            sys::_MWCAP_PRODUCT_ID_MWCAP_PRODUCT_ID_PRO_CAPTURE_DVI => Self::ProCaptureDvi,
+            sys::_MWCAP_PRODUCT_ID_MWCAP_PRODUCT_ID_PRO_CAPTURE_HDMI => Self::ProCaptureHdmi,
+            sys::_MWCAP_PRODUCT_ID_MWCAP_PRODUCT_ID_PRO_CAPTURE_SDI => Self::ProCaptureSdi,
+            sys::_MWCAP_PRODUCT_ID_MWCAP_PRODUCT_ID_PRO_CAPTURE_DUAL_SDI => Self::ProCaptureDualSdi,
+            sys::_MWCAP_PRODUCT_ID_MWCAP_PRODUCT_ID_PRO_CAPTURE_DUAL_DVI => Self::ProCaptureDualDvi,
+            sys::_MWCAP_PRODUCT_ID_MWCAP_PRODUCT_ID_PRO_CAPTURE_DUAL_HDMI => {
+                Self::ProCaptureDualHdmi
+            }
+            sys::_MWCAP_PRODUCT_ID_MWCAP_PRODUCT_ID_PRO_CAPTURE_QUAD_SDI => Self::ProCaptureQuadSdi,
+            sys::_MWCAP_PRODUCT_ID_MWCAP_PRODUCT_ID_PRO_CAPTURE_QUAD_HDMI => {
+                Self::ProCaptureQuadHdmi
+            }
+            sys::_MWCAP_PRODUCT_ID_MWCAP_PRODUCT_ID_PRO_CAPTURE_MINI_HDMI => {
+                Self::ProCaptureMiniHdmi
+            }
+            sys::_MWCAP_PRODUCT_ID_MWCAP_PRODUCT_ID_PRO_CAPTURE_HDMI_4K => Self::ProCaptureHdmi4K,
+            sys::_MWCAP_PRODUCT_ID_MWCAP_PRODUCT_ID_PRO_CAPTURE_MINI_SDI => Self::ProCaptureMiniSdi,
+            sys::_MWCAP_PRODUCT_ID_MWCAP_PRODUCT_ID_PRO_CAPTURE_AIO_4K_PLUS => {
+                Self::ProCaptureAio4KPlus
+            }
+            sys::_MWCAP_PRODUCT_ID_MWCAP_PRODUCT_ID_PRO_CAPTURE_HDMI_4K_PLUS => {
+                Self::ProCaptureHdmi4KPlus
+            }
+            sys::_MWCAP_PRODUCT_ID_MWCAP_PRODUCT_ID_PRO_CAPTURE_DVI_4K => Self::ProCaptureDvi4K,
+            sys::_MWCAP_PRODUCT_ID_MWCAP_PRODUCT_ID_PRO_CAPTURE_AIO_4K => Self::ProCaptureAio4K,
+            sys::_MWCAP_PRODUCT_ID_MWCAP_PRODUCT_ID_PRO_CAPTURE_SDI_4K_PLUS => {
+                Self::ProCaptureSdi4KPlus
+            }
+            sys::_MWCAP_PRODUCT_ID_MWCAP_PRODUCT_ID_PRO_CAPTURE_DUAL_HDMI_4K_PLUS => {
+                Self::ProCaptureDualHdmi4KPlus
+            }
+            sys::_MWCAP_PRODUCT_ID_MWCAP_PRODUCT_ID_PRO_CAPTURE_DUAL_SDI_4K_PLUS => {
+                Self::ProCaptureDualSdi4KPlus
+            }
+            sys::_MWCAP_PRODUCT_ID_MWCAP_PRODUCT_ID_ECO_CAPTURE_OCTA_SDI => Self::EcoCaptureOctaSdi,
+            sys::_MWCAP_PRODUCT_ID_MWCAP_PRODUCT_ID_ECO_CAPTURE_DUAL_HDMI_M2 => {
+                Self::EcoCaptureDualHdmiM2
+            }
+            sys::_MWCAP_PRODUCT_ID_MWCAP_PRODUCT_ID_ECO_CAPTURE_HDMI_4K_M2 => {
+                Self::EcoCaptureHdmi4KM2
+            }
+            sys::_MWCAP_PRODUCT_ID_MWCAP_PRODUCT_ID_ECO_CAPTURE_DUAL_SDI_M2 => {
+                Self::EcoCaptureDualSdiM2
+            }
+            sys::_MWCAP_PRODUCT_ID_MWCAP_PRODUCT_ID_ECO_CAPTURE_QUAD_SDI_M2 => {
+                Self::EcoCaptureQuadSdiM2
+            }
+            sys::_MWCAP_PRODUCT_ID_MWCAP_PRODUCT_ID_USB_CAPTURE_HDMI_PLUS => {
+                Self::UsbCaptureHdmiPlus
+            }
+            sys::_MWCAP_PRODUCT_ID_MWCAP_PRODUCT_ID_USB_CAPTURE_SDI_PLUS => Self::UsbCaptureSdiPlus,
+            sys::_MWCAP_PRODUCT_ID_MWCAP_PRODUCT_ID_USB_CAPTURE_HDMI => Self::UsbCaptureHdmi,
+            sys::_MWCAP_PRODUCT_ID_MWCAP_PRODUCT_ID_USB_CAPTURE_SDI => Self::UsbCaptureSdi,
+            sys::_MWCAP_PRODUCT_ID_MWCAP_PRODUCT_ID_USB_CAPTURE_DVI => Self::UsbCaptureDvi,
+            sys::_MWCAP_PRODUCT_ID_MWCAP_PRODUCT_ID_USB_CAPTURE_HDMI_4K => {
+                Self::UsbCaptureHdmi4KPlus
+            }
+            sys::_MWCAP_PRODUCT_ID_MWCAP_PRODUCT_ID_USB_CAPTURE_SDI_4K => Self::UsbCaptureSdi4KPlus,
+            sys::_MWCAP_PRODUCT_ID_MWCAP_PRODUCT_ID_USB_CAPTURE_AIO => Self::UsbCaptureAio,
+            sys::_MWCAP_PRODUCT_ID_MWCAP_PRODUCT_ID_USB_CAPTURE_AIO_4K => Self::UsbCaptureAio4K,
+            _ => Self::Other(id),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum VideoSignalState {
     None,
@@ -88,6 +227,112 @@ impl From<sys::MWCAP_VIDEO_SIGNAL_STATE> for VideoSignalState {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VideoInputType {
+    None,
+    Hdmi,
+    Vga,
+    Sdi,
+    Component,
+    Cvbs,
+    Yc,
+    Other(u32),
+}
+
+impl From<u32> for VideoInputType {
+    fn from(t: u32) -> Self {
+        match t {
+            sys::_MWCAP_VIDEO_INPUT_TYPE_MWCAP_VIDEO_INPUT_TYPE_NONE => Self::None,
+            sys::_MWCAP_VIDEO_INPUT_TYPE_MWCAP_VIDEO_INPUT_TYPE_HDMI => Self::Hdmi,
+            sys::_MWCAP_VIDEO_INPUT_TYPE_MWCAP_VIDEO_INPUT_TYPE_VGA => Self::Vga,
+            sys::_MWCAP_VIDEO_INPUT_TYPE_MWCAP_VIDEO_INPUT_TYPE_SDI => Self::Sdi,
+            sys::_MWCAP_VIDEO_INPUT_TYPE_MWCAP_VIDEO_INPUT_TYPE_COMPONENT => Self::Component,
+            sys::_MWCAP_VIDEO_INPUT_TYPE_MWCAP_VIDEO_INPUT_TYPE_CVBS => Self::Cvbs,
+            sys::_MWCAP_VIDEO_INPUT_TYPE_MWCAP_VIDEO_INPUT_TYPE_YC => Self::Yc,
+            _ => Self::Other(t),
+        }
+    }
+}
+
+impl From<VideoInputType> for u32 {
+    fn from(t: VideoInputType) -> Self {
+        match t {
+            VideoInputType::None => sys::_MWCAP_VIDEO_INPUT_TYPE_MWCAP_VIDEO_INPUT_TYPE_NONE,
+            VideoInputType::Hdmi => sys::_MWCAP_VIDEO_INPUT_TYPE_MWCAP_VIDEO_INPUT_TYPE_HDMI,
+            VideoInputType::Vga => sys::_MWCAP_VIDEO_INPUT_TYPE_MWCAP_VIDEO_INPUT_TYPE_VGA,
+            VideoInputType::Sdi => sys::_MWCAP_VIDEO_INPUT_TYPE_MWCAP_VIDEO_INPUT_TYPE_SDI,
+            VideoInputType::Component => {
+                sys::_MWCAP_VIDEO_INPUT_TYPE_MWCAP_VIDEO_INPUT_TYPE_COMPONENT
+            }
+            VideoInputType::Cvbs => sys::_MWCAP_VIDEO_INPUT_TYPE_MWCAP_VIDEO_INPUT_TYPE_CVBS,
+            VideoInputType::Yc => sys::_MWCAP_VIDEO_INPUT_TYPE_MWCAP_VIDEO_INPUT_TYPE_YC,
+            VideoInputType::Other(t) => t,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AudioInputType {
+    None,
+    Hdmi,
+    Sdi,
+    LineIn,
+    MicIn,
+    Other(u32),
+}
+
+impl From<u32> for AudioInputType {
+    fn from(t: u32) -> Self {
+        match t {
+            sys::_MWCAP_AUDIO_INPUT_TYPE_MWCAP_AUDIO_INPUT_TYPE_NONE => Self::None,
+            sys::_MWCAP_AUDIO_INPUT_TYPE_MWCAP_AUDIO_INPUT_TYPE_HDMI => Self::Hdmi,
+            sys::_MWCAP_AUDIO_INPUT_TYPE_MWCAP_AUDIO_INPUT_TYPE_SDI => Self::Sdi,
+            sys::_MWCAP_AUDIO_INPUT_TYPE_MWCAP_AUDIO_INPUT_TYPE_LINE_IN => Self::LineIn,
+            sys::_MWCAP_AUDIO_INPUT_TYPE_MWCAP_AUDIO_INPUT_TYPE_MIC_IN => Self::MicIn,
+            _ => Self::Other(t),
+        }
+    }
+}
+
+impl From<AudioInputType> for u32 {
+    fn from(t: AudioInputType) -> Self {
+        match t {
+            AudioInputType::None => sys::_MWCAP_AUDIO_INPUT_TYPE_MWCAP_AUDIO_INPUT_TYPE_NONE,
+            AudioInputType::Hdmi => sys::_MWCAP_AUDIO_INPUT_TYPE_MWCAP_AUDIO_INPUT_TYPE_HDMI,
+            AudioInputType::Sdi => sys::_MWCAP_AUDIO_INPUT_TYPE_MWCAP_AUDIO_INPUT_TYPE_SDI,
+            AudioInputType::LineIn => sys::_MWCAP_AUDIO_INPUT_TYPE_MWCAP_AUDIO_INPUT_TYPE_LINE_IN,
+            AudioInputType::MicIn => sys::_MWCAP_AUDIO_INPUT_TYPE_MWCAP_AUDIO_INPUT_TYPE_MIC_IN,
+            AudioInputType::Other(t) => t,
+        }
+    }
+}
+
+/// An input source, which is a combination of an input type and an index for devices that have
+/// multiple inputs of the same type. This corresponds to the SDK's `INPUT_SOURCE` macro.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InputSource<T> {
+    pub input_type: T,
+    pub index: u8,
+}
+
+pub type VideoInputSource = InputSource<VideoInputType>;
+pub type AudioInputSource = InputSource<AudioInputType>;
+
+impl<T: From<u32>> From<u32> for InputSource<T> {
+    fn from(source: u32) -> Self {
+        Self {
+            input_type: (source >> 8).into(),
+            index: source as u8,
+        }
+    }
+}
+
+impl<T: Into<u32>> From<InputSource<T>> for u32 {
+    fn from(source: InputSource<T>) -> Self {
+        source.input_type.into() << 8 | source.index as u32
+    }
+}
+
 pub struct AudioSignalStatus {
     inner: sys::MWCAP_AUDIO_SIGNAL_STATUS,
 }
@@ -116,6 +361,234 @@ impl From<sys::MWCAP_AUDIO_SIGNAL_STATUS> for AudioSignalStatus {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VideoFrameType {
+    Normal2D,
+    TopAndBottomFull3D,
+    TopAndBottomHalf3D,
+    SideBySideFull3D,
+    SideBySideHalf3D,
+    Other(u32),
+}
+
+impl From<sys::MWCAP_VIDEO_FRAME_TYPE> for VideoFrameType {
+    fn from(t: sys::MWCAP_VIDEO_FRAME_TYPE) -> Self {
+        match t {
+            sys::_MWCAP_VIDEO_FRAME_TYPE_MWCAP_VIDEO_FRAME_2D => Self::Normal2D,
+            sys::_MWCAP_VIDEO_FRAME_TYPE_MWCAP_VIDEO_FRAME_3D_TOP_AND_BOTTOM_FULL => {
+                Self::TopAndBottomFull3D
+            }
+            sys::_MWCAP_VIDEO_FRAME_TYPE_MWCAP_VIDEO_FRAME_3D_TOP_AND_BOTTOM_HALF => {
+                Self::TopAndBottomHalf3D
+            }
+            sys::_MWCAP_VIDEO_FRAME_TYPE_MWCAP_VIDEO_FRAME_3D_SIDE_BY_SIDE_FULL => {
+                Self::SideBySideFull3D
+            }
+            sys::_MWCAP_VIDEO_FRAME_TYPE_MWCAP_VIDEO_FRAME_3D_SIDE_BY_SIDE_HALF => {
+                Self::SideBySideHalf3D
+            }
+            _ => Self::Other(t),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VideoColorFormat {
+    #[default]
+    Unknown,
+    Rgb,
+    Yuv601,
+    Yuv709,
+    Yuv2020,
+    /// BT.2020 with constant luminance.
+    Yuv2020C,
+    Other(u32),
+}
+
+impl From<sys::MWCAP_VIDEO_COLOR_FORMAT> for VideoColorFormat {
+    fn from(f: sys::MWCAP_VIDEO_COLOR_FORMAT) -> Self {
+        match f {
+            sys::_MWCAP_VIDEO_COLOR_FORMAT_MWCAP_VIDEO_COLOR_FORMAT_UNKNOWN => Self::Unknown,
+            sys::_MWCAP_VIDEO_COLOR_FORMAT_MWCAP_VIDEO_COLOR_FORMAT_RGB => Self::Rgb,
+            sys::_MWCAP_VIDEO_COLOR_FORMAT_MWCAP_VIDEO_COLOR_FORMAT_YUV601 => Self::Yuv601,
+            sys::_MWCAP_VIDEO_COLOR_FORMAT_MWCAP_VIDEO_COLOR_FORMAT_YUV709 => Self::Yuv709,
+            sys::_MWCAP_VIDEO_COLOR_FORMAT_MWCAP_VIDEO_COLOR_FORMAT_YUV2020 => Self::Yuv2020,
+            sys::_MWCAP_VIDEO_COLOR_FORMAT_MWCAP_VIDEO_COLOR_FORMAT_YUV2020C => Self::Yuv2020C,
+            _ => Self::Other(f),
+        }
+    }
+}
+
+impl From<VideoColorFormat> for u32 {
+    fn from(f: VideoColorFormat) -> Self {
+        match f {
+            VideoColorFormat::Unknown => {
+                sys::_MWCAP_VIDEO_COLOR_FORMAT_MWCAP_VIDEO_COLOR_FORMAT_UNKNOWN
+            }
+            VideoColorFormat::Rgb => sys::_MWCAP_VIDEO_COLOR_FORMAT_MWCAP_VIDEO_COLOR_FORMAT_RGB,
+            VideoColorFormat::Yuv601 => {
+                sys::_MWCAP_VIDEO_COLOR_FORMAT_MWCAP_VIDEO_COLOR_FORMAT_YUV601
+            }
+            VideoColorFormat::Yuv709 => {
+                sys::_MWCAP_VIDEO_COLOR_FORMAT_MWCAP_VIDEO_COLOR_FORMAT_YUV709
+            }
+            VideoColorFormat::Yuv2020 => {
+                sys::_MWCAP_VIDEO_COLOR_FORMAT_MWCAP_VIDEO_COLOR_FORMAT_YUV2020
+            }
+            VideoColorFormat::Yuv2020C => {
+                sys::_MWCAP_VIDEO_COLOR_FORMAT_MWCAP_VIDEO_COLOR_FORMAT_YUV2020C
+            }
+            VideoColorFormat::Other(f) => f,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QuantizationRange {
+    #[default]
+    Unknown,
+    Full,
+    Limited,
+    Other(u32),
+}
+
+impl From<sys::MWCAP_VIDEO_QUANTIZATION_RANGE> for QuantizationRange {
+    fn from(r: sys::MWCAP_VIDEO_QUANTIZATION_RANGE) -> Self {
+        match r {
+            sys::_MWCAP_VIDEO_QUANTIZATION_RANGE_MWCAP_VIDEO_QUANTIZATION_UNKNOWN => Self::Unknown,
+            sys::_MWCAP_VIDEO_QUANTIZATION_RANGE_MWCAP_VIDEO_QUANTIZATION_FULL => Self::Full,
+            sys::_MWCAP_VIDEO_QUANTIZATION_RANGE_MWCAP_VIDEO_QUANTIZATION_LIMITED => Self::Limited,
+            _ => Self::Other(r),
+        }
+    }
+}
+
+impl From<QuantizationRange> for u32 {
+    fn from(r: QuantizationRange) -> Self {
+        match r {
+            QuantizationRange::Unknown => {
+                sys::_MWCAP_VIDEO_QUANTIZATION_RANGE_MWCAP_VIDEO_QUANTIZATION_UNKNOWN
+            }
+            QuantizationRange::Full => {
+                sys::_MWCAP_VIDEO_QUANTIZATION_RANGE_MWCAP_VIDEO_QUANTIZATION_FULL
+            }
+            QuantizationRange::Limited => {
+                sys::_MWCAP_VIDEO_QUANTIZATION_RANGE_MWCAP_VIDEO_QUANTIZATION_LIMITED
+            }
+            QuantizationRange::Other(r) => r,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SaturationRange {
+    #[default]
+    Unknown,
+    Full,
+    Limited,
+    ExtendedGamut,
+    Other(u32),
+}
+
+impl From<sys::MWCAP_VIDEO_SATURATION_RANGE> for SaturationRange {
+    fn from(r: sys::MWCAP_VIDEO_SATURATION_RANGE) -> Self {
+        match r {
+            sys::_MWCAP_VIDEO_SATURATION_RANGE_MWCAP_VIDEO_SATURATION_UNKNOWN => Self::Unknown,
+            sys::_MWCAP_VIDEO_SATURATION_RANGE_MWCAP_VIDEO_SATURATION_FULL => Self::Full,
+            sys::_MWCAP_VIDEO_SATURATION_RANGE_MWCAP_VIDEO_SATURATION_LIMITED => Self::Limited,
+            sys::_MWCAP_VIDEO_SATURATION_RANGE_MWCAP_VIDEO_SATURATION_EXTENDED_GAMUT => {
+                Self::ExtendedGamut
+            }
+            _ => Self::Other(r),
+        }
+    }
+}
+
+impl From<SaturationRange> for u32 {
+    fn from(r: SaturationRange) -> Self {
+        match r {
+            SaturationRange::Unknown => {
+                sys::_MWCAP_VIDEO_SATURATION_RANGE_MWCAP_VIDEO_SATURATION_UNKNOWN
+            }
+            SaturationRange::Full => sys::_MWCAP_VIDEO_SATURATION_RANGE_MWCAP_VIDEO_SATURATION_FULL,
+            SaturationRange::Limited => {
+                sys::_MWCAP_VIDEO_SATURATION_RANGE_MWCAP_VIDEO_SATURATION_LIMITED
+            }
+            SaturationRange::ExtendedGamut => {
+                sys::_MWCAP_VIDEO_SATURATION_RANGE_MWCAP_VIDEO_SATURATION_EXTENDED_GAMUT
+            }
+            SaturationRange::Other(r) => r,
+        }
+    }
+}
+
+/// A rational number, e.g. for representing frame rates such as 60000/1001 exactly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rational {
+    pub num: u32,
+    pub den: u32,
+}
+
+impl Rational {
+    pub fn new(num: u32, den: u32) -> Self {
+        fn gcd(a: u32, b: u32) -> u32 {
+            if b == 0 {
+                a
+            } else {
+                gcd(b, a % b)
+            }
+        }
+        let d = gcd(num, den).max(1);
+        Self {
+            num: num / d,
+            den: den / d,
+        }
+    }
+
+    pub fn as_f64(&self) -> f64 {
+        self.num as f64 / self.den as f64
+    }
+
+    /// Returns the frame rate corresponding to a frame duration in the SDK's 100ns units.
+    ///
+    /// The SDK's durations are rounded to the nearest 100ns, so this snaps to the nearest integer
+    /// or NTSC-style (x/1001) rate when the duration is within rounding error of one. Otherwise
+    /// the exact rate is returned.
+    pub fn from_frame_duration_100ns(duration: u32) -> Option<Self> {
+        const TICKS_PER_SECOND: u64 = 10_000_000;
+
+        if duration == 0 {
+            return None;
+        }
+        let duration = duration as u64;
+
+        // Allow the duration to be off by up to one tick, so that both rounded and truncated
+        // durations are accepted.
+        let is_close = |num: u64, den: u64| {
+            num > 0 && (TICKS_PER_SECOND * den).abs_diff(duration * num) <= num
+        };
+
+        let integer_rate = (TICKS_PER_SECOND + duration / 2) / duration;
+        if is_close(integer_rate, 1) {
+            return Some(Self::new(integer_rate as _, 1));
+        }
+
+        let ntsc_rate = ((TICKS_PER_SECOND * 1001 / duration) + 500) / 1000 * 1000;
+        if is_close(ntsc_rate, 1001) {
+            return Some(Self::new(ntsc_rate as _, 1001));
+        }
+
+        Some(Self::new(TICKS_PER_SECOND as _, duration as _))
+    }
+}
+
+impl std::fmt::Display for Rational {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.num, self.den)
+    }
+}
+
+#[derive(Clone)]
 pub struct VideoSignalStatus {
     inner: sys::MWCAP_VIDEO_SIGNAL_STATUS,
 }
@@ -125,6 +598,16 @@ impl VideoSignalStatus {
         self.inner.state.into()
     }
 
+    /// Returns the horizontal start position of the active image.
+    pub fn x(&self) -> i32 {
+        self.inner.x
+    }
+
+    /// Returns the vertical start position of the active image.
+    pub fn y(&self) -> i32 {
+        self.inner.y
+    }
+
     pub fn image_width(&self) -> u16 {
         self.inner.cx as _
     }
@@ -133,9 +616,56 @@ impl VideoSignalStatus {
         self.inner.cy as _
     }
 
+    /// Returns the total width, including blanking.
+    pub fn total_width(&self) -> u16 {
+        self.inner.cxTotal as _
+    }
+
+    /// Returns the total height, including blanking.
+    pub fn total_height(&self) -> u16 {
+        self.inner.cyTotal as _
+    }
+
+    pub fn is_interlaced(&self) -> bool {
+        self.inner.bInterlaced != 0
+    }
+
+    /// Returns true for progressive segmented frame (PsF) signals.
+    pub fn is_segmented_frame(&self) -> bool {
+        self.inner.bSegmentedFrame != 0
+    }
+
     pub fn frame_duration(&self) -> Duration {
         Duration::from_nanos(100 * self.inner.dwFrameDuration as u64)
     }
+
+    /// Returns the frame rate as an exact rational, e.g. 60000/1001 for 59.94 fps. For interlaced
+    /// signals, this is the frame rate rather than the field rate. Returns `None` if there's no
+    /// signal.
+    pub fn frame_rate(&self) -> Option<Rational> {
+        Rational::from_frame_duration_100ns(self.inner.dwFrameDuration)
+    }
+
+    /// Returns the aspect ratio as (width, height), e.g. (16, 9).
+    pub fn aspect_ratio(&self) -> (u32, u32) {
+        (self.inner.nAspectX as _, self.inner.nAspectY as _)
+    }
+
+    pub fn frame_type(&self) -> VideoFrameType {
+        self.inner.frameType.into()
+    }
+
+    pub fn color_format(&self) -> VideoColorFormat {
+        self.inner.colorFormat.into()
+    }
+
+    pub fn quantization_range(&self) -> QuantizationRange {
+        self.inner.quantRange.into()
+    }
+
+    pub fn saturation_range(&self) -> SaturationRange {
+        self.inner.satRange.into()
+    }
 }
 
 impl From<sys::MWCAP_VIDEO_SIGNAL_STATUS> for VideoSignalStatus {
@@ -144,6 +674,212 @@ impl From<sys::MWCAP_VIDEO_SIGNAL_STATUS> for VideoSignalStatus {
     }
 }
 
+bitflags! {
+    /// Video capabilities. These are only reported by USB devices and are always empty otherwise.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct VideoCapsFlags: u32 {
+        const HDMI_LOOP_THROUGH = sys::MWCAP_USB_VIDEO_CAP_HDMI_LOOP_THROUGH;
+        const SDI_LOOP_THROUGH = sys::MWCAP_USB_VIDEO_CAP_SDI_LOOP_THROUGH;
+        const PLANAR_FORMAT = sys::MWCAP_USB_VIDEO_CAP_PLANAR_FORMAT;
+    }
+}
+
+pub struct VideoCaps {
+    inner: sys::MWCAP_VIDEO_CAPS,
+}
+
+impl VideoCaps {
+    pub fn flags(&self) -> VideoCapsFlags {
+        VideoCapsFlags::from_bits_retain(self.inner.dwCaps)
+    }
+
+    pub fn max_input_width(&self) -> u16 {
+        self.inner.wMaxInputWidth
+    }
+
+    pub fn max_input_height(&self) -> u16 {
+        self.inner.wMaxInputHeight
+    }
+
+    pub fn max_output_width(&self) -> u16 {
+        self.inner.wMaxOutputWidth
+    }
+
+    pub fn max_output_height(&self) -> u16 {
+        self.inner.wMaxOutputHeight
+    }
+}
+
+impl From<sys::MWCAP_VIDEO_CAPS> for VideoCaps {
+    fn from(caps: sys::MWCAP_VIDEO_CAPS) -> Self {
+        VideoCaps { inner: caps }
+    }
+}
+
+bitflags! {
+    /// Audio capabilities. These are only reported by USB devices and are always empty otherwise.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct AudioCaps: u32 {
+        const MICROPHONE = 1 << sys::_MWCAP_USB_AUDIO_NODE_MWCAP_USB_AUDIO_MICROPHONE;
+        const HEADPHONE = 1 << sys::_MWCAP_USB_AUDIO_NODE_MWCAP_USB_AUDIO_HEADPHONE;
+        const LINE_IN = 1 << sys::_MWCAP_USB_AUDIO_NODE_MWCAP_USB_AUDIO_LINE_IN;
+        const LINE_OUT = 1 << sys::_MWCAP_USB_AUDIO_NODE_MWCAP_USB_AUDIO_LINE_OUT;
+        const EMBEDDED_CAPTURE = 1 << sys::_MWCAP_USB_AUDIO_NODE_MWCAP_USB_AUDIO_EMBEDDED_CAPTURE;
+        const EMBEDDED_PLAYBACK = 1 << sys::_MWCAP_USB_AUDIO_NODE_MWCAP_USB_AUDIO_EMBEDDED_PLAYBACK;
+        const USB_CAPTURE = 1 << sys::_MWCAP_USB_AUDIO_NODE_MWCAP_USB_AUDIO_USB_CAPTURE;
+        const USB_PLAYBACK = 1 << sys::_MWCAP_USB_AUDIO_NODE_MWCAP_USB_AUDIO_USB_PLAYBACK;
+    }
+}
+
+/// The capture resolutions supported by a device. Depending on the device, this is either a
+/// continuous range or a discrete list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SupportedResolutions {
+    Range {
+        min_width: u16,
+        min_height: u16,
+        max_width: u16,
+        max_height: u16,
+        width_step: u16,
+        height_step: u16,
+    },
+    List(Vec<(u16, u16)>),
+}
+
+impl SupportedResolutions {
+    pub fn contains(&self, width: u16, height: u16) -> bool {
+        match self {
+            Self::Range {
+                min_width,
+                min_height,
+                max_width,
+                max_height,
+                width_step,
+                height_step,
+            } => {
+                (*min_width..=*max_width).contains(&width)
+                    && (*min_height..=*max_height).contains(&height)
+                    && (width - min_width) % (*width_step).max(1) == 0
+                    && (height - min_height) % (*height_step).max(1) == 0
+            }
+            Self::List(list) => list.contains(&(width, height)),
+        }
+    }
+}
+
+/// How a video image's colors are encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Colorimetry {
+    pub color_format: VideoColorFormat,
+    pub quantization_range: QuantizationRange,
+    pub saturation_range: SaturationRange,
+}
+
+/// Image controls applied to captured video.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VideoProcSettings {
+    pub brightness: i16,
+    pub contrast: i16,
+    pub hue: i16,
+    pub saturation: i16,
+}
+
+impl Default for VideoProcSettings {
+    fn default() -> Self {
+        Self {
+            brightness: 0,
+            contrast: 100,
+            hue: 0,
+            saturation: 100,
+        }
+    }
+}
+
+impl VideoProcSettings {
+    // These are the ranges documented for `MWCAP_VIDEO_ECO_CAPTURE_SETTINGS`. The SDK can report
+    // ranges per device via `MWGetVideoProcParamRange`, but that function (like
+    // `MWGetVideoProcParam` and `MWSetVideoProcParam`) requires a handle from
+    // `MWCreateVideoCapture`, which this crate doesn't use.
+    pub const BRIGHTNESS_RANGE: RangeInclusive<i16> = -100..=100;
+    pub const CONTRAST_RANGE: RangeInclusive<i16> = 50..=200;
+    pub const HUE_RANGE: RangeInclusive<i16> = -90..=90;
+    pub const SATURATION_RANGE: RangeInclusive<i16> = 0..=200;
+
+    /// Returns an error if any of the settings are out of range.
+    pub fn validate(&self) -> Result<()> {
+        validate_proc_value("brightness", self.brightness, Self::BRIGHTNESS_RANGE)?;
+        validate_proc_value("contrast", self.contrast, Self::CONTRAST_RANGE)?;
+        validate_proc_value("hue", self.hue, Self::HUE_RANGE)?;
+        validate_proc_value("saturation", self.saturation, Self::SATURATION_RANGE)?;
+        Ok(())
+    }
+}
+
+pub(crate) fn validate_proc_value(
+    name: &str,
+    value: i16,
+    range: RangeInclusive<i16>,
+) -> Result<()> {
+    if !range.contains(&value) {
+        whatever!(
+            "{} must be between {} and {}, got {}",
+            name,
+            range.start(),
+            range.end(),
+            value
+        );
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeinterlaceMode {
+    /// Interleaves both fields into a full frame.
+    Weave,
+    /// Blends both fields into a full frame.
+    Blend,
+    /// Uses only the top field, scaled to a full frame.
+    TopField,
+    /// Uses only the bottom field, scaled to a full frame.
+    BottomField,
+    Other(u32),
+}
+
+impl From<sys::MWCAP_VIDEO_DEINTERLACE_MODE> for DeinterlaceMode {
+    fn from(mode: sys::MWCAP_VIDEO_DEINTERLACE_MODE) -> Self {
+        match mode {
+            sys::_MWCAP_VIDEO_DEINTERLACE_MODE_MWCAP_VIDEO_DEINTERLACE_WEAVE => Self::Weave,
+            sys::_MWCAP_VIDEO_DEINTERLACE_MODE_MWCAP_VIDEO_DEINTERLACE_BLEND => Self::Blend,
+            sys::_MWCAP_VIDEO_DEINTERLACE_MODE_MWCAP_VIDEO_DEINTERLACE_TOP_FIELD => Self::TopField,
+            sys::_MWCAP_VIDEO_DEINTERLACE_MODE_MWCAP_VIDEO_DEINTERLACE_BOTTOM_FIELD => {
+                Self::BottomField
+            }
+            _ => Self::Other(mode),
+        }
+    }
+}
+
+impl From<DeinterlaceMode> for u32 {
+    fn from(mode: DeinterlaceMode) -> Self {
+        match mode {
+            DeinterlaceMode::Weave => {
+                sys::_MWCAP_VIDEO_DEINTERLACE_MODE_MWCAP_VIDEO_DEINTERLACE_WEAVE
+            }
+            DeinterlaceMode::Blend => {
+                sys::_MWCAP_VIDEO_DEINTERLACE_MODE_MWCAP_VIDEO_DEINTERLACE_BLEND
+            }
+            DeinterlaceMode::TopField => {
+                sys::_MWCAP_VIDEO_DEINTERLACE_MODE_MWCAP_VIDEO_DEINTERLACE_TOP_FIELD
+            }
+            DeinterlaceMode::BottomField => {
+                sys::_MWCAP_VIDEO_DEINTERLACE_MODE_MWCAP_VIDEO_DEINTERLACE_BOTTOM_FIELD
+            }
+            DeinterlaceMode::Other(mode) => mode,
+        }
+    }
+}
+
 pub struct EcoVideoCaptureFrame {
     // XXX: `_buf` is referenced by `inner`!
     buf: Box<[u8]>,
@@ -170,6 +906,28 @@ impl EcoVideoCaptureFrame {
         &self.buf
     }
 
+    pub fn stride(&self) -> usize {
+        self.inner.cbStride as _
+    }
+
+    pub fn deinterlace_mode(&self) -> DeinterlaceMode {
+        self.inner.deinterlaceMode.into()
+    }
+
+    /// Sets how interlaced sources are converted to frames. Defaults to `DeinterlaceMode::Blend`.
+    pub fn set_deinterlace_mode(&mut self, mode: DeinterlaceMode) {
+        self.inner.deinterlaceMode = mode.into();
+    }
+
+    pub fn is_bottom_up(&self) -> bool {
+        self.inner.bBottomUp != 0
+    }
+
+    /// Sets whether the image is stored bottom row first. Defaults to false.
+    pub fn set_bottom_up(&mut self, bottom_up: bool) {
+        self.inner.bBottomUp = bottom_up as _;
+    }
+
     pub fn as_mut_ptr(&mut self) -> *mut sys::_MWCAP_VIDEO_ECO_CAPTURE_FRAME {
         &mut self.inner
     }
@@ -178,14 +936,64 @@ impl EcoVideoCaptureFrame {
 pub struct EcoVideoCaptureStatus {
     frame: EcoVideoCaptureFrame,
     status: sys::_MWCAP_VIDEO_ECO_CAPTURE_STATUS,
+    discontinuity: bool,
+    colorimetry: Colorimetry,
+    hdr_metadata: Option<HdrMetadata>,
+    anc_packets: Vec<AncPacket>,
 }
 
 impl EcoVideoCaptureStatus {
     pub(crate) fn new(
         frame: EcoVideoCaptureFrame,
         status: sys::_MWCAP_VIDEO_ECO_CAPTURE_STATUS,
+        discontinuity: bool,
+        colorimetry: Colorimetry,
+        hdr_metadata: Option<HdrMetadata>,
+        anc_packets: Vec<AncPacket>,
     ) -> Self {
-        Self { frame, status }
+        Self {
+            frame,
+            status,
+            discontinuity,
+            colorimetry,
+            hdr_metadata,
+            anc_packets,
+        }
+    }
+
+    /// Returns the colorimetry of the captured image, taking into account any input or capture
+    /// overrides.
+    pub fn colorimetry(&self) -> Colorimetry {
+        self.colorimetry
+    }
+
+    /// Returns the HDR metadata in effect when the frame was captured, if enabled via
+    /// `EcoChannel::enable_hdr_metadata`.
+    pub fn hdr_metadata(&self) -> Option<HdrMetadata> {
+        self.hdr_metadata
+    }
+
+    /// Returns the ANC packets received since the previous frame, if enabled via
+    /// `EcoChannel::enable_anc`. Packets are attributed to frames by when they're polled, so a
+    /// packet received just before a frame completes may be given to the next frame instead.
+    pub fn anc_packets(&self) -> &[AncPacket] {
+        &self.anc_packets
+    }
+
+    /// Returns the timecode of the frame from the most recent ATC packet, which requires ANC
+    /// capture to be enabled with a subscription to `AncId::ATC`.
+    pub fn timecode(&self) -> Option<Timecode> {
+        self.anc_packets
+            .iter()
+            .rev()
+            .find_map(|packet| packet.atc())
+            .map(|(_, timecode)| timecode)
+    }
+
+    /// Returns true if this is the first frame after the input format changed, e.g. because
+    /// "follow source" mode restarted the capture.
+    pub fn is_discontinuity(&self) -> bool {
+        self.discontinuity
     }
 
     pub fn frame(&self) -> &EcoVideoCaptureFrame {
@@ -199,6 +1007,521 @@ impl EcoVideoCaptureStatus {
     pub fn timestamp(&self) -> Duration {
         Duration::from_nanos(100 * self.status.llTimestamp as u64)
     }
+
+    /// Splits a frame of an interlaced source into its two fields, in temporal order. This
+    /// emulates field-based capture on Eco devices, which only capture full frames.
+    ///
+    /// The frame must have been captured with `DeinterlaceMode::Weave` in a packed `format`. The
+    /// first field gets the frame's timestamp and the second is offset by `field_duration`.
+    pub fn split_fields(
+        &self,
+        format: FourCC,
+        width: u16,
+        height: u16,
+        field_duration: Duration,
+        top_field_first: bool,
+    ) -> Result<[VideoField; 2]> {
+        if self.frame.deinterlace_mode() != DeinterlaceMode::Weave {
+            whatever!("fields can only be split from woven frames");
+        }
+        if !format.is_packed() {
+            whatever!("fields can only be split from packed formats");
+        }
+        let stride = self.frame.stride();
+        let [top, bottom] = split_rows(
+            self.frame.as_slice(),
+            stride,
+            format.min_stride(width, 1),
+            height as _,
+            self.frame.is_bottom_up(),
+        )?;
+        let (first, second) = match top_field_first {
+            true => ((Field::Top, top), (Field::Bottom, bottom)),
+            false => ((Field::Bottom, bottom), (Field::Top, top)),
+        };
+        Ok([
+            VideoField {
+                field: first.0,
+                timestamp: self.timestamp(),
+                stride,
+                data: first.1,
+            },
+            VideoField {
+                field: second.0,
+                timestamp: self.timestamp() + field_duration,
+                stride,
+                data: second.1,
+            },
+        ])
+    }
+}
+
+/// A single field of an interlaced frame. The rows are in the same order as the frame they came
+/// from, so a field split from a bottom-up frame is also bottom-up.
+pub struct VideoField {
+    pub field: Field,
+    pub timestamp: Duration,
+    pub stride: usize,
+    pub data: Vec<u8>,
+}
+
+/// Splits the rows of an image into its top (even rows) and bottom (odd rows) fields.
+fn split_rows(
+    data: &[u8],
+    stride: usize,
+    row_size: usize,
+    height: usize,
+    bottom_up: bool,
+) -> Result<[Vec<u8>; 2]> {
+    if stride == 0 || stride < row_size {
+        whatever!(
+            "stride {} is too small for rows of {} bytes",
+            stride,
+            row_size
+        );
+    }
+    if stride * height > data.len() {
+        whatever!("frame is too small for the given height");
+    }
+    let mut fields = [
+        Vec::with_capacity(stride * height.div_ceil(2)),
+        Vec::with_capacity(stride * (height / 2)),
+    ];
+    for (i, row) in data.chunks_exact(stride).take(height).enumerate() {
+        let image_row = if bottom_up { height - 1 - i } else { i };
+        fields[image_row % 2].extend_from_slice(row);
+    }
+    Ok(fields)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AspectRatioConvertMode {
+    /// Stretches the image to fill the output.
+    #[default]
+    Ignore,
+    /// Crops the image to fill the output while preserving its aspect ratio.
+    Cropping,
+    /// Pads the image with black borders to preserve its aspect ratio.
+    Padding,
+    Other(u32),
+}
+
+impl From<sys::MWCAP_VIDEO_ASPECT_RATIO_CONVERT_MODE> for AspectRatioConvertMode {
+    fn from(mode: sys::MWCAP_VIDEO_ASPECT_RATIO_CONVERT_MODE) -> Self {
+        match mode {
+            sys::_MWCAP_VIDEO_ASPECT_RATIO_CONVERT_MODE_MWCAP_VIDEO_ASPECT_RATIO_IGNORE => {
+                Self::Ignore
+            }
+            sys::_MWCAP_VIDEO_ASPECT_RATIO_CONVERT_MODE_MWCAP_VIDEO_ASPECT_RATIO_CROPPING => {
+                Self::Cropping
+            }
+            sys::_MWCAP_VIDEO_ASPECT_RATIO_CONVERT_MODE_MWCAP_VIDEO_ASPECT_RATIO_PADDING => {
+                Self::Padding
+            }
+            _ => Self::Other(mode),
+        }
+    }
+}
+
+impl From<AspectRatioConvertMode> for u32 {
+    fn from(mode: AspectRatioConvertMode) -> Self {
+        match mode {
+            AspectRatioConvertMode::Ignore => {
+                sys::_MWCAP_VIDEO_ASPECT_RATIO_CONVERT_MODE_MWCAP_VIDEO_ASPECT_RATIO_IGNORE
+            }
+            AspectRatioConvertMode::Cropping => {
+                sys::_MWCAP_VIDEO_ASPECT_RATIO_CONVERT_MODE_MWCAP_VIDEO_ASPECT_RATIO_CROPPING
+            }
+            AspectRatioConvertMode::Padding => {
+                sys::_MWCAP_VIDEO_ASPECT_RATIO_CONVERT_MODE_MWCAP_VIDEO_ASPECT_RATIO_PADDING
+            }
+            AspectRatioConvertMode::Other(mode) => mode,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Rect {
+    pub left: i32,
+    pub top: i32,
+    pub right: i32,
+    pub bottom: i32,
+}
+
+impl From<sys::RECT> for Rect {
+    fn from(rect: sys::RECT) -> Self {
+        Self {
+            left: rect.left,
+            top: rect.top,
+            right: rect.right,
+            bottom: rect.bottom,
+        }
+    }
+}
+
+impl From<Rect> for sys::RECT {
+    fn from(rect: Rect) -> Self {
+        Self {
+            left: rect.left,
+            top: rect.top,
+            right: rect.right,
+            bottom: rect.bottom,
+        }
+    }
+}
+
+bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub struct VideoProcessFlags: u32 {
+        const FLIP = sys::MWCAP_VIDEO_PROCESS_FLIP;
+        const MIRROR = sys::MWCAP_VIDEO_PROCESS_MIRROR;
+    }
+}
+
+/// Device-level processing applied before video is captured. This also determines what V4L2 and
+/// USB consumers of the device see.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VideoProcessSettings {
+    pub flags: VideoProcessFlags,
+    /// The area of the input to capture. An empty rectangle captures the entire input.
+    pub source_rect: Rect,
+    /// The aspect ratio as (width, height), or (0, 0) to use the input's.
+    pub aspect_ratio: (u32, u32),
+    pub low_latency: bool,
+    pub colorimetry: Colorimetry,
+    pub deinterlace_mode: DeinterlaceMode,
+    pub aspect_ratio_convert_mode: AspectRatioConvertMode,
+}
+
+impl From<sys::MWCAP_VIDEO_PROCESS_SETTINGS> for VideoProcessSettings {
+    fn from(settings: sys::MWCAP_VIDEO_PROCESS_SETTINGS) -> Self {
+        Self {
+            flags: VideoProcessFlags::from_bits_retain(settings.dwProcessSwitchs),
+            source_rect: settings.rectSource.into(),
+            aspect_ratio: (settings.nAspectX as _, settings.nAspectY as _),
+            low_latency: settings.bLowLatency != 0,
+            colorimetry: Colorimetry {
+                color_format: settings.colorFormat.into(),
+                quantization_range: settings.quantRange.into(),
+                saturation_range: settings.satRange.into(),
+            },
+            deinterlace_mode: settings.deinterlaceMode.into(),
+            aspect_ratio_convert_mode: settings.aspectRatioConvertMode.into(),
+        }
+    }
+}
+
+impl From<VideoProcessSettings> for sys::MWCAP_VIDEO_PROCESS_SETTINGS {
+    fn from(settings: VideoProcessSettings) -> Self {
+        Self {
+            dwProcessSwitchs: settings.flags.bits(),
+            rectSource: settings.source_rect.into(),
+            nAspectX: settings.aspect_ratio.0 as _,
+            nAspectY: settings.aspect_ratio.1 as _,
+            bLowLatency: settings.low_latency as _,
+            colorFormat: settings.colorimetry.color_format.into(),
+            quantRange: settings.colorimetry.quantization_range.into(),
+            satRange: settings.colorimetry.saturation_range.into(),
+            deinterlaceMode: settings.deinterlace_mode.into(),
+            aspectRatioConvertMode: settings.aspect_ratio_convert_mode.into(),
+        }
+    }
+}
+
+/// The format that the device presents to V4L2 and USB consumers.
+pub struct VideoConnectionFormat {
+    inner: sys::MWCAP_VIDEO_CONNECTION_FORMAT,
+}
+
+impl VideoConnectionFormat {
+    pub fn is_connected(&self) -> bool {
+        self.inner.bConnected != 0
+    }
+
+    pub fn width(&self) -> u16 {
+        self.inner.cx as _
+    }
+
+    pub fn height(&self) -> u16 {
+        self.inner.cy as _
+    }
+
+    pub fn frame_duration(&self) -> Duration {
+        Duration::from_nanos(100 * self.inner.dwFrameDuration as u64)
+    }
+
+    pub fn format(&self) -> FourCC {
+        self.inner.dwFOURCC.into()
+    }
+
+    pub fn aspect_ratio(&self) -> (u32, u32) {
+        (self.inner.nAspectX as _, self.inner.nAspectY as _)
+    }
+
+    pub fn colorimetry(&self) -> Colorimetry {
+        Colorimetry {
+            color_format: self.inner.colorFormat.into(),
+            quantization_range: self.inner.quantRange.into(),
+            saturation_range: self.inner.satRange.into(),
+        }
+    }
+}
+
+impl From<sys::MWCAP_VIDEO_CONNECTION_FORMAT> for VideoConnectionFormat {
+    fn from(format: sys::MWCAP_VIDEO_CONNECTION_FORMAT) -> Self {
+        VideoConnectionFormat { inner: format }
+    }
+}
+
+/// Identifies a frame in the device's on-board frame buffer, for Pro capture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VideoFrameId {
+    NewestBuffered,
+    NewestBuffering,
+    NextBuffered,
+    NextBuffering,
+    Index(u8),
+}
+
+impl From<VideoFrameId> for i32 {
+    fn from(id: VideoFrameId) -> Self {
+        match id {
+            VideoFrameId::NewestBuffered => sys::MWCAP_VIDEO_FRAME_ID_NEWEST_BUFFERED,
+            VideoFrameId::NewestBuffering => sys::MWCAP_VIDEO_FRAME_ID_NEWEST_BUFFERING,
+            VideoFrameId::NextBuffered => sys::MWCAP_VIDEO_FRAME_ID_NEXT_BUFFERED,
+            VideoFrameId::NextBuffering => sys::MWCAP_VIDEO_FRAME_ID_NEXT_BUFFERING,
+            VideoFrameId::Index(i) => i as _,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Field {
+    Top,
+    Bottom,
+}
+
+impl Field {
+    fn index(self) -> usize {
+        match self {
+            Self::Top => 0,
+            Self::Bottom => 1,
+        }
+    }
+}
+
+/// The state of the device's on-board frame buffer, for Pro capture.
+pub struct VideoBufferInfo {
+    inner: sys::MWCAP_VIDEO_BUFFER_INFO,
+}
+
+impl VideoBufferInfo {
+    pub fn max_frames(&self) -> u32 {
+        self.inner.cMaxFrames
+    }
+
+    /// Returns the index of the frame that's currently being buffered.
+    pub fn newest_buffering(&self) -> u8 {
+        self.inner.iNewestBuffering
+    }
+
+    /// Returns the index of the frame that most recently had a field or slice buffered.
+    pub fn newest_buffered(&self) -> u8 {
+        self.inner.iNewestBuffered
+    }
+
+    /// Returns which field of `newest_buffered` was most recently buffered.
+    pub fn buffered_field(&self) -> Field {
+        match self.inner.iBufferedFieldIndex {
+            0 => Field::Top,
+            _ => Field::Bottom,
+        }
+    }
+
+    pub fn newest_buffered_full_frame(&self) -> u8 {
+        self.inner.iNewestBufferedFullFrame
+    }
+
+    pub fn buffered_full_frame_count(&self) -> u32 {
+        self.inner.cBufferedFullFrames
+    }
+}
+
+impl From<sys::MWCAP_VIDEO_BUFFER_INFO> for VideoBufferInfo {
+    fn from(info: sys::MWCAP_VIDEO_BUFFER_INFO) -> Self {
+        VideoBufferInfo { inner: info }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VideoFrameState {
+    Initial,
+    TopFieldBuffering,
+    BottomFieldBuffering,
+    Buffered,
+    Other(u32),
+}
+
+impl From<sys::MWCAP_VIDEO_FRAME_STATE> for VideoFrameState {
+    fn from(state: sys::MWCAP_VIDEO_FRAME_STATE) -> Self {
+        match state {
+            sys::_MWCAP_VIDEO_FRAME_STATE_MWCAP_VIDEO_FRAME_STATE_INITIAL => Self::Initial,
+            sys::_MWCAP_VIDEO_FRAME_STATE_MWCAP_VIDEO_FRAME_STATE_F0_BUFFERING => {
+                Self::TopFieldBuffering
+            }
+            sys::_MWCAP_VIDEO_FRAME_STATE_MWCAP_VIDEO_FRAME_STATE_F1_BUFFERING => {
+                Self::BottomFieldBuffering
+            }
+            sys::_MWCAP_VIDEO_FRAME_STATE_MWCAP_VIDEO_FRAME_STATE_BUFFERED => Self::Buffered,
+            _ => Self::Other(state),
+        }
+    }
+}
+
+/// Info about a frame in the device's on-board frame buffer, for Pro capture.
+pub struct VideoFrameInfo {
+    inner: sys::MWCAP_VIDEO_FRAME_INFO,
+}
+
+impl VideoFrameInfo {
+    pub fn state(&self) -> VideoFrameState {
+        self.inner.state.into()
+    }
+
+    pub fn is_interlaced(&self) -> bool {
+        self.inner.bInterlaced != 0
+    }
+
+    pub fn is_segmented_frame(&self) -> bool {
+        self.inner.bSegmentedFrame != 0
+    }
+
+    pub fn is_top_field_first(&self) -> bool {
+        self.inner.bTopFieldFirst != 0
+    }
+
+    pub fn is_top_field_inverted(&self) -> bool {
+        self.inner.bTopFieldInverted != 0
+    }
+
+    pub fn width(&self) -> u16 {
+        self.inner.cx as _
+    }
+
+    pub fn height(&self) -> u16 {
+        self.inner.cy as _
+    }
+
+    pub fn aspect_ratio(&self) -> (u32, u32) {
+        (self.inner.nAspectX as _, self.inner.nAspectY as _)
+    }
+
+    /// Returns the device time at which the given field started buffering.
+    pub fn field_start_time(&self, field: Field) -> Duration {
+        let times = self.inner.allFieldStartTimes;
+        Duration::from_nanos(100 * times[field.index()] as u64)
+    }
+
+    /// Returns the device time at which the given field was fully buffered.
+    pub fn field_buffered_time(&self, field: Field) -> Duration {
+        let times = self.inner.allFieldBufferedTimes;
+        Duration::from_nanos(100 * times[field.index()] as u64)
+    }
+
+    /// Returns the SMPTE timecode of the given field, or `None` if it isn't valid. For
+    /// progressive sources, the top field's timecode applies to the whole frame.
+    pub fn timecode(&self, field: Field) -> Option<Timecode> {
+        let tc = self.inner.aSMPTETimeCodes[field.index()];
+        Timecode::from_bcd(tc.byFrames, tc.bySeconds, tc.byMinutes, tc.byHours)
+    }
+}
+
+impl From<sys::MWCAP_VIDEO_FRAME_INFO> for VideoFrameInfo {
+    fn from(info: sys::MWCAP_VIDEO_FRAME_INFO) -> Self {
+        VideoFrameInfo { inner: info }
+    }
+}
+
+/// Info about a frame or field captured by `ProChannel`.
+#[derive(Debug, Clone)]
+pub struct ProVideoCaptureInfo {
+    field: Option<Field>,
+    buffered_time: Duration,
+    timecode: Option<Timecode>,
+    hdr_metadata: Option<HdrMetadata>,
+    anc_packets: Vec<AncPacket>,
+}
+
+impl ProVideoCaptureInfo {
+    pub(crate) fn new(
+        field: Option<Field>,
+        buffered_time: Duration,
+        timecode: Option<Timecode>,
+        hdr_metadata: Option<HdrMetadata>,
+        anc_packets: Vec<AncPacket>,
+    ) -> Self {
+        Self {
+            field,
+            buffered_time,
+            timecode,
+            hdr_metadata,
+            anc_packets,
+        }
+    }
+
+    /// Returns which field was captured, or `None` if a whole frame was captured.
+    pub fn field(&self) -> Option<Field> {
+        self.field
+    }
+
+    /// Returns the device time at which the field, or the frame's last field, was buffered.
+    pub fn buffered_time(&self) -> Duration {
+        self.buffered_time
+    }
+
+    /// Returns the SMPTE timecode that the device read for the field, or for the frame's first
+    /// field, if it was valid.
+    pub fn timecode(&self) -> Option<Timecode> {
+        self.timecode
+    }
+
+    /// Returns the HDR metadata in effect when the frame was captured, if enabled via
+    /// `ProChannel::enable_hdr_metadata`.
+    pub fn hdr_metadata(&self) -> Option<HdrMetadata> {
+        self.hdr_metadata
+    }
+
+    /// Returns the ANC packets received since the previous capture, if enabled via
+    /// `ProChannel::enable_anc`. Packets are attributed to captures by when they're polled, so a
+    /// packet received just before a frame is buffered may be given to the next capture instead.
+    pub fn anc_packets(&self) -> &[AncPacket] {
+        &self.anc_packets
+    }
+}
+
+/// An ANC packet as returned by `get_sdi_anc_packet`.
+pub struct SdiAncPacket {
+    inner: sys::MWCAP_SDI_ANC_PACKET,
+}
+
+impl SdiAncPacket {
+    pub fn did(&self) -> u8 {
+        self.inner.byDID
+    }
+
+    pub fn sdid(&self) -> u8 {
+        self.inner.bySDID
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.inner.abyUDW[..self.inner.byDC as usize]
+    }
+}
+
+impl From<sys::MWCAP_SDI_ANC_PACKET> for SdiAncPacket {
+    fn from(packet: sys::MWCAP_SDI_ANC_PACKET) -> Self {
+        SdiAncPacket { inner: packet }
+    }
 }
 
 pub struct AudioCaptureFrame {
@@ -250,6 +1573,9 @@ impl From<sys::_MWCAP_AUDIO_CAPTURE_FRAME> for AudioCaptureFrame {
 }
 
 bitflags! {
+    /// Events that can be passed to `register_notify`. Note that the `INPUT_SORUCE_*` names
+    /// preserve the SDK's spelling.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub struct NotifyEvents: u32 {
         const INPUT_SORUCE_START_SCAN = 1;
         const INPUT_SORUCE_STOP_SCAN = 2;
@@ -272,3 +1598,158 @@ bitflags! {
         const NEW_SDI_ANC_PACKET = 131072;
     }
 }
+
+/// Identifies an HDMI InfoFrame for `get_hdmi_infoframe`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InfoFrameId {
+    Avi,
+    Audio,
+    Spd,
+    Ms,
+    Vs,
+    Acp,
+    Isrc1,
+    Isrc2,
+    Gamut,
+    Vbi,
+    Hdr,
+    Other(u32),
+}
+
+impl InfoFrameId {
+    pub const ALL: [InfoFrameId; 11] = [
+        Self::Avi,
+        Self::Audio,
+        Self::Spd,
+        Self::Ms,
+        Self::Vs,
+        Self::Acp,
+        Self::Isrc1,
+        Self::Isrc2,
+        Self::Gamut,
+        Self::Vbi,
+        Self::Hdr,
+    ];
+
+    /// Returns the flag indicating that this InfoFrame has been received, which is empty for IDs
+    /// beyond the mask's 32 bits.
+    pub fn mask(&self) -> InfoFrameMask {
+        InfoFrameMask::from_bits_retain(1u32.checked_shl(u32::from(*self)).unwrap_or(0))
+    }
+}
+
+impl From<sys::MWCAP_HDMI_INFOFRAME_ID> for InfoFrameId {
+    fn from(id: sys::MWCAP_HDMI_INFOFRAME_ID) -> Self {
+        match id {
+            sys::_MWCAP_HDMI_INFOFRAME_ID_MWCAP_HDMI_INFOFRAME_ID_AVI => Self::Avi,
+            sys::_MWCAP_HDMI_INFOFRAME_ID_MWCAP_HDMI_INFOFRAME_ID_AUDIO => Self::Audio,
+            sys::_MWCAP_HDMI_INFOFRAME_ID_MWCAP_HDMI_INFOFRAME_ID_SPD => Self::Spd,
+            sys::_MWCAP_HDMI_INFOFRAME_ID_MWCAP_HDMI_INFOFRAME_ID_MS => Self::Ms,
+            sys::_MWCAP_HDMI_INFOFRAME_ID_MWCAP_HDMI_INFOFRAME_ID_VS => Self::Vs,
+            sys::_MWCAP_HDMI_INFOFRAME_ID_MWCAP_HDMI_INFOFRAME_ID_ACP => Self::Acp,
+            sys::_MWCAP_HDMI_INFOFRAME_ID_MWCAP_HDMI_INFOFRAME_ID_ISRC1 => Self::Isrc1,
+            sys::_MWCAP_HDMI_INFOFRAME_ID_MWCAP_HDMI_INFOFRAME_ID_ISRC2 => Self::Isrc2,
+            sys::_MWCAP_HDMI_INFOFRAME_ID_MWCAP_HDMI_INFOFRAME_ID_GAMUT => Self::Gamut,
+            sys::_MWCAP_HDMI_INFOFRAME_ID_MWCAP_HDMI_INFOFRAME_ID_VBI => Self::Vbi,
+            sys::_MWCAP_HDMI_INFOFRAME_ID_MWCAP_HDMI_INFOFRAME_ID_HDR => Self::Hdr,
+            _ => Self::Other(id),
+        }
+    }
+}
+
+impl From<InfoFrameId> for u32 {
+    fn from(id: InfoFrameId) -> Self {
+        match id {
+            InfoFrameId::Avi => sys::_MWCAP_HDMI_INFOFRAME_ID_MWCAP_HDMI_INFOFRAME_ID_AVI,
+            InfoFrameId::Audio => sys::_MWCAP_HDMI_INFOFRAME_ID_MWCAP_HDMI_INFOFRAME_ID_AUDIO,
+            InfoFrameId::Spd => sys::_MWCAP_HDMI_INFOFRAME_ID_MWCAP_HDMI_INFOFRAME_ID_SPD,
+            InfoFrameId::Ms => sys::_MWCAP_HDMI_INFOFRAME_ID_MWCAP_HDMI_INFOFRAME_ID_MS,
+            InfoFrameId::Vs => sys::_MWCAP_HDMI_INFOFRAME_ID_MWCAP_HDMI_INFOFRAME_ID_VS,
+            InfoFrameId::Acp => sys::_MWCAP_HDMI_INFOFRAME_ID_MWCAP_HDMI_INFOFRAME_ID_ACP,
+            InfoFrameId::Isrc1 => sys::_MWCAP_HDMI_INFOFRAME_ID_MWCAP_HDMI_INFOFRAME_ID_ISRC1,
+            InfoFrameId::Isrc2 => sys::_MWCAP_HDMI_INFOFRAME_ID_MWCAP_HDMI_INFOFRAME_ID_ISRC2,
+            InfoFrameId::Gamut => sys::_MWCAP_HDMI_INFOFRAME_ID_MWCAP_HDMI_INFOFRAME_ID_GAMUT,
+            InfoFrameId::Vbi => sys::_MWCAP_HDMI_INFOFRAME_ID_MWCAP_HDMI_INFOFRAME_ID_VBI,
+            InfoFrameId::Hdr => sys::_MWCAP_HDMI_INFOFRAME_ID_MWCAP_HDMI_INFOFRAME_ID_HDR,
+            InfoFrameId::Other(id) => id,
+        }
+    }
+}
+
+bitflags! {
+    /// The HDMI InfoFrames that have been received, as returned by
+    /// `get_hdmi_infoframe_valid_flags`.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct InfoFrameMask: u32 {
+        const AVI = sys::_MWCAP_HDMI_INFOFRAME_MASK_MWCAP_HDMI_INFOFRAME_MASK_AVI;
+        const AUDIO = sys::_MWCAP_HDMI_INFOFRAME_MASK_MWCAP_HDMI_INFOFRAME_MASK_AUDIO;
+        const SPD = sys::_MWCAP_HDMI_INFOFRAME_MASK_MWCAP_HDMI_INFOFRAME_MASK_SPD;
+        const MS = sys::_MWCAP_HDMI_INFOFRAME_MASK_MWCAP_HDMI_INFOFRAME_MASK_MS;
+        const VS = sys::_MWCAP_HDMI_INFOFRAME_MASK_MWCAP_HDMI_INFOFRAME_MASK_VS;
+        const ACP = sys::_MWCAP_HDMI_INFOFRAME_MASK_MWCAP_HDMI_INFOFRAME_MASK_ACP;
+        const ISRC1 = sys::_MWCAP_HDMI_INFOFRAME_MASK_MWCAP_HDMI_INFOFRAME_MASK_ISRC1;
+        const ISRC2 = sys::_MWCAP_HDMI_INFOFRAME_MASK_MWCAP_HDMI_INFOFRAME_MASK_ISRC2;
+        const GAMUT = sys::_MWCAP_HDMI_INFOFRAME_MASK_MWCAP_HDMI_INFOFRAME_MASK_GAMUT;
+        const VBI = sys::_MWCAP_HDMI_INFOFRAME_MASK_MWCAP_HDMI_INFOFRAME_MASK_VBI;
+        const HDR = sys::_MWCAP_HDMI_INFOFRAME_MASK_MWCAP_HDMI_INFOFRAME_MASK_HDR;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_rows() {
+        let data = [0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 0xff, 0xff];
+        assert_eq!(
+            split_rows(&data, 2, 2, 5, false).unwrap(),
+            [vec![0, 0, 2, 2, 4, 4], vec![1, 1, 3, 3]]
+        );
+        // For bottom-up images, the last row in memory is the top row of the image.
+        assert_eq!(
+            split_rows(&data[..8], 2, 1, 4, true).unwrap(),
+            [vec![1, 1, 3, 3], vec![0, 0, 2, 2]]
+        );
+
+        assert!(split_rows(&data, 0, 0, 5, false).is_err());
+        assert!(split_rows(&data, 2, 3, 4, false).is_err());
+        assert!(split_rows(&data, 2, 2, 7, false).is_err());
+    }
+
+    #[test]
+    fn test_infoframe_mask() {
+        assert_eq!(InfoFrameId::Avi.mask(), InfoFrameMask::AVI);
+        assert_eq!(InfoFrameId::Hdr.mask(), InfoFrameMask::HDR);
+        assert_eq!(InfoFrameId::Other(40).mask(), InfoFrameMask::empty());
+    }
+
+    #[test]
+    fn test_frame_rate_from_duration() {
+        for (duration, num, den) in [
+            (166833, 60000, 1001),
+            (166834, 60000, 1001),
+            (333667, 30000, 1001),
+            (417083, 24000, 1001),
+            (83417, 120000, 1001),
+            (166667, 60, 1),
+            (200000, 50, 1),
+            (400000, 25, 1),
+            (416667, 24, 1),
+            (1000000, 10, 1),
+        ] {
+            assert_eq!(
+                Rational::from_frame_duration_100ns(duration),
+                Some(Rational { num, den }),
+                "duration = {}",
+                duration
+            );
+        }
+        assert_eq!(Rational::from_frame_duration_100ns(0), None);
+        // Non-standard rates should be returned exactly.
+        assert_eq!(
+            Rational::from_frame_duration_100ns(150000),
+            Some(Rational { num: 200, den: 3 })
+        );
+    }
+}
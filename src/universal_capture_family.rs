@@ -1,6 +1,11 @@
-use super::{sys, AudioSignalStatus, ChannelInfo, Result, VideoSignalStatus};
+use super::{
+    sys, AncSubscription, AudioCaps, AudioInputSource, AudioSignalStatus, ChannelInfo, Colorimetry,
+    FamilyInfo, FourCC, InfoFrame, InfoFrameId, InfoFrameMask, InputSpecificStatus,
+    QuantizationRange, Result, SdiAncPacket, SupportedResolutions, VideoCaps, VideoColorFormat,
+    VideoConnectionFormat, VideoInputSource, VideoProcessSettings, VideoSignalStatus,
+};
 use snafu::prelude::*;
-use std::{ffi::c_void, mem::MaybeUninit};
+use std::{ffi::c_void, mem::MaybeUninit, os::raw::c_int, time::Duration};
 
 /// # Safety
 /// The pointers returned by implementations of this trait must be valid.
@@ -9,6 +14,12 @@ pub unsafe trait UniversalCaptureFamilyChannel {
 
     fn info(&self) -> &ChannelInfo;
 
+    fn get_family_info(&self) -> Result<FamilyInfo> {
+        FamilyInfo::get(self.info().family(), |ptr, size| unsafe {
+            sys::MWGetFamilyInfo(self.handle(), ptr, size)
+        })
+    }
+
     fn get_audio_signal_status(&self) -> Result<AudioSignalStatus> {
         let mut status = MaybeUninit::uninit();
         unsafe {
@@ -32,4 +43,576 @@ pub unsafe trait UniversalCaptureFamilyChannel {
             Ok(status.assume_init().into())
         }
     }
+
+    fn get_input_specific_status(&self) -> Result<InputSpecificStatus> {
+        let mut status = MaybeUninit::uninit();
+        unsafe {
+            if sys::MWGetInputSpecificStatus(self.handle(), status.as_mut_ptr())
+                != sys::_MW_RESULT__MW_SUCCEEDED
+            {
+                whatever!("unable to get input specific status");
+            }
+            Ok(status.assume_init().into())
+        }
+    }
+
+    /// Returns the color format that the input is treated as having, which is either detected or
+    /// set via `set_video_input_color_format`.
+    fn get_video_input_color_format(&self) -> Result<VideoColorFormat> {
+        let mut format = 0;
+        unsafe {
+            if sys::MWGetVideoInputColorFormat(self.handle(), &mut format as *mut _)
+                != sys::_MW_RESULT__MW_SUCCEEDED
+            {
+                whatever!("unable to get video input color format");
+            }
+        }
+        Ok(format.into())
+    }
+
+    /// Overrides the input's color format, e.g. for sources that incorrectly signal BT.709.
+    fn set_video_input_color_format(&self, format: VideoColorFormat) -> Result<()> {
+        unsafe {
+            if sys::MWSetVideoInputColorFormat(self.handle(), format.into())
+                != sys::_MW_RESULT__MW_SUCCEEDED
+            {
+                whatever!("unable to set video input color format");
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the quantization range that the input is treated as having, which is either
+    /// detected or set via `set_video_input_quantization_range`.
+    fn get_video_input_quantization_range(&self) -> Result<QuantizationRange> {
+        let mut range = 0;
+        unsafe {
+            if sys::MWGetVideoInputQuantizationRange(self.handle(), &mut range as *mut _)
+                != sys::_MW_RESULT__MW_SUCCEEDED
+            {
+                whatever!("unable to get video input quantization range");
+            }
+        }
+        Ok(range.into())
+    }
+
+    /// Overrides the input's quantization range, e.g. for sources that incorrectly signal full
+    /// range.
+    fn set_video_input_quantization_range(&self, range: QuantizationRange) -> Result<()> {
+        unsafe {
+            if sys::MWSetVideoInputQuantizationRange(self.handle(), range.into())
+                != sys::_MW_RESULT__MW_SUCCEEDED
+            {
+                whatever!("unable to set video input quantization range");
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the aspect ratio that the input is treated as having, as (width, height).
+    fn get_video_input_aspect_ratio(&self) -> Result<(u32, u32)> {
+        let (mut x, mut y) = (0, 0);
+        unsafe {
+            if sys::MWGetVideoInputAspectRatio(self.handle(), &mut x as *mut _, &mut y as *mut _)
+                != sys::_MW_RESULT__MW_SUCCEEDED
+            {
+                whatever!("unable to get video input aspect ratio");
+            }
+        }
+        Ok((x as _, y as _))
+    }
+
+    /// Overrides the input's aspect ratio.
+    fn set_video_input_aspect_ratio(&self, width: u32, height: u32) -> Result<()> {
+        unsafe {
+            if sys::MWSetVideoInputAspectRatio(self.handle(), width as _, height as _)
+                != sys::_MW_RESULT__MW_SUCCEEDED
+            {
+                whatever!("unable to set video input aspect ratio");
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the colorimetry that the input is treated as having, taking into account any
+    /// overrides.
+    fn get_input_colorimetry(&self) -> Result<Colorimetry> {
+        Ok(Colorimetry {
+            color_format: self.get_video_input_color_format()?,
+            quantization_range: self.get_video_input_quantization_range()?,
+            saturation_range: self.get_video_signal_status()?.saturation_range(),
+        })
+    }
+
+    fn get_video_capture_connection_format(&self) -> Result<VideoConnectionFormat> {
+        let mut format = MaybeUninit::uninit();
+        unsafe {
+            if sys::MWGetVideoCaptureConnectionFormat(self.handle(), format.as_mut_ptr())
+                != sys::_MW_RESULT__MW_SUCCEEDED
+            {
+                whatever!("unable to get video capture connection format");
+            }
+            Ok(format.assume_init().into())
+        }
+    }
+
+    fn get_video_capture_process_settings(&self) -> Result<VideoProcessSettings> {
+        let mut settings = MaybeUninit::uninit();
+        unsafe {
+            if sys::MWGetVideoCaptureProcessSettings(self.handle(), settings.as_mut_ptr())
+                != sys::_MW_RESULT__MW_SUCCEEDED
+            {
+                whatever!("unable to get video capture process settings");
+            }
+            Ok(settings.assume_init().into())
+        }
+    }
+
+    fn set_video_capture_process_settings(&self, settings: &VideoProcessSettings) -> Result<()> {
+        let mut settings: sys::MWCAP_VIDEO_PROCESS_SETTINGS = (*settings).into();
+        unsafe {
+            if sys::MWSetVideoCaptureProcessSettings(self.handle(), &mut settings as *mut _)
+                != sys::_MW_RESULT__MW_SUCCEEDED
+            {
+                whatever!("unable to set video capture process settings");
+            }
+        }
+        Ok(())
+    }
+
+    fn get_video_caps(&self) -> Result<VideoCaps> {
+        let mut caps = MaybeUninit::uninit();
+        unsafe {
+            if sys::MWGetVideoCaps(self.handle(), caps.as_mut_ptr())
+                != sys::_MW_RESULT__MW_SUCCEEDED
+            {
+                whatever!("unable to get video caps");
+            }
+            Ok(caps.assume_init().into())
+        }
+    }
+
+    fn get_audio_caps(&self) -> Result<AudioCaps> {
+        let mut caps = MaybeUninit::<sys::MWCAP_AUDIO_CAPS>::uninit();
+        unsafe {
+            if sys::MWGetAudioCaps(self.handle(), caps.as_mut_ptr())
+                != sys::_MW_RESULT__MW_SUCCEEDED
+            {
+                whatever!("unable to get audio caps");
+            }
+            Ok(AudioCaps::from_bits_retain(caps.assume_init().dwCaps))
+        }
+    }
+
+    /// Returns the pixel formats that the device can capture to.
+    fn get_supported_color_formats(&self) -> Result<Vec<FourCC>> {
+        let mut count: c_int = 0;
+        unsafe {
+            if sys::MWGetVideoCaptureSupportColorFormat(
+                self.handle(),
+                std::ptr::null_mut(),
+                &mut count as *mut _,
+            ) != sys::_MW_RESULT__MW_SUCCEEDED
+            {
+                whatever!("unable to get supported color format count");
+            }
+        }
+        let mut formats = vec![0; count.max(0) as usize];
+        unsafe {
+            if sys::MWGetVideoCaptureSupportColorFormat(
+                self.handle(),
+                formats.as_mut_ptr(),
+                &mut count as *mut _,
+            ) != sys::_MW_RESULT__MW_SUCCEEDED
+            {
+                whatever!("unable to get supported color formats");
+            }
+        }
+        formats.truncate(count.max(0) as usize);
+        Ok(formats.into_iter().map(FourCC::from).collect())
+    }
+
+    /// Returns the resolutions that the device can capture at.
+    fn get_supported_resolutions(&self) -> Result<SupportedResolutions> {
+        let mut mode = MaybeUninit::uninit();
+        let mut count: c_int = 0;
+        let mode = unsafe {
+            if sys::MWGetVideoCaptureSupportResolutionMode(
+                self.handle(),
+                mode.as_mut_ptr(),
+                &mut count as *mut _,
+            ) != sys::_MW_RESULT__MW_SUCCEEDED
+            {
+                whatever!("unable to get supported resolution mode");
+            }
+            mode.assume_init()
+        };
+
+        match mode {
+            sys::_VIDEO_RESOLUTION_MODE_MWCAP_VIDEO_RESOLUTION_MODE_RANGE => {
+                let mut range = MaybeUninit::<sys::MWCAP_VIDEO_RESOLUTION_RANGE>::uninit();
+                let range = unsafe {
+                    if sys::MWGetVideoCaptureSupportRangeResolution(
+                        self.handle(),
+                        range.as_mut_ptr(),
+                    ) != sys::_MW_RESULT__MW_SUCCEEDED
+                    {
+                        whatever!("unable to get supported resolution range");
+                    }
+                    range.assume_init()
+                };
+                Ok(SupportedResolutions::Range {
+                    min_width: range.minResolution.cx as _,
+                    min_height: range.minResolution.cy as _,
+                    max_width: range.maxResolution.cx as _,
+                    max_height: range.maxResolution.cy as _,
+                    width_step: range.nStepCx as _,
+                    height_step: range.nStepCy as _,
+                })
+            }
+            sys::_VIDEO_RESOLUTION_MODE_MWCAP_VIDEO_RESOLUTION_MODE_LIST => {
+                let mut resolutions =
+                    vec![sys::MWCAP_VIDEO_RESOLUTION { cx: 0, cy: 0 }; count.max(0) as usize];
+                let mut list = sys::MWCAP_VIDEO_RESOLUTION_LIST {
+                    nListSize: count,
+                    plistResolution: resolutions.as_mut_ptr(),
+                };
+                unsafe {
+                    if sys::MWGetVideoCaptureSupportListResolution(
+                        self.handle(),
+                        &mut list as *mut _,
+                    ) != sys::_MW_RESULT__MW_SUCCEEDED
+                    {
+                        whatever!("unable to get supported resolution list");
+                    }
+                }
+                resolutions.truncate(list.nListSize.clamp(0, count) as usize);
+                Ok(SupportedResolutions::List(
+                    resolutions
+                        .into_iter()
+                        .map(|r| (r.cx as _, r.cy as _))
+                        .collect(),
+                ))
+            }
+            _ => whatever!("unknown resolution mode {}", mode),
+        }
+    }
+
+    /// Checks whether the device can capture with the given configuration. If it can't, the
+    /// returned error describes why.
+    ///
+    /// Not all devices support all of the underlying queries. Any query that isn't supported is
+    /// skipped, so a successful result isn't a guarantee that capture will succeed.
+    fn validate_capture_config(
+        &self,
+        width: u16,
+        height: u16,
+        format: FourCC,
+        frame_duration: Option<Duration>,
+    ) -> Result<()> {
+        if width == 0 || height == 0 {
+            whatever!("invalid capture size {}x{}", width, height);
+        }
+
+        if let Some(frame_duration) = frame_duration {
+            // Frame durations are expressed in 100ns units by the SDK.
+            if frame_duration.as_nanos() < 100 || frame_duration.as_nanos() / 100 > i64::MAX as u128
+            {
+                whatever!("invalid frame duration {:?}", frame_duration);
+            }
+        }
+
+        let caps = self.get_video_caps()?;
+        if caps.max_output_width() > 0
+            && caps.max_output_height() > 0
+            && (width > caps.max_output_width() || height > caps.max_output_height())
+        {
+            whatever!(
+                "capture size {}x{} exceeds the maximum output size of {}x{}",
+                width,
+                height,
+                caps.max_output_width(),
+                caps.max_output_height(),
+            );
+        }
+
+        if let Ok(formats) = self.get_supported_color_formats() {
+            if !formats.is_empty() && !formats.contains(&format) {
+                whatever!(
+                    "format {:?} is not supported, supported formats are {:?}",
+                    format,
+                    formats
+                );
+            }
+        }
+
+        if let Ok(resolutions) = self.get_supported_resolutions() {
+            if !resolutions.contains(width, height) {
+                whatever!(
+                    "capture size {}x{} is not supported, supported resolutions are {:?}",
+                    width,
+                    height,
+                    resolutions
+                );
+            }
+        }
+
+        if format.image_size(width, height, format.min_stride(width, 1)) == 0 {
+            whatever!("format {:?} is not a known pixel format", format);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the video inputs available on the channel.
+    fn get_video_input_sources(&self) -> Result<Vec<VideoInputSource>> {
+        get_input_source_array(|sources, count| unsafe {
+            sys::MWGetVideoInputSourceArray(self.handle(), sources, count)
+        })
+    }
+
+    fn get_video_input_source(&self) -> Result<VideoInputSource> {
+        let mut source = 0;
+        unsafe {
+            if sys::MWGetVideoInputSource(self.handle(), &mut source as *mut _)
+                != sys::_MW_RESULT__MW_SUCCEEDED
+            {
+                whatever!("unable to get video input source");
+            }
+        }
+        Ok(source.into())
+    }
+
+    /// Switches the channel to the given video input. Input source changes can be observed via
+    /// `NotifyEvents::VIDEO_INPUT_SOURCE_CHANGE`.
+    fn set_video_input_source(&mut self, source: VideoInputSource) -> Result<()> {
+        unsafe {
+            if sys::MWSetVideoInputSource(self.handle(), source.into())
+                != sys::_MW_RESULT__MW_SUCCEEDED
+            {
+                whatever!("unable to set video input source");
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the audio inputs available on the channel.
+    fn get_audio_input_sources(&self) -> Result<Vec<AudioInputSource>> {
+        get_input_source_array(|sources, count| unsafe {
+            sys::MWGetAudioInputSourceArray(self.handle(), sources, count)
+        })
+    }
+
+    fn get_audio_input_source(&self) -> Result<AudioInputSource> {
+        let mut source = 0;
+        unsafe {
+            if sys::MWGetAudioInputSource(self.handle(), &mut source as *mut _)
+                != sys::_MW_RESULT__MW_SUCCEEDED
+            {
+                whatever!("unable to get audio input source");
+            }
+        }
+        Ok(source.into())
+    }
+
+    /// Switches the channel to the given audio input. Input source changes can be observed via
+    /// `NotifyEvents::AUDIO_INPUT_SOURCE_CHANGE`.
+    fn set_audio_input_source(&mut self, source: AudioInputSource) -> Result<()> {
+        unsafe {
+            if sys::MWSetAudioInputSource(self.handle(), source.into())
+                != sys::_MW_RESULT__MW_SUCCEEDED
+            {
+                whatever!("unable to set audio input source");
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns whether the audio input automatically follows the video input.
+    fn get_av_input_source_link(&self) -> Result<bool> {
+        let mut link = 0;
+        unsafe {
+            if sys::MWGetAVInputSourceLink(self.handle(), &mut link as *mut _)
+                != sys::_MW_RESULT__MW_SUCCEEDED
+            {
+                whatever!("unable to get av input source link");
+            }
+        }
+        Ok(link != 0)
+    }
+
+    /// Sets whether the audio input automatically follows the video input.
+    fn set_av_input_source_link(&mut self, link: bool) -> Result<()> {
+        unsafe {
+            if sys::MWSetAVInputSourceLink(self.handle(), link as _)
+                != sys::_MW_RESULT__MW_SUCCEEDED
+            {
+                whatever!("unable to set av input source link");
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns whether automatic input source scanning is enabled.
+    fn get_input_source_scan(&self) -> Result<bool> {
+        let mut scan = 0;
+        unsafe {
+            if sys::MWGetInputSourceScan(self.handle(), &mut scan as *mut _)
+                != sys::_MW_RESULT__MW_SUCCEEDED
+            {
+                whatever!("unable to get input source scan");
+            }
+        }
+        Ok(scan != 0)
+    }
+
+    /// Enables or disables automatic input source scanning, in which the device switches to
+    /// whichever input has a signal. Scanning can be observed via the
+    /// `NotifyEvents::INPUT_SORUCE_*` events.
+    fn set_input_source_scan(&mut self, scan: bool) -> Result<()> {
+        unsafe {
+            if sys::MWSetInputSourceScan(self.handle(), scan as _) != sys::_MW_RESULT__MW_SUCCEEDED
+            {
+                whatever!("unable to set input source scan");
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns whether the device is currently scanning for an input source.
+    fn get_input_source_scan_state(&self) -> Result<bool> {
+        let mut scanning = 0;
+        unsafe {
+            if sys::MWGetInputSourceScanState(self.handle(), &mut scanning as *mut _)
+                != sys::_MW_RESULT__MW_SUCCEEDED
+            {
+                whatever!("unable to get input source scan state");
+            }
+        }
+        Ok(scanning != 0)
+    }
+
+    /// Returns which HDMI InfoFrames have been received from the source.
+    fn get_hdmi_infoframe_valid_flags(&self) -> Result<InfoFrameMask> {
+        let mut flags = 0;
+        unsafe {
+            if sys::MWGetHDMIInfoFrameValidFlag(self.handle(), &mut flags as *mut _)
+                != sys::_MW_RESULT__MW_SUCCEEDED
+            {
+                whatever!("unable to get hdmi infoframe valid flags");
+            }
+        }
+        Ok(InfoFrameMask::from_bits_retain(flags))
+    }
+
+    /// Returns the raw packet of the given InfoFrame, i.e. its header, checksum, and payload.
+    fn get_hdmi_infoframe_packet(&self, id: InfoFrameId) -> Result<Vec<u8>> {
+        let mut packet = MaybeUninit::<sys::HDMI_INFOFRAME_PACKET>::zeroed();
+        unsafe {
+            if sys::MWGetHDMIInfoFramePacket(self.handle(), id.into(), packet.as_mut_ptr())
+                != sys::_MW_RESULT__MW_SUCCEEDED
+            {
+                whatever!("unable to get hdmi infoframe {:?}", id);
+            }
+            Ok(std::slice::from_raw_parts(
+                packet.as_ptr() as *const u8,
+                std::mem::size_of::<sys::HDMI_INFOFRAME_PACKET>(),
+            )
+            .to_vec())
+        }
+    }
+
+    /// Returns the given InfoFrame, parsed and with its checksum verified.
+    fn get_hdmi_infoframe(&self, id: InfoFrameId) -> Result<InfoFrame> {
+        InfoFrame::parse(&self.get_hdmi_infoframe_packet(id)?)
+    }
+
+    /// Returns all of the InfoFrames that have been received from the source. InfoFrames that
+    /// can't be read or parsed, e.g. due to a bad checksum, are skipped, so one bad InfoFrame
+    /// doesn't hide the rest. Use `get_hdmi_infoframe` to get the error for a given InfoFrame.
+    fn get_hdmi_infoframes(&self) -> Result<Vec<InfoFrame>> {
+        let valid = self.get_hdmi_infoframe_valid_flags()?;
+        Ok(InfoFrameId::ALL
+            .iter()
+            .filter(|id| valid.contains(id.mask()))
+            .filter_map(|id| self.get_hdmi_infoframe(*id).ok())
+            .collect())
+    }
+
+    /// Sets one of the `MAX_ANC_SUBSCRIPTIONS` types of ANC packet captured from SDI inputs, or
+    /// clears it if `subscription` is `None`.
+    fn set_sdi_anc_type(&self, index: u8, subscription: Option<&AncSubscription>) -> Result<()> {
+        let (hanc, vanc, did, sdid) = match subscription {
+            Some(s) => (s.hanc, s.vanc, s.id.did, s.id.sdid),
+            None => (false, false, 0, 0),
+        };
+        unsafe {
+            if sys::MWCaptureSetSDIANCType(self.handle(), index, hanc as _, vanc as _, did, sdid)
+                != sys::_MW_RESULT__MW_SUCCEEDED
+            {
+                whatever!("unable to set sdi anc type");
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the next captured SDI ANC packet, or `None` if there are none.
+    fn get_sdi_anc_packet(&self) -> Result<Option<SdiAncPacket>> {
+        let mut packet = MaybeUninit::<sys::MWCAP_SDI_ANC_PACKET>::zeroed();
+        unsafe {
+            if sys::MWCaptureGetSDIANCPacket(self.handle(), packet.as_mut_ptr())
+                != sys::_MW_RESULT__MW_SUCCEEDED
+            {
+                whatever!("unable to get sdi anc packet");
+            }
+            let packet = packet.assume_init();
+            // A DID of 0 means there are no more packets.
+            Ok((packet.byDID != 0).then(|| packet.into()))
+        }
+    }
+
+    /// Returns the EDID presented to the source on HDMI inputs. It can be parsed via
+    /// `Edid::parse`.
+    fn get_edid(&self) -> Result<Vec<u8>> {
+        // The base block plus up to three extension blocks.
+        let mut data = vec![0u8; 512];
+        let mut size = data.len() as _;
+        unsafe {
+            if sys::MWGetEDID(self.handle(), data.as_mut_ptr(), &mut size as *mut _)
+                != sys::_MW_RESULT__MW_SUCCEEDED
+            {
+                whatever!("unable to get edid");
+            }
+        }
+        data.truncate(size as _);
+        Ok(data)
+    }
+
+    /// Sets the EDID presented to the source on HDMI inputs, e.g. one built via `Edid::to_bytes`.
+    fn set_edid(&mut self, edid: &[u8]) -> Result<()> {
+        let mut data = edid.to_vec();
+        unsafe {
+            if sys::MWSetEDID(self.handle(), data.as_mut_ptr(), data.len() as _)
+                != sys::_MW_RESULT__MW_SUCCEEDED
+            {
+                whatever!("unable to set edid");
+            }
+        }
+        Ok(())
+    }
+}
+
+fn get_input_source_array<T: From<u32>>(
+    f: impl Fn(*mut u32, *mut u32) -> sys::MW_RESULT,
+) -> Result<Vec<T>> {
+    let mut count = 0;
+    if f(std::ptr::null_mut(), &mut count as *mut _) != sys::_MW_RESULT__MW_SUCCEEDED {
+        whatever!("unable to get input source count");
+    }
+    let mut sources = vec![0; count as usize];
+    if f(sources.as_mut_ptr(), &mut count as *mut _) != sys::_MW_RESULT__MW_SUCCEEDED {
+        whatever!("unable to get input sources");
+    }
+    sources.truncate(count as usize);
+    Ok(sources.into_iter().map(T::from).collect())
 }
@@ -0,0 +1,231 @@
+use super::{AudioCaptureFrame, AudioSignalStatus, Result};
+use snafu::prelude::*;
+use std::{
+    io::{Seek, SeekFrom, Write},
+    time::Duration,
+};
+
+// The offsets of the fields that are filled in when the file is finished.
+const RIFF_SIZE_OFFSET: u64 = 4;
+const DESCRIPTION_OFFSET: u64 = 44;
+const TIME_REFERENCE_OFFSET: u64 = 382;
+const DATA_SIZE_OFFSET: u64 = 650;
+const HEADER_SIZE: u64 = 654;
+
+const BEXT_SIZE: u32 = 602;
+const DESCRIPTION_SIZE: usize = 256;
+
+/// The number of stereo pairs in each `AudioCaptureFrame` sample frame, which holds the left
+/// samples of every pair followed by the right samples.
+const CHANNEL_PAIRS: usize = 4;
+
+/// The PCM format of a WAV file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WavFormat {
+    pub sample_rate: u32,
+    pub channels: u16,
+    /// The number of significant bits per sample. Samples are stored in whole bytes.
+    pub bits_per_sample: u16,
+}
+
+impl WavFormat {
+    fn sample_size(&self) -> usize {
+        (self.bits_per_sample as usize).div_ceil(8)
+    }
+}
+
+impl From<&AudioSignalStatus> for WavFormat {
+    fn from(status: &AudioSignalStatus) -> Self {
+        Self {
+            sample_rate: status.sample_rate(),
+            channels: status.channel_count() as _,
+            bits_per_sample: status.bits_per_sample() as _,
+        }
+    }
+}
+
+/// Writes captured LPCM audio as a Broadcast Wave Format file.
+///
+/// The device timestamp of the first frame is recorded in the bext chunk, both in the description
+/// and as the time reference. Note that the time reference counts samples from the device clock's
+/// epoch rather than from midnight.
+pub struct WavWriter<W: Write + Seek> {
+    out: W,
+    format: WavFormat,
+    start: Option<Duration>,
+    data_size: u64,
+}
+
+impl<W: Write + Seek> WavWriter<W> {
+    pub fn new(mut out: W, format: WavFormat) -> Result<Self> {
+        if format.sample_rate == 0 || format.channels == 0 {
+            whatever!("no audio signal");
+        }
+        if format.channels as usize > CHANNEL_PAIRS * 2
+            || !(1..=32).contains(&format.bits_per_sample)
+        {
+            whatever!("unsupported audio format {:?}", format);
+        }
+
+        let block_align = format.channels as usize * format.sample_size();
+        let mut header = Vec::with_capacity(HEADER_SIZE as _);
+        header.extend_from_slice(b"RIFF\0\0\0\0WAVE");
+        header.extend_from_slice(b"fmt ");
+        header.extend_from_slice(&16u32.to_le_bytes());
+        header.extend_from_slice(&1u16.to_le_bytes());
+        header.extend_from_slice(&format.channels.to_le_bytes());
+        header.extend_from_slice(&format.sample_rate.to_le_bytes());
+        header.extend_from_slice(&(format.sample_rate * block_align as u32).to_le_bytes());
+        header.extend_from_slice(&(block_align as u16).to_le_bytes());
+        header.extend_from_slice(&(format.sample_size() as u16 * 8).to_le_bytes());
+        header.extend_from_slice(b"bext");
+        header.extend_from_slice(&BEXT_SIZE.to_le_bytes());
+        let mut bext = vec![0; BEXT_SIZE as _];
+        let originator = env!("CARGO_PKG_NAME").as_bytes();
+        bext[256..256 + originator.len()].copy_from_slice(originator);
+        // The version.
+        bext[346..348].copy_from_slice(&1u16.to_le_bytes());
+        header.extend_from_slice(&bext);
+        header.extend_from_slice(b"data\0\0\0\0");
+
+        out.write_all(&header)
+            .whatever_context("unable to write wav header")?;
+        Ok(Self {
+            out,
+            format,
+            start: None,
+            data_size: 0,
+        })
+    }
+
+    pub fn write_frame(&mut self, frame: &AudioCaptureFrame) -> Result<()> {
+        self.write_samples(frame.samples(), frame.timestamp())
+    }
+
+    fn write_samples(&mut self, samples: &[u32], timestamp: Duration) -> Result<()> {
+        self.start.get_or_insert(timestamp);
+        let sample_size = self.format.sample_size();
+        let mut buf = Vec::with_capacity(samples.len() * sample_size);
+        for frame in samples.chunks_exact(CHANNEL_PAIRS * 2) {
+            for channel in 0..self.format.channels as usize {
+                let sample = frame[channel / 2 + channel % 2 * CHANNEL_PAIRS];
+                // Samples are left-justified, so keep the most significant bytes.
+                buf.extend_from_slice(&sample.to_le_bytes()[4 - sample_size..]);
+            }
+        }
+        self.out
+            .write_all(&buf)
+            .whatever_context("unable to write wav data")?;
+        self.data_size += buf.len() as u64;
+        Ok(())
+    }
+
+    /// Fills in the header and returns the underlying writer.
+    pub fn finish(mut self) -> Result<W> {
+        if self.data_size % 2 != 0 {
+            self.out
+                .write_all(&[0])
+                .whatever_context("unable to write wav data")?;
+        }
+        let riff_size = HEADER_SIZE - 8 + self.data_size.next_multiple_of(2);
+        let (Ok(riff_size), Ok(data_size)) =
+            (u32::try_from(riff_size), u32::try_from(self.data_size))
+        else {
+            whatever!("wav file is too large");
+        };
+
+        let start = self.start.unwrap_or_default();
+        let mut description = format!(
+            "Device timestamp: {}.{:09} s",
+            start.as_secs(),
+            start.subsec_nanos()
+        )
+        .into_bytes();
+        description.resize(DESCRIPTION_SIZE, 0);
+        let time_reference =
+            (start.as_nanos() * self.format.sample_rate as u128 / 1_000_000_000) as u64;
+
+        self.write_at(RIFF_SIZE_OFFSET, &riff_size.to_le_bytes())?;
+        self.write_at(DESCRIPTION_OFFSET, &description)?;
+        self.write_at(TIME_REFERENCE_OFFSET, &time_reference.to_le_bytes())?;
+        self.write_at(DATA_SIZE_OFFSET, &data_size.to_le_bytes())?;
+        self.out
+            .seek(SeekFrom::End(0))
+            .whatever_context("unable to seek wav file")?;
+        self.out
+            .flush()
+            .whatever_context("unable to flush wav file")?;
+        Ok(self.out)
+    }
+
+    fn write_at(&mut self, offset: u64, data: &[u8]) -> Result<()> {
+        self.out
+            .seek(SeekFrom::Start(offset))
+            .whatever_context("unable to seek wav file")?;
+        self.out
+            .write_all(data)
+            .whatever_context("unable to write wav header")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_wav() {
+        let format = WavFormat {
+            sample_rate: 48000,
+            channels: 4,
+            bits_per_sample: 20,
+        };
+        let mut writer = WavWriter::new(Cursor::new(Vec::new()), format).unwrap();
+
+        // Two sample frames, where each sample is its channel index in the upper bytes.
+        let samples: Vec<u32> = (0..16)
+            .map(|i| {
+                let index = i % 8;
+                let channel = index % CHANNEL_PAIRS * 2 + index / CHANNEL_PAIRS;
+                ((channel as u32 + 1) << 24) | ((i as u32 / 8) << 16) | 0xff
+            })
+            .collect();
+        writer
+            .write_samples(&samples, Duration::from_millis(1500))
+            .unwrap();
+        let wav = writer.finish().unwrap().into_inner();
+
+        assert_eq!(wav.len(), HEADER_SIZE as usize + 24);
+        assert_eq!(&wav[..4], b"RIFF");
+        assert_eq!(
+            u32::from_le_bytes(wav[4..8].try_into().unwrap()),
+            654 - 8 + 24
+        );
+        // 4 channels of 24 bits.
+        assert_eq!(&wav[22..24], &4u16.to_le_bytes());
+        assert_eq!(&wav[32..36], &[12, 0, 24, 0]);
+        assert_eq!(&wav[36..40], b"bext");
+        assert!(wav[44..].starts_with(b"Device timestamp: 1.500000000 s\0"));
+        assert!(wav[300..].starts_with(env!("CARGO_PKG_NAME").as_bytes()));
+        assert_eq!(u64::from_le_bytes(wav[382..390].try_into().unwrap()), 72000);
+        assert_eq!(&wav[646..650], b"data");
+        assert_eq!(u32::from_le_bytes(wav[650..654].try_into().unwrap()), 24);
+        assert_eq!(
+            &wav[654..],
+            &[
+                0, 0, 1, 0, 0, 2, 0, 0, 3, 0, 0, 4, //
+                0, 1, 1, 0, 1, 2, 0, 1, 3, 0, 1, 4,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_format() {
+        let format = WavFormat {
+            sample_rate: 0,
+            channels: 2,
+            bits_per_sample: 16,
+        };
+        assert!(WavWriter::new(Cursor::new(Vec::new()), format).is_err());
+    }
+}
@@ -0,0 +1,348 @@
+use super::{EcoVideoCaptureFrame, FourCC, QuantizationRange, Rational, Result, VideoSignalStatus};
+use snafu::prelude::*;
+use std::io::Write;
+
+/// How the frames of a Y4M stream are scanned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Y4mInterlace {
+    Progressive,
+    TopFieldFirst,
+    BottomFieldFirst,
+}
+
+/// The stream parameters of a Y4M file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Y4mHeader {
+    pub width: u16,
+    pub height: u16,
+    /// The pixel format of the captured frames, which is converted to the matching Y4M colorspace.
+    pub format: FourCC,
+    pub frame_rate: Rational,
+    pub interlace: Y4mInterlace,
+    /// The pixel aspect ratio, or 0:0 if unknown.
+    pub pixel_aspect: Rational,
+    pub quantization_range: QuantizationRange,
+}
+
+impl Y4mHeader {
+    /// Describes frames captured from a signal at its native resolution.
+    ///
+    /// Interlaced signals are tagged as top field first, which assumes that frames are captured
+    /// with `DeinterlaceMode::Weave`. Set `interlace` to `Progressive` if they're deinterlaced.
+    pub fn new(status: &VideoSignalStatus, format: FourCC) -> Result<Self> {
+        let Some(frame_rate) = status.frame_rate() else {
+            whatever!("no video signal");
+        };
+        let (width, height) = (status.image_width(), status.image_height());
+        let (aspect_x, aspect_y) = status.aspect_ratio();
+        Ok(Self {
+            width,
+            height,
+            format,
+            frame_rate,
+            interlace: if status.is_interlaced() && !status.is_segmented_frame() {
+                Y4mInterlace::TopFieldFirst
+            } else {
+                Y4mInterlace::Progressive
+            },
+            pixel_aspect: Rational::new(aspect_x * height as u32, aspect_y * width as u32),
+            quantization_range: status.quantization_range(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Layout {
+    Mono,
+    /// Separate Y, U, and V planes, with chroma rows half the stride of luma rows.
+    Planar {
+        swap_uv: bool,
+    },
+    /// A Y plane followed by an interleaved UV plane with the same stride.
+    SemiPlanar {
+        swap_uv: bool,
+    },
+    /// 4:2:2 macropixels of four bytes, given as the offsets of Y0, Y1, U, and V.
+    Packed([usize; 4]),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PixelFormat {
+    layout: Layout,
+    chroma_420: bool,
+    /// Whether samples are 16-bit words with 10 significant bits in the upper bits.
+    high_bit_depth: bool,
+}
+
+impl PixelFormat {
+    fn new(format: FourCC) -> Option<Self> {
+        let (layout, chroma_420, high_bit_depth) = match &format.as_u32().to_le_bytes() {
+            b"GREY" | b"Y800" | b"Y8  " => (Layout::Mono, false, false),
+            b"I420" | b"IYUV" => (Layout::Planar { swap_uv: false }, true, false),
+            b"YV12" => (Layout::Planar { swap_uv: true }, true, false),
+            b"I422" => (Layout::Planar { swap_uv: false }, false, false),
+            b"YV16" => (Layout::Planar { swap_uv: true }, false, false),
+            b"NV12" => (Layout::SemiPlanar { swap_uv: false }, true, false),
+            b"NV21" => (Layout::SemiPlanar { swap_uv: true }, true, false),
+            b"NV16" => (Layout::SemiPlanar { swap_uv: false }, false, false),
+            b"NV61" => (Layout::SemiPlanar { swap_uv: true }, false, false),
+            b"P010" => (Layout::SemiPlanar { swap_uv: false }, true, true),
+            b"P210" => (Layout::SemiPlanar { swap_uv: false }, false, true),
+            b"YUY2" | b"YUYV" => (Layout::Packed([0, 2, 1, 3]), false, false),
+            b"YVYU" => (Layout::Packed([0, 2, 3, 1]), false, false),
+            b"UYVY" => (Layout::Packed([1, 3, 0, 2]), false, false),
+            b"VYUY" => (Layout::Packed([1, 3, 2, 0]), false, false),
+            _ => return None,
+        };
+        Some(Self {
+            layout,
+            chroma_420,
+            high_bit_depth,
+        })
+    }
+
+    fn colorspace(&self) -> &'static str {
+        match (self.layout, self.chroma_420, self.high_bit_depth) {
+            (Layout::Mono, _, _) => "mono",
+            (_, true, false) => "420mpeg2",
+            (_, true, true) => "420p10",
+            (_, false, false) => "422",
+            (_, false, true) => "422p10",
+        }
+    }
+}
+
+/// Writes uncompressed YUV frames as a YUV4MPEG2 stream.
+///
+/// Only YUV formats can be written. Semi-planar and packed formats are rearranged into planes,
+/// and 10-bit formats are shifted down to the least significant bits, but sample values are
+/// otherwise written as captured.
+pub struct Y4mWriter<W: Write> {
+    out: W,
+    header: Y4mHeader,
+    pixel_format: PixelFormat,
+}
+
+impl<W: Write> Y4mWriter<W> {
+    pub fn new(mut out: W, header: Y4mHeader) -> Result<Self> {
+        let Some(pixel_format) = PixelFormat::new(header.format) else {
+            whatever!("{:?} can't be written to y4m", header.format);
+        };
+        let interlace = match header.interlace {
+            Y4mInterlace::Progressive => 'p',
+            Y4mInterlace::TopFieldFirst => 't',
+            Y4mInterlace::BottomFieldFirst => 'b',
+        };
+        let mut line = format!(
+            "YUV4MPEG2 W{} H{} F{}:{} I{} A{}:{} C{}",
+            header.width,
+            header.height,
+            header.frame_rate.num,
+            header.frame_rate.den,
+            interlace,
+            header.pixel_aspect.num,
+            header.pixel_aspect.den,
+            pixel_format.colorspace()
+        );
+        match header.quantization_range {
+            QuantizationRange::Full => line.push_str(" XCOLORRANGE=FULL"),
+            QuantizationRange::Limited => line.push_str(" XCOLORRANGE=LIMITED"),
+            _ => {}
+        }
+        line.push('\n');
+        out.write_all(line.as_bytes())
+            .whatever_context("unable to write y4m header")?;
+        Ok(Self {
+            out,
+            header,
+            pixel_format,
+        })
+    }
+
+    pub fn write_eco_frame(&mut self, frame: &EcoVideoCaptureFrame) -> Result<()> {
+        if frame.is_bottom_up() {
+            whatever!("bottom-up frames can't be written to y4m");
+        }
+        self.write_frame(frame.as_slice(), frame.stride())
+    }
+
+    /// Writes a frame in the header's format with the given stride of the first plane, in bytes.
+    pub fn write_frame(&mut self, data: &[u8], stride: usize) -> Result<()> {
+        let (width, height) = (self.header.width as usize, self.header.height as usize);
+        let (chroma_width, chroma_height) = (
+            width.div_ceil(2),
+            if self.pixel_format.chroma_420 {
+                height.div_ceil(2)
+            } else {
+                height
+            },
+        );
+        let sample_size = if self.pixel_format.high_bit_depth {
+            2
+        } else {
+            1
+        };
+        let chroma_size = chroma_width * chroma_height * sample_size;
+
+        let mut buf = b"FRAME\n".to_vec();
+        match self.pixel_format.layout {
+            Layout::Mono => {
+                copy_plane(&mut buf, data, 0, stride, width, height)?;
+            }
+            Layout::Planar { swap_uv } => {
+                let chroma_stride = stride / 2;
+                let u = stride * height;
+                let v = u + chroma_stride * chroma_height;
+                let (u, v) = if swap_uv { (v, u) } else { (u, v) };
+                copy_plane(&mut buf, data, 0, stride, width, height)?;
+                copy_plane(
+                    &mut buf,
+                    data,
+                    u,
+                    chroma_stride,
+                    chroma_width,
+                    chroma_height,
+                )?;
+                copy_plane(
+                    &mut buf,
+                    data,
+                    v,
+                    chroma_stride,
+                    chroma_width,
+                    chroma_height,
+                )?;
+            }
+            Layout::SemiPlanar { swap_uv } => {
+                copy_plane(&mut buf, data, 0, stride, width * sample_size, height)?;
+                let uv = stride * height;
+                let mut interleaved = Vec::with_capacity(chroma_size * 2);
+                copy_plane(
+                    &mut interleaved,
+                    data,
+                    uv,
+                    stride,
+                    chroma_width * sample_size * 2,
+                    chroma_height,
+                )?;
+                let (u, v) = if swap_uv { (1, 0) } else { (0, 1) };
+                for plane in [u, v] {
+                    for pair in interleaved.chunks_exact(sample_size * 2) {
+                        buf.extend_from_slice(&pair[plane * sample_size..][..sample_size]);
+                    }
+                }
+            }
+            Layout::Packed([y0, y1, u, v]) => {
+                let mut packed = Vec::with_capacity(chroma_width * 4 * height);
+                copy_plane(&mut packed, data, 0, stride, chroma_width * 4, height)?;
+                for row in packed.chunks_exact(chroma_width * 4) {
+                    let luma = row.chunks_exact(4).flat_map(|m| [m[y0], m[y1]]);
+                    buf.extend(luma.take(width));
+                }
+                for offset in [u, v] {
+                    buf.extend(packed.chunks_exact(4).map(|m| m[offset]));
+                }
+            }
+        }
+
+        if self.pixel_format.high_bit_depth {
+            for sample in buf[6..].chunks_exact_mut(2) {
+                let value = u16::from_le_bytes([sample[0], sample[1]]) >> 6;
+                sample.copy_from_slice(&value.to_le_bytes());
+            }
+        }
+        self.out
+            .write_all(&buf)
+            .whatever_context("unable to write y4m frame")
+    }
+
+    /// Flushes and returns the underlying writer.
+    pub fn into_inner(mut self) -> Result<W> {
+        self.out
+            .flush()
+            .whatever_context("unable to flush y4m stream")?;
+        Ok(self.out)
+    }
+}
+
+fn copy_plane(
+    buf: &mut Vec<u8>,
+    data: &[u8],
+    offset: usize,
+    stride: usize,
+    row_size: usize,
+    rows: usize,
+) -> Result<()> {
+    for row in 0..rows {
+        let start = offset + row * stride;
+        let Some(row) = data.get(start..start + row_size) else {
+            whatever!("frame is too small for its format");
+        };
+        buf.extend_from_slice(row);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(format: &str, width: u16, height: u16) -> Y4mHeader {
+        let c: Vec<char> = format.chars().collect();
+        Y4mHeader {
+            width,
+            height,
+            format: FourCC::new(c[0], c[1], c[2], c[3]),
+            frame_rate: Rational::new(30000, 1001),
+            interlace: Y4mInterlace::TopFieldFirst,
+            pixel_aspect: Rational::new(1, 1),
+            quantization_range: QuantizationRange::Limited,
+        }
+    }
+
+    fn write(header: Y4mHeader, data: &[u8], stride: usize) -> Vec<u8> {
+        let mut writer = Y4mWriter::new(Vec::new(), header).unwrap();
+        writer.write_frame(data, stride).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    #[test]
+    fn test_planar() {
+        // A 2x2 I420 frame with 4 bytes of padding per luma row.
+        let data = [1, 2, 0, 0, 3, 4, 0, 0, 5, 0, 6, 0];
+        assert_eq!(
+            write(header("I420", 2, 2), &data, 4),
+            b"YUV4MPEG2 W2 H2 F30000:1001 It A1:1 C420mpeg2 XCOLORRANGE=LIMITED\n\
+              FRAME\n\x01\x02\x03\x04\x05\x06"
+        );
+
+        // The same frame as NV21.
+        let data = [1, 2, 0, 0, 3, 4, 0, 0, 6, 5, 0, 0];
+        assert!(write(header("NV21", 2, 2), &data, 4).ends_with(b"FRAME\n\x01\x02\x03\x04\x05\x06"));
+    }
+
+    #[test]
+    fn test_packed() {
+        // 4x1 UYVY.
+        let data = [10, 1, 11, 2, 12, 3, 13, 4];
+        assert!(write(header("UYVY", 4, 1), &data, 8)
+            .ends_with(b"C422 XCOLORRANGE=LIMITED\nFRAME\n\x01\x02\x03\x04\x0a\x0c\x0b\x0d"));
+    }
+
+    #[test]
+    fn test_high_bit_depth() {
+        // 2x1 P210, with MSB-aligned samples.
+        let sample = |v: u16| (v << 6).to_le_bytes();
+        let data = [sample(1000), sample(1001), sample(500), sample(600)].concat();
+        let output = write(header("P210", 2, 1), &data, 4);
+        assert!(
+            output.ends_with(&[b"FRAME\n".as_slice(), &[232, 3, 233, 3, 244, 1, 88, 2]].concat())
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(Y4mWriter::new(Vec::new(), header("BGR ", 2, 2)).is_err());
+        let mut writer = Y4mWriter::new(Vec::new(), header("I420", 2, 2)).unwrap();
+        assert!(writer.write_frame(&[0; 5], 2).is_err());
+    }
+}